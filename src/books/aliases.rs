@@ -0,0 +1,263 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use bible_lib::Bible;
+use regex::Regex;
+
+use crate::guildfile::GuildSettings;
+
+// the `chapter:verse` part of a reference, used to find where references are in a message
+static REFERENCE_ANCHOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+:\d+").unwrap());
+// words that could be part of a book name
+static BOOK_WORD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[0-9]*[A-Za-z]+\.?|[0-9]+").unwrap());
+
+// the most words a book name (or alias) can have, i.e. `song of solomon`
+const MAX_BOOK_WORDS: usize = 4;
+
+/// Built-in abbreviations for books, keyed by the name bible-lib's detection understands
+/// Abbreviations that are also common words (`es`, `pm`, `re`) are left out so `re: John 3:16` or
+/// `3 pm` aren't read as books
+const BOOK_ALIASES: &[(&str, &[&str])] = &[
+    ("genesis", &["gen", "ge", "gn"]),
+    ("exodus", &["exod", "exo", "ex"]),
+    ("leviticus", &["lev", "le", "lv"]),
+    ("numbers", &["num", "nu", "nm", "nb"]),
+    ("deuteronomy", &["deut", "de", "dt"]),
+    ("joshua", &["josh", "jos", "jsh"]),
+    ("judges", &["judg", "jdg", "jg", "jdgs"]),
+    ("ruth", &["rth", "ru"]),
+    ("ezra", &["ezr"]),
+    ("nehemiah", &["neh", "ne"]),
+    ("esther", &["esth", "est"]),
+    ("job", &["jb"]),
+    ("psalms", &["psalm", "ps", "psa", "pss", "psm", "pslm"]),
+    ("proverbs", &["prov", "pro", "prv", "pr"]),
+    ("ecclesiastes", &["eccles", "eccl", "ecc", "ec", "qoh"]),
+    (
+        "song of solomon",
+        &[
+            "song of songs",
+            "song",
+            "songs",
+            "sos",
+            "song of sol",
+            "canticles",
+            "cant",
+        ],
+    ),
+    ("isaiah", &["isa"]),
+    ("jeremiah", &["jer", "je", "jr"]),
+    ("lamentations", &["lam", "la"]),
+    ("ezekiel", &["ezek", "eze", "ezk"]),
+    ("daniel", &["dan", "da", "dn"]),
+    ("hosea", &["hos", "ho"]),
+    ("joel", &["jl"]),
+    ("amos", &[]),
+    ("obadiah", &["obad", "ob"]),
+    ("jonah", &["jnh", "jon"]),
+    ("micah", &["mic", "mc"]),
+    ("nahum", &["nah", "na"]),
+    ("habakkuk", &["hab", "hb"]),
+    ("zephaniah", &["zeph", "zep", "zp"]),
+    ("haggai", &["hag", "hg"]),
+    ("zechariah", &["zech", "zec", "zc"]),
+    ("malachi", &["mal", "ml"]),
+    ("matthew", &["matt", "mat", "mt"]),
+    ("mark", &["mrk", "mar", "mk", "mr"]),
+    ("luke", &["luk", "lk"]),
+    ("john", &["jhn", "joh", "jn"]),
+    ("acts", &["act", "ac"]),
+    ("romans", &["rom", "ro", "rm"]),
+    ("galatians", &["gal", "ga"]),
+    ("ephesians", &["eph", "ephes"]),
+    ("philippians", &["phil", "php", "pp"]),
+    ("colossians", &["col", "co"]),
+    ("titus", &["tit", "ti"]),
+    ("philemon", &["philem", "phlm", "phm"]),
+    ("hebrews", &["heb"]),
+    ("james", &["jas", "jm"]),
+    ("jude", &["jud", "jd"]),
+    ("revelation", &["rev", "the revelation", "apocalypse"]),
+];
+
+/// Built-in abbreviations for numbered books (`1 samuel`, `2 samuel`, ...)
+/// Each alias is used with every number, so `sam` gives `1 sam` and `2 sam`
+const NUMBERED_BOOK_ALIASES: &[(&str, u8, &[&str])] = &[
+    ("samuel", 2, &["sam", "sa", "sm"]),
+    ("kings", 2, &["kgs", "ki", "kin", "kg"]),
+    ("chronicles", 2, &["chron", "chr", "ch"]),
+    ("corinthians", 2, &["cor", "co"]),
+    ("thessalonians", 2, &["thess", "thes", "th"]),
+    ("timothy", 2, &["tim", "ti", "tm"]),
+    ("peter", 2, &["pet", "pe", "pt", "ptr"]),
+    ("john", 3, &["jhn", "joh", "jn", "jo"]),
+];
+
+/// The full names of every book, as used by bible-lib's detection
+pub fn canonical_books() -> Vec<String> {
+    let mut books: Vec<String> = BOOK_ALIASES
+        .iter()
+        .map(|(book, _)| book.to_string())
+        .collect();
+    for (book, count, _) in NUMBERED_BOOK_ALIASES {
+        for number in 1..=*count {
+            books.push(format!("{} {}", number, book));
+        }
+    }
+    books
+}
+
+/// Lowercases a book name and normalizes the forms people commonly type
+/// `Gen.` becomes `gen`, `I John` / `1st John` / `1John` become `1 john`
+pub fn normalize_book_name(name: &str) -> String {
+    let name = name.to_lowercase().replace('.', " ");
+    let mut words: Vec<String> = name.split_whitespace().map(String::from).collect();
+
+    // ordinals and roman numerals
+    if words.len() > 1 {
+        let number = match words[0].as_str() {
+            "i" | "1st" | "first" => Some("1"),
+            "ii" | "2nd" | "second" => Some("2"),
+            "iii" | "3rd" | "third" => Some("3"),
+            _ => None,
+        };
+        if let Some(number) = number {
+            words[0] = number.to_string();
+        }
+    }
+
+    // split a number stuck to the book name, like `1john`
+    if let Some(first) = words.first().cloned() {
+        if let Some(index) = first.find(|c: char| !c.is_ascii_digit()) {
+            if index > 0 {
+                let (number, rest) = first.split_at(index);
+                words.splice(0..1, [number.to_string(), rest.to_string()]);
+            }
+        }
+    }
+
+    words.join(" ")
+}
+
+/// Maps abbreviations and alternate names to book names
+/// Built-in aliases can be extended from the config file and per guild
+#[derive(Clone)]
+pub struct BookAliases {
+    // normalized alias -> name bible-lib's detection understands
+    aliases: HashMap<String, String>,
+}
+
+impl BookAliases {
+    pub fn new() -> Self {
+        let mut aliases = HashMap::new();
+
+        for (book, book_aliases) in BOOK_ALIASES {
+            aliases.insert(book.to_string(), book.to_string());
+            for alias in *book_aliases {
+                aliases.insert(alias.to_string(), book.to_string());
+            }
+        }
+
+        for (book, count, book_aliases) in NUMBERED_BOOK_ALIASES {
+            for number in 1..=*count {
+                let full = format!("{} {}", number, book);
+                aliases.insert(full.clone(), full.clone());
+                for alias in *book_aliases {
+                    aliases.insert(format!("{} {}", number, alias), full.clone());
+                }
+            }
+        }
+
+        Self { aliases }
+    }
+
+    /// Add aliases on top of the current ones (alias -> book)
+    /// The book can itself be any name or alias that is already known
+    pub fn extend(&mut self, extra: &HashMap<String, String>) {
+        for (alias, book) in extra {
+            let book = self
+                .canonical_name(book)
+                .unwrap_or_else(|| normalize_book_name(book));
+            self.aliases.insert(normalize_book_name(alias), book);
+        }
+    }
+
+    /// A copy of these aliases with a guild's custom aliases added
+    pub fn with_guild(&self, guild: &GuildSettings) -> Self {
+        let mut aliases = self.clone();
+        aliases.extend(guild.get_book_aliases());
+        aliases
+    }
+
+    /// Every known alias and the book it refers to
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.aliases.iter()
+    }
+
+    /// The full name of a book from any of its aliases, if it is known
+    pub fn canonical_name(&self, name: &str) -> Option<String> {
+        self.aliases.get(&normalize_book_name(name)).cloned()
+    }
+
+    /// Resolve a book name or alias to the name used by the loaded bible
+    pub fn resolve(&self, name: &str, bible: &Bible) -> Option<String> {
+        let book = self
+            .canonical_name(name)
+            .unwrap_or_else(|| normalize_book_name(name));
+
+        if bible.verses.contains_key(&book) {
+            return Some(book);
+        }
+
+        // some translations use `psalm` instead of `psalms`
+        let alternate = match book.strip_suffix('s') {
+            Some(singular) => singular.to_string(),
+            None => format!("{}s", book),
+        };
+        if bible.verses.contains_key(&alternate) {
+            return Some(alternate);
+        }
+
+        None
+    }
+
+    /// Rewrite the book names of references in a message to their full names
+    /// so they can be detected, i.e. `Jn 3:16` becomes `john 3:16`
+    pub fn normalize_text(&self, text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for anchor in REFERENCE_ANCHOR.find_iter(text) {
+            let before = &text[last..anchor.start()];
+            let words: Vec<usize> = BOOK_WORD.find_iter(before).map(|w| w.start()).collect();
+
+            // try the longest run of words before the chapter first, i.e. `song of songs` before `songs`
+            let mut replaced = false;
+            for count in (1..=words.len().min(MAX_BOOK_WORDS)).rev() {
+                let start = words[words.len() - count];
+                if let Some(book) = self.canonical_name(&before[start..]) {
+                    normalized.push_str(&before[..start]);
+                    normalized.push_str(&book);
+                    normalized.push(' ');
+                    replaced = true;
+                    break;
+                }
+            }
+            if !replaced {
+                normalized.push_str(before);
+            }
+
+            normalized.push_str(anchor.as_str());
+            last = anchor.end();
+        }
+        normalized.push_str(&text[last..]);
+
+        normalized
+    }
+}
+
+impl Default for BookAliases {
+    fn default() -> Self {
+        Self::new()
+    }
+}
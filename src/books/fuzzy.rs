@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use bible_lib::{Bible, BibleLookup};
+use levenshtein::levenshtein;
+
+use crate::books::aliases::{normalize_book_name, BookAliases};
+
+// the most suggestions given for a misspelled book
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Suggest the books closest to a misspelled book name, best match first
+/// Returns the names used by the loaded bible
+pub fn suggest_books(name: &str, bible: &Bible, aliases: &BookAliases) -> Vec<String> {
+    let name = normalize_book_name(name);
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    // allow roughly one typo for every three letters
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    // compare against every book and alias, keeping the best distance per book
+    let mut distances: HashMap<String, usize> = HashMap::new();
+    let candidates = bible
+        .get_books()
+        .into_iter()
+        .map(|book| (book.clone(), book))
+        .chain(
+            aliases
+                .iter()
+                .map(|(alias, book)| (alias.clone(), book.clone())),
+        );
+    for (candidate, book) in candidates {
+        let distance = levenshtein(&name, &candidate);
+        if distance > max_distance {
+            continue;
+        }
+        let Some(book) = aliases.resolve(&book, bible) else {
+            continue;
+        };
+        let best = distances.entry(book).or_insert(distance);
+        *best = (*best).min(distance);
+    }
+
+    // closest first, ties in canonical order
+    let order = bible.get_sorted_books();
+    let mut suggestions: Vec<(String, usize)> = distances.into_iter().collect();
+    suggestions.sort_by_key(|(book, distance)| {
+        (
+            *distance,
+            order.iter().position(|b| b == book).unwrap_or(usize::MAX),
+        )
+    });
+
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(book, _)| book)
+        .collect()
+}
+
+/// Complete a partially typed book name, for autocomplete
+/// Books starting with the text come first, then books with a matching alias, then close matches
+pub fn complete_books(
+    partial: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+    limit: usize,
+) -> Vec<String> {
+    let partial = normalize_book_name(partial);
+    let books = bible.get_sorted_books();
+
+    let mut completions: Vec<String> = books
+        .iter()
+        .filter(|book| book.starts_with(&partial))
+        .cloned()
+        .collect();
+
+    // aliases like `jn` or `1 cor`
+    let mut from_aliases: Vec<String> = aliases
+        .iter()
+        .filter(|(alias, _)| alias.starts_with(&partial))
+        .filter_map(|(_, book)| aliases.resolve(book, bible))
+        .collect();
+    from_aliases.sort_by_key(|book| books.iter().position(|b| b == book));
+    completions.extend(from_aliases);
+
+    // anything containing the text, then typos
+    completions.extend(books.iter().filter(|book| book.contains(&partial)).cloned());
+    completions.extend(suggest_books(&partial, bible, aliases));
+
+    let mut unique = Vec::new();
+    for book in completions {
+        if !unique.contains(&book) {
+            unique.push(book);
+        }
+    }
+    unique.truncate(limit);
+    unique
+}
+
+/// Explains why a reference could not be found
+/// Returns the explanation and, if the book doesn't exist, the books it may have meant
+pub fn explain_invalid_reference(
+    lookup: &BibleLookup,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> (String, Vec<String>) {
+    let book = BibleLookup::capitalize_book(&lookup.book);
+
+    let Ok(max_chapter) = bible.get_max_chapter(&lookup.book) else {
+        let suggestions = suggest_books(&lookup.book, bible, aliases);
+        let explanation = match suggestions.as_slice() {
+            [] => format!("Book not found: {}", book),
+            [only] => format!(
+                "Book not found: {}. Did you mean **{}**?",
+                book,
+                BibleLookup::capitalize_book(only)
+            ),
+            // name them all, since not every caller shows the suggestions as buttons
+            [rest @ .., last] => format!(
+                "Book not found: {}. Did you mean {} or **{}**?",
+                book,
+                rest.iter()
+                    .map(|suggestion| format!("**{}**", BibleLookup::capitalize_book(suggestion)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                BibleLookup::capitalize_book(last)
+            ),
+        };
+        return (explanation, suggestions);
+    };
+
+    let Ok(max_verse) = bible.get_max_verse(&lookup.book, lookup.chapter) else {
+        return (
+            format!(
+                "Chapter not found: {} {} ({} only has {} chapters)",
+                book, lookup.chapter, book, max_chapter
+            ),
+            Vec::new(),
+        );
+    };
+
+    (
+        format!(
+            "Verse not found: {} ({} {} only has {} verses)",
+            lookup, book, lookup.chapter, max_verse
+        ),
+        Vec::new(),
+    )
+}
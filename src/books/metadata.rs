@@ -0,0 +1,711 @@
+use std::fmt::{Display, Formatter};
+
+/// The testament a book belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Testament {
+    Old,
+    New,
+}
+
+impl Display for Testament {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Testament::Old => write!(f, "Old Testament"),
+            Testament::New => write!(f, "New Testament"),
+        }
+    }
+}
+
+/// The traditional section of the canon a book is grouped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Law,
+    History,
+    Wisdom,
+    MajorProphets,
+    MinorProphets,
+    Gospels,
+    PaulineEpistles,
+    GeneralEpistles,
+    Prophecy,
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Section::Law => write!(f, "Law"),
+            Section::History => write!(f, "History"),
+            Section::Wisdom => write!(f, "Wisdom and Poetry"),
+            Section::MajorProphets => write!(f, "Major Prophets"),
+            Section::MinorProphets => write!(f, "Minor Prophets"),
+            Section::Gospels => write!(f, "Gospels"),
+            Section::PaulineEpistles => write!(f, "Pauline Epistles"),
+            Section::GeneralEpistles => write!(f, "General Epistles"),
+            Section::Prophecy => write!(f, "Prophecy"),
+        }
+    }
+}
+
+/// The groups of sections users can filter by (display name, code, sections)
+pub const CATEGORIES: &[(&str, &str, &[Section])] = &[
+    ("Law", "law", &[Section::Law]),
+    ("History", "history", &[Section::History]),
+    ("Wisdom", "wisdom", &[Section::Wisdom]),
+    (
+        "Prophets",
+        "prophets",
+        &[
+            Section::MajorProphets,
+            Section::MinorProphets,
+            Section::Prophecy,
+        ],
+    ),
+    ("Gospels", "gospels", &[Section::Gospels]),
+    (
+        "Epistles",
+        "epistles",
+        &[Section::PaulineEpistles, Section::GeneralEpistles],
+    ),
+];
+
+/// The sections in a category from `CATEGORIES`
+pub fn category_sections(code: &str) -> Option<&'static [Section]> {
+    CATEGORIES
+        .iter()
+        .find(|(_, category, _)| category.eq_ignore_ascii_case(code))
+        .map(|(_, _, sections)| *sections)
+}
+
+/// Facts about a book of the Bible
+#[derive(Debug)]
+pub struct BookInfo {
+    /// the full name, as used by bible-lib's detection
+    pub name: &'static str,
+    /// how many chapters it has in the translations bible-lib provides
+    pub chapters: u32,
+    pub testament: Testament,
+    /// the genre of the book, by where it sits in the canon
+    pub section: Section,
+    /// the traditional author
+    pub author: &'static str,
+    /// the approximate date it was written
+    pub date: &'static str,
+    /// a one-line summary
+    pub summary: &'static str,
+}
+
+/// Every book in canonical order
+pub const BOOKS: &[BookInfo] = &[
+    BookInfo {
+        name: "genesis",
+        chapters: 50,
+        testament: Testament::Old,
+        section: Section::Law,
+        author: "Moses",
+        date: "c. 1440–1400 BC",
+        summary: "The creation of the world, the fall, the flood and the patriarchs Abraham, Isaac, Jacob and Joseph.",
+    },
+    BookInfo {
+        name: "exodus",
+        chapters: 40,
+        testament: Testament::Old,
+        section: Section::Law,
+        author: "Moses",
+        date: "c. 1440–1400 BC",
+        summary: "God delivers Israel from slavery in Egypt and gives the law and the tabernacle at Sinai.",
+    },
+    BookInfo {
+        name: "leviticus",
+        chapters: 27,
+        testament: Testament::Old,
+        section: Section::Law,
+        author: "Moses",
+        date: "c. 1440–1400 BC",
+        summary: "Laws for sacrifice, priesthood and holy living for a people set apart to God.",
+    },
+    BookInfo {
+        name: "numbers",
+        chapters: 36,
+        testament: Testament::Old,
+        section: Section::Law,
+        author: "Moses",
+        date: "c. 1440–1400 BC",
+        summary: "Israel's census and forty years of wandering in the wilderness because of unbelief.",
+    },
+    BookInfo {
+        name: "deuteronomy",
+        chapters: 34,
+        testament: Testament::Old,
+        section: Section::Law,
+        author: "Moses",
+        date: "c. 1400 BC",
+        summary: "Moses' farewell sermons restating the law before Israel enters the promised land.",
+    },
+    BookInfo {
+        name: "joshua",
+        chapters: 24,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Joshua",
+        date: "c. 1400–1370 BC",
+        summary: "Israel conquers and divides the land of Canaan under Joshua.",
+    },
+    BookInfo {
+        name: "judges",
+        chapters: 21,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Samuel (traditionally)",
+        date: "c. 1050–1000 BC",
+        summary: "Cycles of sin, oppression and deliverance through judges like Deborah, Gideon and Samson.",
+    },
+    BookInfo {
+        name: "ruth",
+        chapters: 4,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Samuel (traditionally)",
+        date: "c. 1000 BC",
+        summary: "A Moabite widow's loyalty and her redemption by Boaz, ancestor of David.",
+    },
+    BookInfo {
+        name: "1 samuel",
+        chapters: 31,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Samuel, Nathan and Gad",
+        date: "c. 930 BC",
+        summary: "The last judge Samuel, the rise and fall of Saul, and the anointing of David.",
+    },
+    BookInfo {
+        name: "2 samuel",
+        chapters: 24,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Nathan and Gad",
+        date: "c. 930 BC",
+        summary: "David's reign over Israel, God's covenant with him, and the cost of his sin.",
+    },
+    BookInfo {
+        name: "1 kings",
+        chapters: 22,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Jeremiah (traditionally)",
+        date: "c. 560–540 BC",
+        summary: "Solomon's reign and temple, the divided kingdom, and the ministry of Elijah.",
+    },
+    BookInfo {
+        name: "2 kings",
+        chapters: 25,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Jeremiah (traditionally)",
+        date: "c. 560–540 BC",
+        summary: "The decline of Israel and Judah, Elisha's ministry, and the exiles to Assyria and Babylon.",
+    },
+    BookInfo {
+        name: "1 chronicles",
+        chapters: 29,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Ezra (traditionally)",
+        date: "c. 450–425 BC",
+        summary: "Genealogies from Adam and the reign of David, focused on worship and the temple.",
+    },
+    BookInfo {
+        name: "2 chronicles",
+        chapters: 36,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Ezra (traditionally)",
+        date: "c. 450–425 BC",
+        summary: "The kings of Judah from Solomon to the exile, measured by their faithfulness.",
+    },
+    BookInfo {
+        name: "ezra",
+        chapters: 10,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Ezra",
+        date: "c. 440 BC",
+        summary: "The return from exile, the rebuilding of the temple, and the renewal of the law.",
+    },
+    BookInfo {
+        name: "nehemiah",
+        chapters: 13,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Nehemiah",
+        date: "c. 430 BC",
+        summary: "Nehemiah leads the rebuilding of Jerusalem's walls and the renewal of the covenant.",
+    },
+    BookInfo {
+        name: "esther",
+        chapters: 10,
+        testament: Testament::Old,
+        section: Section::History,
+        author: "Unknown",
+        date: "c. 460–350 BC",
+        summary: "A Jewish queen in Persia risks her life to save her people from destruction.",
+    },
+    BookInfo {
+        name: "job",
+        chapters: 42,
+        testament: Testament::Old,
+        section: Section::Wisdom,
+        author: "Unknown",
+        date: "Uncertain",
+        summary: "A righteous man's suffering and his wrestling with God's justice and wisdom.",
+    },
+    BookInfo {
+        name: "psalms",
+        chapters: 150,
+        testament: Testament::Old,
+        section: Section::Wisdom,
+        author: "David and others",
+        date: "c. 1440–450 BC",
+        summary: "Israel's songbook of praise, lament, thanksgiving and prayer.",
+    },
+    BookInfo {
+        name: "proverbs",
+        chapters: 31,
+        testament: Testament::Old,
+        section: Section::Wisdom,
+        author: "Solomon and others",
+        date: "c. 950–700 BC",
+        summary: "Short sayings of practical wisdom rooted in the fear of the Lord.",
+    },
+    BookInfo {
+        name: "ecclesiastes",
+        chapters: 12,
+        testament: Testament::Old,
+        section: Section::Wisdom,
+        author: "Solomon (traditionally)",
+        date: "c. 935 BC",
+        summary: "The Preacher searches for meaning \"under the sun\" and finds it only in God.",
+    },
+    BookInfo {
+        name: "song of solomon",
+        chapters: 8,
+        testament: Testament::Old,
+        section: Section::Wisdom,
+        author: "Solomon",
+        date: "c. 965 BC",
+        summary: "Love poetry celebrating the love between a bride and her groom.",
+    },
+    BookInfo {
+        name: "isaiah",
+        chapters: 66,
+        testament: Testament::Old,
+        section: Section::MajorProphets,
+        author: "Isaiah",
+        date: "c. 740–680 BC",
+        summary: "Judgment and comfort for Judah, and prophecies of the coming Servant and Messiah.",
+    },
+    BookInfo {
+        name: "jeremiah",
+        chapters: 52,
+        testament: Testament::Old,
+        section: Section::MajorProphets,
+        author: "Jeremiah",
+        date: "c. 627–580 BC",
+        summary: "The weeping prophet warns Judah before Babylon's conquest and promises a new covenant.",
+    },
+    BookInfo {
+        name: "lamentations",
+        chapters: 5,
+        testament: Testament::Old,
+        section: Section::MajorProphets,
+        author: "Jeremiah (traditionally)",
+        date: "c. 586 BC",
+        summary: "Poems of grief over the destruction of Jerusalem, with hope in God's faithfulness.",
+    },
+    BookInfo {
+        name: "ezekiel",
+        chapters: 48,
+        testament: Testament::Old,
+        section: Section::MajorProphets,
+        author: "Ezekiel",
+        date: "c. 593–570 BC",
+        summary: "Visions of God's glory, judgment on Jerusalem, and the restoration of Israel.",
+    },
+    BookInfo {
+        name: "daniel",
+        chapters: 12,
+        testament: Testament::Old,
+        section: Section::MajorProphets,
+        author: "Daniel",
+        date: "c. 605–530 BC",
+        summary: "Daniel's faithfulness in Babylon and visions of the kingdoms to come.",
+    },
+    BookInfo {
+        name: "hosea",
+        chapters: 14,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Hosea",
+        date: "c. 755–715 BC",
+        summary: "Hosea's marriage to an unfaithful wife pictures God's love for unfaithful Israel.",
+    },
+    BookInfo {
+        name: "joel",
+        chapters: 3,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Joel",
+        date: "Uncertain",
+        summary: "A plague of locusts foreshadows the day of the Lord and the outpouring of the Spirit.",
+    },
+    BookInfo {
+        name: "amos",
+        chapters: 9,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Amos",
+        date: "c. 760–750 BC",
+        summary: "A shepherd prophet condemns Israel's injustice and empty religion.",
+    },
+    BookInfo {
+        name: "obadiah",
+        chapters: 1,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Obadiah",
+        date: "c. 585 BC",
+        summary: "Judgment on Edom for its pride and its violence against Judah.",
+    },
+    BookInfo {
+        name: "jonah",
+        chapters: 4,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Jonah",
+        date: "c. 785–760 BC",
+        summary: "A reluctant prophet is sent to Nineveh and learns of God's mercy to the nations.",
+    },
+    BookInfo {
+        name: "micah",
+        chapters: 7,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Micah",
+        date: "c. 735–700 BC",
+        summary: "Judgment for injustice and the promise of a ruler born in Bethlehem.",
+    },
+    BookInfo {
+        name: "nahum",
+        chapters: 3,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Nahum",
+        date: "c. 660–615 BC",
+        summary: "The coming fall of Nineveh, capital of Assyria.",
+    },
+    BookInfo {
+        name: "habakkuk",
+        chapters: 3,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Habakkuk",
+        date: "c. 609–605 BC",
+        summary: "A prophet questions God about evil and learns that the righteous live by faith.",
+    },
+    BookInfo {
+        name: "zephaniah",
+        chapters: 3,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Zephaniah",
+        date: "c. 640–620 BC",
+        summary: "The day of the Lord brings judgment on Judah and the nations, then restoration.",
+    },
+    BookInfo {
+        name: "haggai",
+        chapters: 2,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Haggai",
+        date: "c. 520 BC",
+        summary: "The returned exiles are urged to finish rebuilding the temple.",
+    },
+    BookInfo {
+        name: "zechariah",
+        chapters: 14,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Zechariah",
+        date: "c. 520–480 BC",
+        summary: "Visions encouraging the rebuilding of the temple and prophecies of the coming King.",
+    },
+    BookInfo {
+        name: "malachi",
+        chapters: 4,
+        testament: Testament::Old,
+        section: Section::MinorProphets,
+        author: "Malachi",
+        date: "c. 430 BC",
+        summary: "God calls a careless people back to faithful worship before the messenger comes.",
+    },
+    BookInfo {
+        name: "matthew",
+        chapters: 28,
+        testament: Testament::New,
+        section: Section::Gospels,
+        author: "Matthew",
+        date: "c. AD 50–70",
+        summary: "Jesus as the promised Messiah and King, fulfilling the Old Testament.",
+    },
+    BookInfo {
+        name: "mark",
+        chapters: 16,
+        testament: Testament::New,
+        section: Section::Gospels,
+        author: "Mark",
+        date: "c. AD 55–65",
+        summary: "A fast-paced account of Jesus as the suffering Servant and Son of God.",
+    },
+    BookInfo {
+        name: "luke",
+        chapters: 24,
+        testament: Testament::New,
+        section: Section::Gospels,
+        author: "Luke",
+        date: "c. AD 60–62",
+        summary: "An orderly account of Jesus as the Savior of all people, especially the lost and the poor.",
+    },
+    BookInfo {
+        name: "john",
+        chapters: 21,
+        testament: Testament::New,
+        section: Section::Gospels,
+        author: "John",
+        date: "c. AD 85–95",
+        summary: "Signs and teachings showing that Jesus is the Son of God, so that readers may believe.",
+    },
+    BookInfo {
+        name: "acts",
+        chapters: 28,
+        testament: Testament::New,
+        section: Section::History,
+        author: "Luke",
+        date: "c. AD 62",
+        summary: "The spread of the gospel from Jerusalem to Rome through the Spirit-led church.",
+    },
+    BookInfo {
+        name: "romans",
+        chapters: 16,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 57",
+        summary: "The gospel of righteousness by faith in Christ for Jew and Gentile alike.",
+    },
+    BookInfo {
+        name: "1 corinthians",
+        chapters: 16,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 55",
+        summary: "Correction for a divided church on unity, morality, worship, love and the resurrection.",
+    },
+    BookInfo {
+        name: "2 corinthians",
+        chapters: 13,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 56",
+        summary: "Paul defends his ministry and writes of strength in weakness and generous giving.",
+    },
+    BookInfo {
+        name: "galatians",
+        chapters: 6,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 49–55",
+        summary: "Freedom in Christ: justification by faith, not by works of the law.",
+    },
+    BookInfo {
+        name: "ephesians",
+        chapters: 6,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 60–62",
+        summary: "The church as one body in Christ and the new life believers are called to.",
+    },
+    BookInfo {
+        name: "philippians",
+        chapters: 4,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 61",
+        summary: "A joyful letter from prison about knowing Christ and contentment in all things.",
+    },
+    BookInfo {
+        name: "colossians",
+        chapters: 4,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 60–62",
+        summary: "The supremacy and sufficiency of Christ over every power and philosophy.",
+    },
+    BookInfo {
+        name: "1 thessalonians",
+        chapters: 5,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 51",
+        summary: "Encouragement for a young church and hope in the return of Christ.",
+    },
+    BookInfo {
+        name: "2 thessalonians",
+        chapters: 3,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 51–52",
+        summary: "Clarifications about the day of the Lord and a call to keep working faithfully.",
+    },
+    BookInfo {
+        name: "1 timothy",
+        chapters: 6,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 62–64",
+        summary: "Instructions to Timothy on church leadership, sound doctrine and godliness.",
+    },
+    BookInfo {
+        name: "2 timothy",
+        chapters: 4,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 66–67",
+        summary: "Paul's final letter, charging Timothy to guard the gospel and endure.",
+    },
+    BookInfo {
+        name: "titus",
+        chapters: 3,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 62–64",
+        summary: "Appointing elders in Crete and living out sound doctrine through good works.",
+    },
+    BookInfo {
+        name: "philemon",
+        chapters: 1,
+        testament: Testament::New,
+        section: Section::PaulineEpistles,
+        author: "Paul",
+        date: "c. AD 60–62",
+        summary: "Paul appeals for Onesimus, a runaway slave, to be welcomed as a brother.",
+    },
+    BookInfo {
+        name: "hebrews",
+        chapters: 13,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "Unknown",
+        date: "c. AD 64–68",
+        summary: "Christ as the greater high priest and the fulfillment of the old covenant.",
+    },
+    BookInfo {
+        name: "james",
+        chapters: 5,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "James",
+        date: "c. AD 45–50",
+        summary: "Practical wisdom on trials, speech and a faith that is shown by works.",
+    },
+    BookInfo {
+        name: "1 peter",
+        chapters: 5,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "Peter",
+        date: "c. AD 62–64",
+        summary: "Hope and holy living for believers suffering as strangers in the world.",
+    },
+    BookInfo {
+        name: "2 peter",
+        chapters: 3,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "Peter",
+        date: "c. AD 64–67",
+        summary: "Warnings against false teachers and a reminder of the Lord's return.",
+    },
+    BookInfo {
+        name: "1 john",
+        chapters: 5,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "John",
+        date: "c. AD 85–95",
+        summary: "Assurance of eternal life through walking in the light, truth and love.",
+    },
+    BookInfo {
+        name: "2 john",
+        chapters: 1,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "John",
+        date: "c. AD 85–95",
+        summary: "A short call to walk in love and reject deceivers.",
+    },
+    BookInfo {
+        name: "3 john",
+        chapters: 1,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "John",
+        date: "c. AD 85–95",
+        summary: "Praise for Gaius's hospitality and a rebuke of Diotrephes.",
+    },
+    BookInfo {
+        name: "jude",
+        chapters: 1,
+        testament: Testament::New,
+        section: Section::GeneralEpistles,
+        author: "Jude",
+        date: "c. AD 65–80",
+        summary: "A call to contend for the faith against ungodly false teachers.",
+    },
+    BookInfo {
+        name: "revelation",
+        chapters: 22,
+        testament: Testament::New,
+        section: Section::Prophecy,
+        author: "John",
+        date: "c. AD 95",
+        summary: "Visions of Christ's victory, judgment on evil, and the new heaven and new earth.",
+    },
+];
+
+/// Look up a book by its full name
+/// Accepts the names used by any translation, i.e. `psalm` or `psalms`
+pub fn book_info(name: &str) -> Option<&'static BookInfo> {
+    let name = name.to_lowercase();
+    BOOKS
+        .iter()
+        .find(|info| info.name == name || info.name.strip_suffix('s') == Some(name.as_str()))
+}
+
+/// Where a book comes in canonical order, starting at 1
+pub fn canonical_position(name: &str) -> Option<usize> {
+    let info = book_info(name)?;
+    BOOKS
+        .iter()
+        .position(|book| book.name == info.name)
+        .map(|index| index + 1)
+}
@@ -0,0 +1,3 @@
+pub mod aliases;
+pub mod fuzzy;
+pub mod metadata;
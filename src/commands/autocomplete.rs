@@ -0,0 +1,180 @@
+use crate::books::{aliases::BookAliases, fuzzy::complete_books};
+use crate::commands::bookmark::bookmark_choices;
+use crate::commands::collection::{collection_choices, passage_choices};
+use crate::commands::memory::card_choices;
+use crate::commands::prayer::request_choices;
+use crate::dictionary::Dictionary;
+use crate::nay;
+use crate::topics::TopicalIndex;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    CommandInteraction, Context, CreateAutocompleteResponse, CreateInteractionResponse,
+    ResolvedOption, ResolvedValue,
+};
+
+// discord shows at most 25 choices
+const MAX_CHOICES: usize = 25;
+
+/// Responds to autocomplete for any command option named `book`, `chapter`, `topic`, `term`, `bookmark`,
+/// `collection`, `passage`, `card` or `request`
+pub async fn run(
+    ctx: &Context,
+    autocomplete: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+    topics: &TopicalIndex,
+    dictionary: &Dictionary,
+) {
+    let Some(focused) = autocomplete.data.autocomplete() else {
+        return;
+    };
+
+    let response = match focused.name {
+        "book" => book_choices(focused.value, bible, aliases),
+        "chapter" => {
+            let options = autocomplete.data.options();
+            let book = string_option(&options, "book").unwrap_or_default();
+            chapter_choices(book, focused.value, bible, aliases)
+        }
+        "topic" => topics
+            .complete(focused.value, MAX_CHOICES)
+            .into_iter()
+            .fold(CreateAutocompleteResponse::new(), |response, topic| {
+                response.add_string_choice(topic, topic)
+            }),
+        "bookmark" => bookmark_choices(
+            autocomplete.user.id,
+            focused.value,
+            bible,
+            aliases,
+            MAX_CHOICES,
+        )
+        .into_iter()
+        .fold(
+            CreateAutocompleteResponse::new(),
+            |response, (label, number)| response.add_string_choice(label, number),
+        ),
+        "card" => card_choices(
+            autocomplete.user.id,
+            focused.value,
+            bible,
+            aliases,
+            MAX_CHOICES,
+        )
+        .into_iter()
+        .fold(
+            CreateAutocompleteResponse::new(),
+            |response, (label, number)| response.add_string_choice(label, number),
+        ),
+        "collection" => match autocomplete.guild_id {
+            Some(guild) => collection_choices(&guild, focused.value, MAX_CHOICES)
+                .into_iter()
+                .fold(CreateAutocompleteResponse::new(), |response, name| {
+                    response.add_string_choice(name.clone(), name)
+                }),
+            None => CreateAutocompleteResponse::new(),
+        },
+        "passage" => {
+            let options = autocomplete.data.options();
+            let collection = string_option(&options, "collection").unwrap_or_default();
+            match autocomplete.guild_id {
+                Some(guild) => passage_choices(
+                    &guild,
+                    collection,
+                    focused.value,
+                    bible,
+                    aliases,
+                    MAX_CHOICES,
+                )
+                .into_iter()
+                .fold(
+                    CreateAutocompleteResponse::new(),
+                    |response, (label, number)| response.add_string_choice(label, number),
+                ),
+                None => CreateAutocompleteResponse::new(),
+            }
+        }
+        "request" => match autocomplete.guild_id {
+            Some(guild) => {
+                let can_manage = autocomplete
+                    .member
+                    .as_ref()
+                    .and_then(|member| member.permissions)
+                    .is_some_and(|permissions| permissions.manage_guild());
+                request_choices(
+                    &guild,
+                    autocomplete.user.id,
+                    can_manage,
+                    focused.value,
+                    MAX_CHOICES,
+                )
+                .into_iter()
+                .fold(
+                    CreateAutocompleteResponse::new(),
+                    |response, (label, id)| response.add_string_choice(label, id),
+                )
+            }
+            None => CreateAutocompleteResponse::new(),
+        },
+        "term" => dictionary
+            .complete(focused.value, MAX_CHOICES)
+            .into_iter()
+            .fold(CreateAutocompleteResponse::new(), |response, term| {
+                response.add_string_choice(term, term)
+            }),
+        _ => CreateAutocompleteResponse::new(),
+    };
+
+    if let Err(e) = autocomplete
+        .create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response))
+        .await
+    {
+        nay!("Failed to respond to autocomplete: {}", e);
+    }
+}
+
+/// The value of a string option, even if it is still being typed
+/// Options of a subcommand are nested inside it, so those are searched too
+fn string_option<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|option| match &option.value {
+        ResolvedValue::SubCommand(options) => string_option(options, name),
+        ResolvedValue::String(value) if option.name == name => Some(*value),
+        ResolvedValue::Autocomplete { value, .. } if option.name == name => Some(*value),
+        _ => None,
+    })
+}
+
+fn book_choices(partial: &str, bible: &Bible, aliases: &BookAliases) -> CreateAutocompleteResponse {
+    complete_books(partial, bible, aliases, MAX_CHOICES)
+        .into_iter()
+        .fold(CreateAutocompleteResponse::new(), |response, book| {
+            response.add_string_choice(BibleLookup::capitalize_book(&book), book)
+        })
+}
+
+fn chapter_choices(
+    book: &str,
+    partial: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> CreateAutocompleteResponse {
+    let Some(book) = aliases.resolve(book, bible) else {
+        return CreateAutocompleteResponse::new();
+    };
+    let Ok(mut chapters) = bible.get_chapters(&book) else {
+        return CreateAutocompleteResponse::new();
+    };
+    chapters.sort();
+
+    let partial = partial.trim();
+    chapters
+        .into_iter()
+        .filter(|chapter| chapter.to_string().starts_with(partial))
+        .take(MAX_CHOICES)
+        .fold(CreateAutocompleteResponse::new(), |response, chapter| {
+            response.add_int_choice(
+                format!("{} {}", BibleLookup::capitalize_book(&book), chapter),
+                chapter as i64,
+            )
+        })
+}
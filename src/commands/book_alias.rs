@@ -0,0 +1,160 @@
+use crate::books::aliases::{normalize_book_name, BookAliases};
+use crate::discord_helpers::command_response;
+use crate::guildfile::GuildSettings;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, GuildId,
+    Permissions, ResolvedOption, ResolvedValue,
+};
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    command: &CommandInteraction,
+    guild: &GuildId,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(action),
+        ..
+    }) = options.first()
+    else {
+        // error message
+        command_response(
+            ctx,
+            command,
+            "You must specify an action: `add`, `remove`, or `list`",
+        )
+        .await;
+        return;
+    };
+
+    // optional values
+    let mut alias = None;
+    let mut book = None;
+    for option in options {
+        if let ResolvedValue::String(value) = option.value {
+            match option.name {
+                "alias" => alias = Some(value),
+                "book" => book = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    let mut guild_file = GuildSettings::get(guild);
+
+    match action.to_lowercase().as_str() {
+        "add" => {
+            let (Some(alias), Some(book)) = (alias, book) else {
+                command_response(ctx, command, "You must specify an alias and a book to add!")
+                    .await;
+                return;
+            };
+
+            // the alias has to point to a real book
+            let aliases = aliases.with_guild(&guild_file);
+            let Some(resolved) = aliases.resolve(book, bible) else {
+                command_response(ctx, command, format!("Book not found: {}", book)).await;
+                return;
+            };
+
+            let alias = normalize_book_name(alias);
+            let canonical = aliases
+                .canonical_name(book)
+                .unwrap_or_else(|| resolved.clone());
+            guild_file.add_book_alias(alias.clone(), canonical);
+            command_response(
+                ctx,
+                command,
+                format!(
+                    "`{}` now refers to {}!",
+                    alias,
+                    BibleLookup::capitalize_book(&resolved)
+                ),
+            )
+            .await;
+        }
+        "remove" => {
+            let Some(alias) = alias else {
+                command_response(ctx, command, "You must specify an alias to remove!").await;
+                return;
+            };
+
+            let alias = normalize_book_name(alias);
+            if guild_file.remove_book_alias(&alias) {
+                command_response(ctx, command, format!("`{}` has been removed.", alias)).await;
+            } else {
+                command_response(
+                    ctx,
+                    command,
+                    format!("`{}` is not one of this server's aliases.", alias),
+                )
+                .await;
+            }
+        }
+        "list" => {
+            let mut list = guild_file
+                .get_book_aliases()
+                .iter()
+                .map(|(alias, book)| {
+                    format!("`{}` → {}", alias, BibleLookup::capitalize_book(book))
+                })
+                .collect::<Vec<String>>();
+            list.sort();
+
+            if list.is_empty() {
+                command_response(ctx, command, "This server has no custom book aliases.").await;
+            } else {
+                command_response(
+                    ctx,
+                    command,
+                    format!("This server's book aliases:\n{}", list.join("\n")),
+                )
+                .await;
+            }
+        }
+        _ => {
+            // error message
+            command_response(
+                ctx,
+                command,
+                "You must specify an action: `add`, `remove`, or `list`",
+            )
+            .await;
+        }
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("book_alias")
+        .description("Manage this server's custom abbreviations for books of the Bible")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "action",
+                "`add`, `remove` or `list`",
+            )
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "alias",
+                "The abbreviation, i.e. `Jhn`",
+            )
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "book",
+                "The book it refers to, i.e. `John`",
+            )
+            .set_autocomplete(true)
+            .required(false),
+        )
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+}
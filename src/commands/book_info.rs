@@ -0,0 +1,89 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::discord_helpers::{
+    command_response, command_response_with_buttons, craft_book_overview_embed, suggestion_buttons,
+};
+use crate::nay;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateCommand,
+    CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ResolvedOption, ResolvedValue,
+};
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(book),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a book of the Bible!").await;
+        return;
+    };
+
+    // understand abbreviations like `Jn` or `1 Cor`
+    let embed = aliases
+        .resolve(book, bible)
+        .and_then(|book| craft_book_overview_embed(&book, bible));
+    let Some(embed) = embed else {
+        let lookup = BibleLookup::new(*book, 1, 1);
+        let (explanation, suggestions) = explain_invalid_reference(&lookup, bible, aliases);
+        let buttons = suggestion_buttons(&suggestions, book_info_button_id);
+        command_response_with_buttons(ctx, cmd, explanation, buttons).await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new().embed(embed);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The id of a button that shows a book's overview
+pub fn book_info_button_id(book: &str) -> String {
+    format!("book_info:{}", book)
+}
+
+/// Shows the book from a `book_info:` button in place of the message it was on
+pub async fn run_button(
+    book: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+) {
+    let Some(embed) = craft_book_overview_embed(book, bible) else {
+        nay!("Book from a button was not found: {}", book);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(embed)
+        .components(Vec::new());
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("book_info")
+        .description("Learn about a book of the Bible")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "book", "The book of the Bible")
+                .set_autocomplete(true)
+                .required(true),
+        )
+        .dm_permission(true)
+}
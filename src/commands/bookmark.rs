@@ -0,0 +1,597 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::commands::open_verse_button_id;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::discord_helpers::verse_navigation::parse_lookup_id;
+use crate::discord_helpers::{ephemeral_button_response, ephemeral_response, verse_preview};
+use crate::nay;
+use crate::userfile::{Bookmark, UserSettings};
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateAttachment, CreateButton, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ResolvedOption, ResolvedValue, UserId,
+};
+
+// the most bookmarks a user can keep
+const MAX_BOOKMARKS: usize = 500;
+const MAX_NOTE_CHARS: usize = 300;
+const MAX_TAGS: usize = 5;
+const MAX_TAG_CHARS: usize = 20;
+// how many bookmarks are on each page of the list
+const BOOKMARKS_PER_PAGE: usize = 10;
+const PREVIEW_CHARS: usize = 100;
+const BUTTONS_PER_ROW: usize = 5;
+
+const NO_BOOKMARKS: &str =
+    "You don't have any bookmarks yet. Use `/bookmark add` or the **Save** button under a verse.";
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+    }) = options.first()
+    else {
+        ephemeral_response(ctx, cmd, "You must choose what to do with your bookmarks!").await;
+        return;
+    };
+
+    // optional values
+    let mut reference = None;
+    let mut note = None;
+    let mut tags = None;
+    let mut tag = None;
+    let mut bookmark = None;
+    let mut format = "markdown";
+    for option in sub_options {
+        match (option.name, &option.value) {
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("note", ResolvedValue::String(value)) => note = Some(*value),
+            ("tags", ResolvedValue::String(value)) => tags = Some(*value),
+            ("tag", ResolvedValue::String(value)) => tag = Some(*value),
+            ("bookmark", ResolvedValue::String(value)) => bookmark = Some(*value),
+            ("format", ResolvedValue::String(value)) => format = value,
+            _ => {}
+        }
+    }
+
+    match *subcommand {
+        "add" => {
+            let Some(reference) = reference else {
+                ephemeral_response(ctx, cmd, "You must specify a reference!").await;
+                return;
+            };
+            add(ctx, cmd, reference, note, tags, bible, aliases).await;
+        }
+        "list" => {
+            let tag = tag.and_then(|tag| parse_tags(tag).into_iter().next());
+            let Some((embed, buttons)) =
+                craft_bookmark_page(cmd.user.id, tag.as_deref(), 0, bible, aliases)
+            else {
+                let msg = match tag {
+                    Some(tag) => format!("You don't have any bookmarks tagged `#{}`.", tag),
+                    None => NO_BOOKMARKS.to_string(),
+                };
+                ephemeral_response(ctx, cmd, msg).await;
+                return;
+            };
+
+            let data = CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(buttons)
+                .ephemeral(true);
+            if let Err(e) = cmd
+                .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+                .await
+            {
+                nay!("Failed to respond to command: {}", e);
+            }
+        }
+        "remove" => {
+            let Some(bookmark) = bookmark else {
+                ephemeral_response(ctx, cmd, "You must choose a bookmark to remove!").await;
+                return;
+            };
+            remove(ctx, cmd, bookmark, bible, aliases).await;
+        }
+        "export" => export(ctx, cmd, format, bible, aliases).await,
+        _ => {
+            nay!("Unknown bookmark subcommand: {}", subcommand);
+        }
+    }
+}
+
+/// Tags from a comma or space separated list, lowercase and without the `#`
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag: String = tag
+            .trim_start_matches('#')
+            .to_lowercase()
+            .chars()
+            .take(MAX_TAG_CHARS)
+            .collect();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+async fn add(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    reference: &str,
+    note: Option<&str>,
+    tags: Option<&str>,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() else {
+        ephemeral_response(
+            ctx,
+            cmd,
+            format!(
+                "No reference found in `{}`. Try something like `John 3:16`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    };
+    if bible.get_verse(lookup.clone(), false).is_err() {
+        let (explanation, _) = explain_invalid_reference(&lookup, bible, aliases);
+        ephemeral_response(ctx, cmd, explanation).await;
+        return;
+    }
+
+    let tags = tags.map(parse_tags).unwrap_or_default();
+    if tags.len() > MAX_TAGS {
+        ephemeral_response(
+            ctx,
+            cmd,
+            format!("A bookmark can have at most {} tags.", MAX_TAGS),
+        )
+        .await;
+        return;
+    }
+    let note = note.map(str::trim).filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > MAX_NOTE_CHARS) {
+        ephemeral_response(
+            ctx,
+            cmd,
+            format!("Notes can be at most {} characters long.", MAX_NOTE_CHARS),
+        )
+        .await;
+        return;
+    }
+
+    let bookmark = Bookmark {
+        note: note.map(String::from),
+        tags,
+        ..Bookmark::new(&lookup, aliases)
+    };
+    let msg = save(cmd.user.id, bookmark, &lookup);
+    ephemeral_response(ctx, cmd, msg).await;
+}
+
+/// Saves a bookmark for a user, returning what to tell them
+fn save(user: UserId, bookmark: Bookmark, lookup: &BibleLookup) -> String {
+    let updates = bookmark.note.is_some() || !bookmark.tags.is_empty();
+    // the limit is checked against the file as it is when the bookmark is added,
+    // so saves at the same time can't go over it
+    match UserSettings::get(&user).add_bookmark(bookmark, MAX_BOOKMARKS) {
+        None => format!(
+            "You can keep at most {} bookmarks. Remove some with `/bookmark remove` first.",
+            MAX_BOOKMARKS
+        ),
+        Some(true) => format!(
+            "Saved **{}** to your bookmarks. See them with `/bookmark list`.",
+            lookup
+        ),
+        Some(false) if updates => format!(
+            "Updated the note and tags of your bookmark for **{}**.",
+            lookup
+        ),
+        Some(false) => format!("**{}** is already in your bookmarks.", lookup),
+    }
+}
+
+async fn remove(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bookmark: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let mut settings = UserSettings::get(&cmd.user.id);
+
+    // autocomplete gives the bookmark's number, but a reference can be typed too
+    let index = match bookmark.trim().parse::<usize>() {
+        Ok(number) => number.checked_sub(1),
+        Err(_) => detect_verses(bookmark, aliases, bible)
+            .into_iter()
+            .next()
+            .and_then(|lookup| {
+                let target = Bookmark::new(&lookup, aliases);
+                settings
+                    .get_bookmarks()
+                    .iter()
+                    .position(|existing| existing.same_passage(&target))
+            }),
+    };
+
+    let msg = match index.and_then(|index| settings.remove_bookmark(index)) {
+        Some(removed) => format!(
+            "Removed **{}** from your bookmarks.",
+            removed.lookup(bible, aliases)
+        ),
+        None => format!("`{}` isn't one of your bookmarks.", bookmark),
+    };
+    ephemeral_response(ctx, cmd, msg).await;
+}
+
+/// A bookmark's reference, note and tags as one line of markdown
+fn describe(bookmark: &Bookmark, bible: &Bible, aliases: &BookAliases) -> String {
+    let mut line = format!("**{}**", bookmark.lookup(bible, aliases));
+    if let Some(note) = &bookmark.note {
+        line.push_str(&format!(" — {}", note));
+    }
+    for tag in &bookmark.tags {
+        line.push_str(&format!(" `#{}`", tag));
+    }
+    line
+}
+
+/// The id of the page buttons for a user's bookmarks (the page number is added by `page_buttons`)
+/// The user isn't part of it, as each user only ever sees their own bookmarks
+pub fn bookmark_page_id(tag: Option<&str>) -> String {
+    format!("bookmark_page:{}", tag.unwrap_or_default())
+}
+
+/// A single page of a user's bookmarks (only those with `tag`, if given), with buttons to open them
+/// Bookmarks are numbered by their place in the whole list, for `/bookmark remove`
+/// Returns None if there are no bookmarks to show
+pub fn craft_bookmark_page(
+    user: UserId,
+    tag: Option<&str>,
+    page: usize,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let settings = UserSettings::get(&user);
+    let bookmarks: Vec<(usize, &Bookmark)> = settings
+        .get_bookmarks()
+        .iter()
+        .enumerate()
+        .filter(|(_, bookmark)| tag.is_none_or(|tag| bookmark.tags.iter().any(|t| t == tag)))
+        .collect();
+    if bookmarks.is_empty() {
+        return None;
+    }
+    let count = bookmarks.len().div_ceil(BOOKMARKS_PER_PAGE);
+    let page = page.min(count - 1);
+    let shown = &bookmarks[page * BOOKMARKS_PER_PAGE..]
+        [..BOOKMARKS_PER_PAGE.min(bookmarks.len() - page * BOOKMARKS_PER_PAGE)];
+
+    let list = shown
+        .iter()
+        .map(|(x, bookmark)| {
+            format!(
+                "{}. {}\n{}",
+                x + 1,
+                describe(bookmark, bible, aliases),
+                verse_preview(&bookmark.lookup(bible, aliases), bible, PREVIEW_CHARS)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let heading = match tag {
+        Some(tag) => format!("🔖 Your bookmarks tagged #{}", tag),
+        None => "🔖 Your bookmarks".to_string(),
+    };
+    let title = if count > 1 {
+        format!("{} ({}/{})", heading, page + 1, count)
+    } else {
+        heading
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(list.join("\n"))
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} bookmark{}. Remove one with /bookmark remove, or download them with /bookmark export.",
+            bookmarks.len(),
+            if bookmarks.len() == 1 { "" } else { "s" }
+        )));
+
+    let mut rows: Vec<CreateActionRow> = shown
+        .chunks(BUTTONS_PER_ROW)
+        .map(|chunk| {
+            CreateActionRow::Buttons(
+                chunk
+                    .iter()
+                    .map(|(_, bookmark)| {
+                        let lookup = bookmark.lookup(bible, aliases);
+                        CreateButton::new(open_verse_button_id(&lookup, bible))
+                            .label(lookup.to_string())
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    rows.extend(page_buttons(&bookmark_page_id(tag), page, count));
+
+    Some((embed, rows))
+}
+
+/// Moves a bookmark list to the page from a `bookmark_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((tag, page)) = parse_page_button(args) else {
+        nay!("Invalid bookmark page button: {}", args);
+        return;
+    };
+    let tag = Some(tag).filter(|tag| !tag.is_empty());
+
+    let data = match craft_bookmark_page(component.user.id, tag, page, bible, aliases) {
+        Some((embed, buttons)) => CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(buttons),
+        // every bookmark on the list was removed since it was sent
+        None => CreateInteractionResponseMessage::new()
+            .content(NO_BOOKMARKS)
+            .embeds(Vec::new())
+            .components(Vec::new()),
+    };
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// Bookmarks the verse from a `save:` button for whoever pressed it
+pub async fn run_save_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    aliases: &BookAliases,
+) {
+    let Some(lookup) = parse_lookup_id(args) else {
+        nay!("Invalid save button: {}", args);
+        return;
+    };
+
+    let msg = save(component.user.id, Bookmark::new(&lookup, aliases), &lookup);
+    ephemeral_button_response(ctx, component, msg).await;
+}
+
+/// Sends the user their bookmarks as a file
+async fn export(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    format: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let settings = UserSettings::get(&cmd.user.id);
+    let bookmarks = settings.get_bookmarks();
+    if bookmarks.is_empty() {
+        ephemeral_response(ctx, cmd, NO_BOOKMARKS).await;
+        return;
+    }
+
+    let (data, filename) = match format {
+        // everything that is stored, so they can be kept or moved elsewhere
+        "json" => match serde_json::to_string_pretty(bookmarks) {
+            Ok(data) => (data, "bookmarks.json"),
+            Err(e) => {
+                nay!("Failed to serialize bookmarks for {}: {}", cmd.user.id, e);
+                ephemeral_response(ctx, cmd, "Failed to export your bookmarks.").await;
+                return;
+            }
+        },
+        "text" => {
+            let entries = bookmarks
+                .iter()
+                .map(|bookmark| {
+                    let lookup = bookmark.lookup(bible, aliases);
+                    let mut entry = lookup.to_string();
+                    if let Some(note) = &bookmark.note {
+                        entry.push_str(&format!(" - {}", note));
+                    }
+                    if !bookmark.tags.is_empty() {
+                        let tags: Vec<String> = bookmark
+                            .tags
+                            .iter()
+                            .map(|tag| format!("#{}", tag))
+                            .collect();
+                        entry.push_str(&format!(" [{}]", tags.join(" ")));
+                    }
+                    let text = bible.get_verse(lookup, false).unwrap_or_default();
+                    format!("{}\n{}", entry, text.trim())
+                })
+                .collect::<Vec<String>>();
+            (
+                format!(
+                    "{}\n\nFrom the {} Bible.\n",
+                    entries.join("\n\n"),
+                    bible.get_translation()
+                ),
+                "bookmarks.txt",
+            )
+        }
+        _ => {
+            let entries = bookmarks
+                .iter()
+                .map(|bookmark| {
+                    let lookup = bookmark.lookup(bible, aliases);
+                    let mut entry = format!("## {}\n", lookup);
+                    if let Some(note) = &bookmark.note {
+                        entry.push_str(&format!("*{}*\n", note));
+                    }
+                    if !bookmark.tags.is_empty() {
+                        let tags: Vec<String> = bookmark
+                            .tags
+                            .iter()
+                            .map(|tag| format!("`#{}`", tag))
+                            .collect();
+                        entry.push_str(&format!("{}\n", tags.join(" ")));
+                    }
+                    let text = bible.get_verse(lookup, false).unwrap_or_default();
+                    format!("{}\n> {}", entry, text.trim())
+                })
+                .collect::<Vec<String>>();
+            (
+                format!(
+                    "# Bookmarks\n\n{}\n\n*From the {} Bible.*\n",
+                    entries.join("\n\n"),
+                    bible.get_translation()
+                ),
+                "bookmarks.md",
+            )
+        }
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content(format!(
+            "Your {} bookmark{}:",
+            bookmarks.len(),
+            if bookmarks.len() == 1 { "" } else { "s" }
+        ))
+        .add_file(CreateAttachment::bytes(data.into_bytes(), filename))
+        .ephemeral(true);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The bookmark choices for `/bookmark remove`, numbered like `/bookmark list`
+pub fn bookmark_choices(
+    user: UserId,
+    partial: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+    limit: usize,
+) -> Vec<(String, String)> {
+    let partial = partial.trim().to_lowercase();
+    UserSettings::get(&user)
+        .get_bookmarks()
+        .iter()
+        .enumerate()
+        .filter_map(|(x, bookmark)| {
+            let mut label = format!("{}. {}", x + 1, bookmark.lookup(bible, aliases));
+            if let Some(note) = &bookmark.note {
+                label.push_str(&format!(" — {}", note));
+            }
+            if !label.to_lowercase().contains(&partial) {
+                return None;
+            }
+            // discord allows at most 100 characters in a choice
+            if label.chars().count() > 100 {
+                label = label.chars().take(99).collect::<String>() + "…";
+            }
+            Some((label, (x + 1).to_string()))
+        })
+        .take(limit)
+        .collect()
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("bookmark")
+        .description("Save verses to come back to later")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Bookmark a verse or passage",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "reference",
+                    "The verse or passage, i.e. `John 3:16`",
+                )
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "note",
+                    "A note to keep with it",
+                )
+                .max_length(MAX_NOTE_CHARS as u16)
+                .required(false),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "tags",
+                    "Tags to group it by, i.e. `comfort, memory`",
+                )
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Show your bookmarks")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "tag",
+                        "Only show bookmarks with this tag",
+                    )
+                    .required(false),
+                ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a bookmark")
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "bookmark",
+                        "The bookmark to remove",
+                    )
+                    .set_autocomplete(true)
+                    .required(true),
+                ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "export",
+                "Download your bookmarks as a file",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "format",
+                    "The kind of file (Markdown by default)",
+                )
+                .add_string_choice("Markdown", "markdown")
+                .add_string_choice("Plain text", "text")
+                .add_string_choice("JSON", "json")
+                .required(false),
+            ),
+        )
+        .dm_permission(true)
+}
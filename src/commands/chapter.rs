@@ -1,29 +1,37 @@
-use crate::commands::send_bible_chapter;
-use crate::discord_helpers::command_response;
-use bible_lib::Bible;
+use crate::books::aliases::BookAliases;
+use crate::commands::{craft_bible_chapter_embeds, send_bible_chapter};
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::discord_helpers::{command_response, ephemeral_button_response};
+use crate::nay;
+use bible_lib::{Bible, BibleLookup};
 use serenity::all::{
-    ChannelId, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    ResolvedOption, ResolvedValue,
+    CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateActionRow,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateMessage, ResolvedOption, ResolvedValue,
 };
 
 pub async fn run(
     options: &[ResolvedOption<'_>],
     ctx: &Context,
     cmd: &CommandInteraction,
-    channel: &ChannelId,
     bible: &Bible,
+    aliases: &BookAliases,
 ) {
     if let Some(ResolvedOption {
         value: ResolvedValue::String(book),
         ..
-    }) = options.get(0)
+    }) = options.first()
     {
         if let Some(ResolvedOption {
             value: ResolvedValue::Integer(chapter),
             ..
         }) = options.get(1)
         {
-            send_bible_chapter(*book, *chapter as u32, ctx, cmd, channel, bible).await;
+            // understand abbreviations like `Jn` or `1 Cor`
+            let book = aliases
+                .resolve(book, bible)
+                .unwrap_or_else(|| book.to_string());
+            send_bible_chapter(book, *chapter as u32, ctx, cmd, bible, aliases).await;
         } else {
             command_response(ctx, cmd, "You must specify a chapter!").await;
         }
@@ -32,11 +40,130 @@ pub async fn run(
     }
 }
 
+/// The id of a button that shows a chapter
+pub fn chapter_button_id(book: &str, chapter: u32) -> String {
+    format!("chapter:{}:{}", book, chapter)
+}
+
+/// Shows the chapter from a `chapter:` button in place of the message it was on
+pub async fn run_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+) {
+    let Some((book, chapter)) = args
+        .rsplit_once(':')
+        .and_then(|(book, chapter)| Some((book, chapter.parse::<u32>().ok()?)))
+    else {
+        nay!("Invalid chapter button: {}", args);
+        ephemeral_button_response(ctx, component, "Sorry, that button doesn't work anymore.").await;
+        return;
+    };
+    let Some(embeds) = craft_bible_chapter_embeds(book, chapter, bible) else {
+        nay!("Chapter from a button was not found: {} {}", book, chapter);
+        ephemeral_button_response(
+            ctx,
+            component,
+            format!(
+                "Sorry, I couldn't find {} {}.",
+                BibleLookup::capitalize_book(&book.to_string()),
+                chapter
+            ),
+        )
+        .await;
+        return;
+    };
+
+    let mut embeds = embeds.into_iter();
+    let Some(first) = embeds.next() else {
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(first)
+        .components(Vec::new());
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+        return;
+    }
+
+    // send the rest to the channel
+    for embed in embeds {
+        let builder = CreateMessage::new().embed(embed);
+        if let Err(e) = component.channel_id.send_message(&ctx.http, builder).await {
+            nay!("Failed to send message: {}", e);
+        }
+    }
+}
+
+/// The id of the page buttons for a chapter (the page number is added by `page_buttons`)
+pub fn chapter_page_id(book: &str, chapter: u32) -> String {
+    format!("chapter_page:{}:{}", book, chapter)
+}
+
+/// A single page of a chapter with buttons to move between pages
+/// Returns None if the chapter doesn't exist
+pub fn craft_chapter_page(
+    book: &str,
+    chapter: u32,
+    page: usize,
+    bible: &Bible,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let embeds = craft_bible_chapter_embeds(book, chapter, bible)?;
+    let count = embeds.len();
+    let page = page.min(count.saturating_sub(1));
+    let embed = embeds.into_iter().nth(page)?;
+
+    let id = chapter_page_id(book, chapter);
+    Some((embed, page_buttons(&id, page, count)))
+}
+
+/// Moves a paginated chapter to the page from a `chapter_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+) {
+    let Some((chapter, page)) = parse_page_button(args) else {
+        nay!("Invalid chapter page button: {}", args);
+        return;
+    };
+    let Some((book, chapter)) = chapter.rsplit_once(':') else {
+        nay!("Invalid chapter page button: {}", args);
+        return;
+    };
+    let Ok(chapter) = chapter.parse::<u32>() else {
+        nay!("Invalid chapter page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_chapter_page(book, chapter, page, bible) else {
+        nay!("Chapter from a button was not found: {} {}", book, chapter);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
 pub fn register() -> CreateCommand {
     CreateCommand::new("chapter")
         .description("Specify a chapter from the bible")
         .add_option(
             CreateCommandOption::new(CommandOptionType::String, "book", "The book of the Bible")
+                .set_autocomplete(true)
                 .required(true),
         )
         .add_option(
@@ -45,6 +172,10 @@ pub fn register() -> CreateCommand {
                 "chapter",
                 "The chapter of the book",
             )
+            .set_autocomplete(true)
+            // psalms has the most chapters
+            .min_int_value(1)
+            .max_int_value(150)
             .required(true),
         )
         .dm_permission(true)
@@ -0,0 +1,580 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::discord_helpers::{ephemeral_button_response, ephemeral_response};
+use crate::guildfile::{Collection, GuildSettings};
+use crate::nay;
+use crate::saved_passage::SavedPassage;
+use bible_lib::Bible;
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateActionRow,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, GuildId, ResolvedOption, ResolvedValue,
+};
+
+const MAX_COLLECTIONS: usize = 25;
+const MAX_PASSAGES: usize = 200;
+const MAX_NAME_CHARS: usize = 50;
+const MAX_DESCRIPTION_CHARS: usize = 300;
+// how many passages are on each page
+const PASSAGES_PER_PAGE: usize = 5;
+// passages are cut short after about this many characters
+const MAX_PASSAGE_CHARS: usize = 700;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    guild: &GuildId,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+    }) = options.first()
+    else {
+        ephemeral_response(ctx, cmd, "You must choose what to do with collections!").await;
+        return;
+    };
+
+    // optional values
+    let mut name = None;
+    let mut description = None;
+    let mut reference = None;
+    let mut passage = None;
+    for option in sub_options {
+        match (option.name, &option.value) {
+            ("name", ResolvedValue::String(value)) => name = Some(value.trim()),
+            ("collection", ResolvedValue::String(value)) => name = Some(value.trim()),
+            ("description", ResolvedValue::String(value)) => description = Some(value.trim()),
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("passage", ResolvedValue::String(value)) => passage = Some(*value),
+            _ => {}
+        }
+    }
+
+    // everyone can read collections, but only server managers can change them
+    let changes = !matches!(*subcommand, "show" | "post");
+    let can_manage = cmd
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_guild());
+    if changes && !can_manage {
+        ephemeral_response(
+            ctx,
+            cmd,
+            "You need the Manage Server permission to change collections.",
+        )
+        .await;
+        return;
+    }
+
+    let mut settings = GuildSettings::get(guild);
+    let msg = match (*subcommand, name) {
+        ("show", None) => list_collections(&settings),
+        ("daily", None) => {
+            settings.set_daily_verse_collection(None);
+            "The daily verse is back to the one shared by every server.".to_string()
+        }
+        (_, None) => "You must specify a collection!".to_string(),
+        ("create", Some(name)) => {
+            if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+                format!(
+                    "Collection names must be 1 to {} characters long.",
+                    MAX_NAME_CHARS
+                )
+            } else if settings.get_collections().len() >= MAX_COLLECTIONS {
+                format!(
+                    "A server can have at most {} collections. Delete one with `/collection delete` first.",
+                    MAX_COLLECTIONS
+                )
+            } else if settings.create_collection(
+                name.to_string(),
+                description
+                    .filter(|description| !description.is_empty())
+                    .map(String::from),
+            ) {
+                format!(
+                    "Created the collection **{}**. Add passages with `/collection add`.",
+                    name
+                )
+            } else {
+                format!("There is already a collection called **{}**.", name)
+            }
+        }
+        ("delete", Some(name)) => {
+            if settings.delete_collection(name) {
+                format!("Deleted the collection **{}**.", name)
+            } else {
+                no_collection(name)
+            }
+        }
+        ("add", Some(name)) => {
+            let Some(reference) = reference else {
+                ephemeral_response(ctx, cmd, "You must specify a reference!").await;
+                return;
+            };
+            add(&mut settings, name, reference, bible, aliases)
+        }
+        ("remove", Some(name)) => {
+            let Some(passage) = passage else {
+                ephemeral_response(ctx, cmd, "You must choose a passage to remove!").await;
+                return;
+            };
+            remove(&mut settings, name, passage, bible, aliases)
+        }
+        ("daily", Some(name)) => {
+            match settings.get_collection(name) {
+                Some(collection) if collection.passages.is_empty() => format!(
+                    "**{}** has no passages yet. Add some with `/collection add` first.",
+                    collection.name
+                ),
+                Some(collection) => {
+                    let name = collection.name.clone();
+                    settings.set_daily_verse_collection(Some(name.clone()));
+                    let mut msg = format!(
+                        "The daily verse will now come from **{}**, one passage a day in order.",
+                        name
+                    );
+                    if settings.get_daily_verse_channel().is_none() {
+                        msg.push_str(" Register a daily verse channel with `/register_channel` to receive it.");
+                    }
+                    msg
+                }
+                None => no_collection(name),
+            }
+        }
+        ("show" | "post", Some(name)) => {
+            let Some(collection) = settings.get_collection(name) else {
+                ephemeral_response(ctx, cmd, no_collection(name)).await;
+                return;
+            };
+            let Some((embed, buttons)) = craft_collection_page(collection, 0, bible, aliases)
+            else {
+                ephemeral_response(
+                    ctx,
+                    cmd,
+                    format!(
+                        "**{}** has no passages yet. Add some with `/collection add`.",
+                        collection.name
+                    ),
+                )
+                .await;
+                return;
+            };
+
+            // posting shares it with the channel, showing is just for whoever asked
+            let data = CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(buttons)
+                .ephemeral(*subcommand == "show");
+            if let Err(e) = cmd
+                .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+                .await
+            {
+                nay!("Failed to respond to command: {}", e);
+            }
+            return;
+        }
+        _ => {
+            nay!("Unknown collection subcommand: {}", subcommand);
+            return;
+        }
+    };
+
+    ephemeral_response(ctx, cmd, msg).await;
+}
+
+fn no_collection(name: &str) -> String {
+    format!(
+        "There is no collection called **{}**. See them all with `/collection show`.",
+        name
+    )
+}
+
+/// Every collection of a guild, with how many passages it has
+fn list_collections(settings: &GuildSettings) -> String {
+    let collections = settings.get_collections();
+    if collections.is_empty() {
+        return "This server has no collections yet. Create one with `/collection create`."
+            .to_string();
+    }
+
+    let daily = settings
+        .get_daily_verse_collection()
+        .map(|daily| &daily.name);
+    let lines = collections
+        .iter()
+        .map(|collection| {
+            let mut line = format!(
+                "**{}** ({} passage{})",
+                collection.name,
+                collection.passages.len(),
+                if collection.passages.len() == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            );
+            if daily == Some(&collection.name) {
+                line.push_str(" 📅 daily verse");
+            }
+            if let Some(description) = &collection.description {
+                line.push_str(&format!(" — {}", description));
+            }
+            line
+        })
+        .collect::<Vec<String>>();
+    format!("Collections in this server:\n{}", lines.join("\n"))
+}
+
+/// Adds every passage found in `reference` to a collection, returning what to tell the user
+fn add(
+    settings: &mut GuildSettings,
+    name: &str,
+    reference: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> String {
+    let Some(collection) = settings.get_collection(name) else {
+        return no_collection(name);
+    };
+    let name = collection.name.clone();
+    let count = collection.passages.len();
+
+    let lookups = detect_verses(reference, aliases, bible);
+    if lookups.is_empty() {
+        return format!(
+            "No reference found in `{}`. Try something like `John 3:16`",
+            reference
+        );
+    }
+    if let Some(invalid) = lookups
+        .iter()
+        .find(|lookup| bible.get_verse((*lookup).clone(), false).is_err())
+    {
+        return explain_invalid_reference(invalid, bible, aliases).0;
+    }
+    if count + lookups.len() > MAX_PASSAGES {
+        return format!(
+            "A collection can have at most {} passages, and **{}** has {}.",
+            MAX_PASSAGES, name, count
+        );
+    }
+
+    let mut added = Vec::new();
+    let mut already = Vec::new();
+    for lookup in lookups {
+        match settings.add_to_collection(&name, SavedPassage::new(&lookup, aliases)) {
+            Some(true) => added.push(lookup.to_string()),
+            _ => already.push(lookup.to_string()),
+        }
+    }
+
+    let mut msg = String::new();
+    if !added.is_empty() {
+        msg.push_str(&format!("Added {} to **{}**.", added.join(", "), name));
+    }
+    if !already.is_empty() {
+        msg.push_str(&format!(
+            " {} {} already in it.",
+            already.join(", "),
+            if already.len() == 1 { "was" } else { "were" }
+        ));
+    }
+    msg.trim().to_string()
+}
+
+/// Removes a passage (by its number or reference) from a collection, returning what to tell the user
+fn remove(
+    settings: &mut GuildSettings,
+    name: &str,
+    passage: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> String {
+    let Some(collection) = settings.get_collection(name) else {
+        return no_collection(name);
+    };
+    let name = collection.name.clone();
+
+    // autocomplete gives the passage's number, but a reference can be typed too
+    let index = match passage.trim().parse::<usize>() {
+        Ok(number) => number.checked_sub(1),
+        Err(_) => detect_verses(passage, aliases, bible)
+            .into_iter()
+            .next()
+            .and_then(|lookup| {
+                let target = SavedPassage::new(&lookup, aliases);
+                collection
+                    .passages
+                    .iter()
+                    .position(|existing| *existing == target)
+            }),
+    };
+
+    match index.and_then(|index| settings.remove_from_collection(&name, index)) {
+        Some(removed) => format!(
+            "Removed {} from **{}**.",
+            removed.lookup(bible, aliases),
+            name
+        ),
+        None => format!("`{}` isn't in **{}**.", passage, name),
+    }
+}
+
+/// The id of the page buttons for a collection (the page number is added by `page_buttons`)
+pub fn collection_page_id(name: &str) -> String {
+    format!("collection_page:{}", name)
+}
+
+/// A single page of the passages in a collection, with buttons to move between pages
+/// Returns None if the collection has no passages
+pub fn craft_collection_page(
+    collection: &Collection,
+    page: usize,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let passages = &collection.passages;
+    if passages.is_empty() {
+        return None;
+    }
+    let count = passages.len().div_ceil(PASSAGES_PER_PAGE);
+    let page = page.min(count - 1);
+
+    let list = passages
+        .iter()
+        .enumerate()
+        .skip(page * PASSAGES_PER_PAGE)
+        .take(PASSAGES_PER_PAGE)
+        .map(|(x, passage)| {
+            let lookup = passage.lookup(bible, aliases);
+            let text = bible.get_verse(lookup.clone(), true).unwrap_or_default();
+            let text = text.trim();
+            let text = if text.chars().count() > MAX_PASSAGE_CHARS {
+                let cut: String = text.chars().take(MAX_PASSAGE_CHARS).collect();
+                let cut = cut.rsplit_once(' ').map(|(start, _)| start).unwrap_or(&cut);
+                format!("{}…", cut)
+            } else {
+                text.to_string()
+            };
+            format!("**{}. {}**\n{}", x + 1, lookup, text)
+        })
+        .collect::<Vec<String>>();
+
+    let description = match &collection.description {
+        Some(description) => format!("*{}*\n\n{}", description, list.join("\n\n")),
+        None => list.join("\n\n"),
+    };
+    let title = if count > 1 {
+        format!("📚 {} ({}/{})", collection.name, page + 1, count)
+    } else {
+        format!("📚 {}", collection.name)
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} passage{} from the {} Bible.",
+            passages.len(),
+            if passages.len() == 1 { "" } else { "s" },
+            bible.get_translation()
+        )));
+
+    Some((
+        embed,
+        page_buttons(&collection_page_id(&collection.name), page, count),
+    ))
+}
+
+/// Moves a collection to the page from a `collection_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((name, page)) = parse_page_button(args) else {
+        nay!("Invalid collection page button: {}", args);
+        return;
+    };
+    let Some(guild) = component.guild_id else {
+        nay!("Collection page button used outside a server: {}", args);
+        return;
+    };
+
+    let settings = GuildSettings::get(&guild);
+    let Some((embed, buttons)) = settings
+        .get_collection(name)
+        .and_then(|collection| craft_collection_page(collection, page, bible, aliases))
+    else {
+        ephemeral_button_response(
+            ctx,
+            component,
+            format!("The collection **{}** no longer has any passages.", name),
+        )
+        .await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// The collection choices for autocomplete
+pub fn collection_choices(guild: &GuildId, partial: &str, limit: usize) -> Vec<String> {
+    let partial = partial.trim().to_lowercase();
+    GuildSettings::get(guild)
+        .get_collections()
+        .iter()
+        .map(|collection| collection.name.clone())
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(limit)
+        .collect()
+}
+
+/// The passage choices of a collection for `/collection remove`, numbered like the collection
+pub fn passage_choices(
+    guild: &GuildId,
+    name: &str,
+    partial: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+    limit: usize,
+) -> Vec<(String, String)> {
+    let partial = partial.trim().to_lowercase();
+    let settings = GuildSettings::get(guild);
+    let Some(collection) = settings.get_collection(name) else {
+        return Vec::new();
+    };
+
+    collection
+        .passages
+        .iter()
+        .enumerate()
+        .map(|(x, passage)| {
+            (
+                format!("{}. {}", x + 1, passage.lookup(bible, aliases)),
+                (x + 1).to_string(),
+            )
+        })
+        .filter(|(label, _)| label.to_lowercase().contains(&partial))
+        .take(limit)
+        .collect()
+}
+
+pub fn register() -> CreateCommand {
+    let collection = |description: &str, required: bool| {
+        CreateCommandOption::new(CommandOptionType::String, "collection", description)
+            .set_autocomplete(true)
+            .required(required)
+    };
+
+    CreateCommand::new("collection")
+        .description("Named lists of passages for this server, like a sermon series")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "create",
+                "Create a collection (requires Manage Server)",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "name",
+                    "The name of the collection, i.e. `Memory Verses`",
+                )
+                .max_length(MAX_NAME_CHARS as u16)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "description",
+                    "What the collection is for",
+                )
+                .max_length(MAX_DESCRIPTION_CHARS as u16)
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "delete",
+                "Delete a collection (requires Manage Server)",
+            )
+            .add_sub_option(collection("The collection to delete", true)),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Add passages to a collection (requires Manage Server)",
+            )
+            .add_sub_option(collection("The collection to add to", true))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "reference",
+                    "The verses or passages, i.e. `John 3:16` or `Romans 8:28-30; Psalm 23:1`",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Remove a passage from a collection (requires Manage Server)",
+            )
+            .add_sub_option(collection("The collection to remove from", true))
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "passage",
+                    "The passage to remove",
+                )
+                .set_autocomplete(true)
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "show",
+                "Show a collection (only to you), or list every collection",
+            )
+            .add_sub_option(collection("The collection to show", false)),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "post",
+                "Post a collection in this channel",
+            )
+            .add_sub_option(collection("The collection to post", true)),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "daily",
+                "Take the daily verse from a collection, or leave empty for the usual one (requires Manage Server)",
+            )
+            .add_sub_option(collection("The collection to take the daily verse from", false)),
+        )
+        .dm_permission(false)
+}
@@ -0,0 +1,227 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::commentary::Commentaries;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::command_response;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button, split_into_pages};
+use crate::discord_helpers::verse_navigation::{
+    lookup_id, parse_lookup_id, parse_translated_lookup_id,
+};
+use crate::nay;
+use crate::reference_parser::{parse_whole_reference, WholeReference};
+use crate::translations::Translations;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateActionRow,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
+};
+
+// how much of a section is on each page
+const PAGE_CHARS: usize = 2000;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    commentaries: &Commentaries,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(reference),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+
+    if commentaries.is_empty() {
+        command_response(ctx, cmd, "No commentary has been imported.").await;
+        return;
+    }
+
+    let Some(lookup) = find_reference(reference, bible, aliases) else {
+        command_response(
+            ctx,
+            cmd,
+            format!(
+                "No reference found in `{}`. Try something like `John 3:16` or `Psalm 23`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    };
+    if bible.get_verse(lookup.clone(), false).is_err() {
+        let (explanation, _) = explain_invalid_reference(&lookup, bible, aliases);
+        command_response(ctx, cmd, explanation).await;
+        return;
+    }
+
+    let Some((embed, buttons)) = craft_commentary_page(&lookup, 0, commentaries, bible, aliases)
+    else {
+        command_response(ctx, cmd, format!("There is no commentary on {}.", lookup)).await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// A verse reference, or a whole chapter like `Psalm 23`
+fn find_reference(reference: &str, bible: &Bible, aliases: &BookAliases) -> Option<BibleLookup> {
+    if let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() {
+        return Some(lookup);
+    }
+
+    let Some(WholeReference::Chapter { book, chapter }) = parse_whole_reference(reference, aliases)
+    else {
+        return None;
+    };
+    let book = aliases.resolve(&book, bible)?;
+    let max_verse = bible.get_max_verse(&book, chapter).ok()?;
+    Some(BibleLookup::new_range(book, chapter, 1, max_verse))
+}
+
+/// The id of the page buttons for the commentary on a reference (the page number is added by `page_buttons`)
+pub fn commentary_page_id(lookup: &BibleLookup) -> String {
+    format!("commentary_page:{}", lookup_id(lookup))
+}
+
+/// A single page of the commentary on a reference, with buttons to move between pages
+/// Every section about the reference is included, each split into pages if it's long
+/// Returns None if there is no commentary on it
+pub fn craft_commentary_page(
+    lookup: &BibleLookup,
+    page: usize,
+    commentaries: &Commentaries,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let pages: Vec<_> = commentaries
+        .sections_for(lookup, aliases)
+        .into_iter()
+        .flat_map(|section| {
+            split_into_pages(&section.text, PAGE_CHARS)
+                .into_iter()
+                .map(move |text| (section, text))
+        })
+        .collect();
+    if pages.is_empty() {
+        return None;
+    }
+    let count = pages.len();
+    let page = page.min(count - 1);
+    let (section, text) = &pages[page];
+
+    let title = if count > 1 {
+        format!(
+            "💬 {} ({}/{})",
+            section.reference(bible, aliases),
+            page + 1,
+            count
+        )
+    } else {
+        format!("💬 {}", section.reference(bible, aliases))
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(text)
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!("From {}.", section.source)));
+
+    Some((
+        embed,
+        page_buttons(&commentary_page_id(lookup), page, count),
+    ))
+}
+
+/// Shows the commentary from a verse's "Commentary" button, only to whoever pressed it
+pub async fn run_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    commentaries: &Commentaries,
+    translations: &Translations,
+    aliases: &BookAliases,
+) {
+    let Some((translation, lookup)) = parse_translated_lookup_id(args) else {
+        nay!("Invalid commentary button: {}", args);
+        return;
+    };
+    let bible = &*translations.get_or_default(translation);
+
+    let data = match craft_commentary_page(&lookup, 0, commentaries, bible, aliases) {
+        Some((embed, buttons)) => CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(buttons),
+        None => CreateInteractionResponseMessage::new()
+            .content(format!("There is no commentary on {}.", lookup)),
+    };
+    if let Err(e) = component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(data.ephemeral(true)),
+        )
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// Moves the commentary to the page from a `commentary_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    commentaries: &Commentaries,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((lookup, page)) = parse_page_button(args) else {
+        nay!("Invalid commentary page button: {}", args);
+        return;
+    };
+    let Some(lookup) = parse_lookup_id(lookup) else {
+        nay!("Invalid commentary page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_commentary_page(&lookup, page, commentaries, bible, aliases)
+    else {
+        nay!("Commentary from a button was not found: {}", lookup);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("commentary")
+        .description("Read what a commentary says about a verse or chapter")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The verse, passage or chapter, i.e. `John 3:16` or `Psalm 23`",
+            )
+            .required(true),
+        )
+        .dm_permission(true)
+}
@@ -0,0 +1,190 @@
+use crate::books::aliases::BookAliases;
+use crate::commands::open_verse_button_id;
+use crate::dictionary::Dictionary;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button, split_into_pages};
+use crate::discord_helpers::{command_response, MAX_BUTTON_ROWS};
+use crate::nay;
+use crate::reference_parser::parse_references;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
+};
+
+// how much of a definition is on each page
+const PAGE_CHARS: usize = 2000;
+const BUTTONS_PER_ROW: usize = 5;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    dictionary: &Dictionary,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(term),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a term!").await;
+        return;
+    };
+
+    if dictionary.is_empty() {
+        command_response(ctx, cmd, "No dictionary has been imported.").await;
+        return;
+    }
+
+    let Some(entry) = dictionary.find(term) else {
+        command_response(
+            ctx,
+            cmd,
+            format!("\"{}\" isn't in the dictionary.", term.trim()),
+        )
+        .await;
+        return;
+    };
+    let Some((embed, buttons)) = craft_definition_page(&entry.term, 0, dictionary, bible, aliases)
+    else {
+        return;
+    };
+
+    // let people know when a misspelling was corrected
+    let content = if entry.term.eq_ignore_ascii_case(term.trim()) {
+        String::new()
+    } else {
+        format!(
+            "No entry for \"{}\", showing **{}** instead.",
+            term.trim(),
+            entry.term
+        )
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content(content)
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The id of the page buttons for a definition (the page number is added by `page_buttons`)
+pub fn definition_page_id(term: &str) -> String {
+    format!("define_page:{}", term)
+}
+
+/// The verses referenced in some text, in the order they appear, skipping ones that don't exist
+fn referenced_verses(text: &str, bible: &Bible, aliases: &BookAliases) -> Vec<BibleLookup> {
+    let mut lookups = Vec::new();
+    for span in parse_references(text, aliases) {
+        let Some(book) = aliases.resolve(&span.book, bible) else {
+            continue;
+        };
+        let lookup = span.to_lookup(&book, bible);
+        if bible.get_verse(lookup.clone(), false).is_ok() && !lookups.contains(&lookup) {
+            lookups.push(lookup);
+        }
+    }
+    lookups
+}
+
+/// A single page of a definition, with buttons for the verses it mentions and to move between pages
+/// Returns None if the term isn't in the dictionary
+pub fn craft_definition_page(
+    term: &str,
+    page: usize,
+    dictionary: &Dictionary,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let entry = dictionary.get(term)?;
+    let pages = split_into_pages(&entry.definition, PAGE_CHARS);
+    let count = pages.len();
+    let page = page.min(count - 1);
+    let text = &pages[page];
+
+    let title = if count > 1 {
+        format!("📚 {} ({}/{})", entry.term, page + 1, count)
+    } else {
+        format!("📚 {}", entry.term)
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(text)
+        .color(Colour::GOLD);
+
+    // a button for each verse on this page, with room left for the page buttons
+    let page_rows = page_buttons(&definition_page_id(&entry.term), page, count);
+    let verse_rows = MAX_BUTTON_ROWS - page_rows.len();
+    let mut rows: Vec<CreateActionRow> = referenced_verses(text, bible, aliases)
+        .chunks(BUTTONS_PER_ROW)
+        .take(verse_rows)
+        .map(|chunk| {
+            CreateActionRow::Buttons(
+                chunk
+                    .iter()
+                    .map(|lookup| {
+                        CreateButton::new(open_verse_button_id(lookup, bible))
+                            .label(lookup.to_string())
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    rows.extend(page_rows);
+
+    Some((embed, rows))
+}
+
+/// Moves a definition to the page from a `define_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    dictionary: &Dictionary,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((term, page)) = parse_page_button(args) else {
+        nay!("Invalid definition page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_definition_page(term, page, dictionary, bible, aliases)
+    else {
+        nay!("Term from a button was not found: {}", term);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("define")
+        .description("Look up a word or name in the Bible dictionary")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "term",
+                "The word or name, i.e. `Aaron` or `atonement`",
+            )
+            .set_autocomplete(true)
+            .required(true),
+        )
+        .dm_permission(true)
+}
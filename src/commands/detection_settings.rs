@@ -0,0 +1,178 @@
+use crate::discord_helpers::{command_response, MAX_BUTTON_ROWS};
+use crate::guildfile::{DeliveryPolicy, GuildSettings};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, GuildId,
+    Permissions, ResolvedOption, ResolvedValue,
+};
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    command: &CommandInteraction,
+    guild: &GuildId,
+) {
+    let mut guild_file = GuildSettings::get(guild);
+    let mut settings = guild_file.get_detection_settings();
+
+    // apply any options that were given
+    let mut changed = false;
+    for option in options {
+        match (option.name, &option.value) {
+            ("max_references", ResolvedValue::Integer(value)) => {
+                settings.max_references = *value as usize
+            }
+            ("verses_per_message", ResolvedValue::Integer(value)) => {
+                settings.verses_per_message = *value as usize
+            }
+            ("channel_cooldown", ResolvedValue::Integer(value)) => {
+                settings.channel_cooldown = *value as u64
+            }
+            ("user_cooldown", ResolvedValue::Integer(value)) => {
+                settings.user_cooldown = *value as u64
+            }
+            ("whole_chapters", ResolvedValue::Boolean(value)) => settings.whole_chapters = *value,
+            ("whole_books", ResolvedValue::Boolean(value)) => settings.whole_books = *value,
+            ("commentary_button", ResolvedValue::Boolean(value)) => {
+                settings.commentary_button = *value
+            }
+            ("long_passages", ResolvedValue::String(value)) => {
+                let Some(policy) = DeliveryPolicy::from_code(value) else {
+                    continue;
+                };
+                settings.long_passages = policy
+            }
+            _ => continue,
+        }
+        changed = true;
+    }
+
+    if changed {
+        guild_file.set_detection_settings(settings);
+    }
+
+    // settings from before verses had buttons could group up to 10 verses,
+    // and the commentary button gives each verse a second row
+    let verses_per_reply = if settings.verses_per_message > settings.verses_per_reply() {
+        format!(
+            "{} (set to {}, but each verse has {} of buttons and a reply can only hold {} rows)",
+            settings.verses_per_reply(),
+            settings.verses_per_message,
+            if settings.button_rows_per_verse() == 1 {
+                "a row"
+            } else {
+                "two rows"
+            },
+            MAX_BUTTON_ROWS
+        )
+    } else {
+        settings.verses_per_reply().to_string()
+    };
+
+    command_response(
+        ctx,
+        command,
+        format!(
+            "{}\nMax references per message: `{}`\nVerses per reply: `{}`\nChannel cooldown: `{}s`\nUser cooldown: `{}s`\nWhole chapters: `{}`\nBook overviews: `{}`\nCommentary button: `{}`\nLong passages: `{}`",
+            if changed {
+                "Verse detection settings updated!"
+            } else {
+                "Current verse detection settings:"
+            },
+            settings.max_references,
+            verses_per_reply,
+            settings.channel_cooldown,
+            settings.user_cooldown,
+            if settings.whole_chapters { "on" } else { "off" },
+            if settings.whole_books { "on" } else { "off" },
+            if settings.commentary_button {
+                "on"
+            } else {
+                "off"
+            },
+            settings.long_passages
+        ),
+    )
+    .await;
+}
+
+pub fn register() -> CreateCommand {
+    let mut long_passages = CreateCommandOption::new(
+        CommandOptionType::String,
+        "long_passages",
+        "How passages too long for one message are sent (pages with buttons by default)",
+    )
+    .required(false);
+    for policy in DeliveryPolicy::ALL {
+        long_passages = long_passages.add_string_choice(policy.to_string(), policy.code());
+    }
+
+    CreateCommand::new("detection_settings")
+        .description("View or change the limits for verses detected in messages")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "max_references",
+                "The most references shown for a single message",
+            )
+            .min_int_value(1)
+            .max_int_value(25)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "verses_per_message",
+                "How many verses are grouped into one reply (at most 5)",
+            )
+            .min_int_value(1)
+            .max_int_value(MAX_BUTTON_ROWS as u64)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "channel_cooldown",
+                "Seconds between verse replies in the same channel",
+            )
+            .min_int_value(0)
+            .max_int_value(3600)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "user_cooldown",
+                "Seconds between verse replies to the same user",
+            )
+            .min_int_value(0)
+            .max_int_value(3600)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "whole_chapters",
+                "Show whole chapters for references like `Psalm 23` or `[Ps 23]`",
+            )
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "whole_books",
+                "Show a book overview for references like `the book of Jude` or `[Jud]`",
+            )
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "commentary_button",
+                "Add a Commentary button to verses (on its own row, so replies hold at most 2 verses)",
+            )
+            .required(false),
+        )
+        .add_option(long_passages)
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .dm_permission(false)
+}
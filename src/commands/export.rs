@@ -0,0 +1,163 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::{attachment_name, ephemeral_response};
+use crate::export::{export, ExportFormat, ExportPassage};
+use crate::nay;
+use crate::reference_parser::{parse_whole_reference, WholeReference};
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+    CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+
+// the most passages one file can hold
+const MAX_PASSAGES: usize = 20;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let mut reference = None;
+    let mut format = ExportFormat::Markdown;
+    for option in options {
+        match (option.name, &option.value) {
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("format", ResolvedValue::String(value)) => {
+                format = ExportFormat::from_code(value).unwrap_or(format)
+            }
+            _ => {}
+        }
+    }
+
+    let Some(reference) = reference else {
+        ephemeral_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+    let passages = match find_passages(reference, bible, aliases) {
+        Ok(passages) => passages,
+        Err(msg) => {
+            ephemeral_response(ctx, cmd, msg).await;
+            return;
+        }
+    };
+
+    let titles = passages
+        .iter()
+        .map(|passage| passage.title.clone())
+        .collect::<Vec<String>>();
+    let filename = if passages.len() == 1 {
+        attachment_name(&titles[0], format.extension())
+    } else {
+        format!("passages.{}", format.extension())
+    };
+
+    // PDFs take a moment to lay out, so discord is told the response is coming
+    if let Err(e) = cmd
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await
+    {
+        nay!("Failed to defer command: {}", e);
+        return;
+    }
+
+    let translation = bible.get_translation().to_string();
+    let exported =
+        tokio::task::spawn_blocking(move || export(&passages, &translation, format)).await;
+
+    let response = match exported {
+        Ok(Some(data)) => EditInteractionResponse::new()
+            .content(format!("**{}**", titles.join("; ")))
+            .new_attachment(CreateAttachment::bytes(data, filename)),
+        Ok(None) => {
+            nay!("Failed to export {}", reference);
+            EditInteractionResponse::new().content("Sorry, I couldn't make that file.")
+        }
+        Err(e) => {
+            nay!("Export task failed: {}", e);
+            EditInteractionResponse::new().content("Sorry, I couldn't make that file.")
+        }
+    };
+    if let Err(e) = cmd.edit_response(&ctx.http, response).await {
+        nay!("Failed to send export: {}", e);
+    }
+}
+
+/// The passages in a reference: verses and ranges (several can be given at once), or a whole chapter
+/// Returns a message for the user if there aren't any, or one can't be found
+fn find_passages(
+    reference: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Result<Vec<ExportPassage>, String> {
+    let lookups = detect_verses(reference, aliases, bible);
+    if lookups.is_empty() {
+        return match parse_whole_reference(reference, aliases) {
+            Some(WholeReference::Chapter { book, chapter }) => {
+                let book = aliases.resolve(&book, bible).unwrap_or(book);
+                ExportPassage::from_chapter(&book, chapter, bible)
+                    .map(|passage| vec![passage])
+                    .ok_or_else(|| {
+                        explain_invalid_reference(
+                            &BibleLookup::new(&book, chapter, 1),
+                            bible,
+                            aliases,
+                        )
+                        .0
+                    })
+            }
+            Some(WholeReference::Book(_)) => Err(
+                "Whole books are too long to export. Try a chapter at a time, i.e. `Psalm 23`"
+                    .to_string(),
+            ),
+            None => Err(format!(
+                "No reference found in `{}`. Try something like `John 3:16-21` or `Psalm 23`",
+                reference
+            )),
+        };
+    }
+    if lookups.len() > MAX_PASSAGES {
+        return Err(format!(
+            "A file can hold at most {} passages. Try exporting them in a few goes.",
+            MAX_PASSAGES
+        ));
+    }
+
+    lookups
+        .iter()
+        .map(|lookup| {
+            ExportPassage::from_lookup(lookup, bible)
+                .ok_or_else(|| explain_invalid_reference(lookup, bible, aliases).0)
+        })
+        .collect()
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("export")
+        .description("Download a passage as a Markdown, plain text or PDF file")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The passages or chapter to export, i.e. Romans 8:28-39 or Psalm 23",
+            )
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "format", "The kind of file")
+                .add_string_choice("Markdown", "markdown")
+                .add_string_choice("Plain text", "text")
+                .add_string_choice("PDF", "pdf")
+                .required(true),
+        )
+        .dm_permission(true)
+}
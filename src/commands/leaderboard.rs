@@ -0,0 +1,77 @@
+use crate::discord_helpers::ephemeral_response;
+use crate::guildfile::GuildSettings;
+use crate::nay;
+use serenity::all::{
+    Colour, CommandInteraction, Context, CreateCommand, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseMessage, GuildId,
+};
+
+// how many places are shown
+const TOP_PLACES: usize = 10;
+
+pub async fn run(ctx: &Context, cmd: &CommandInteraction, guild: &GuildId) {
+    let settings = GuildSettings::get(guild);
+
+    // highest score first, ties go to whoever has the lower id so the order is stable
+    let mut scores: Vec<(u64, u32)> = settings
+        .get_quiz_scores()
+        .iter()
+        .map(|(user, score)| (*user, *score))
+        .filter(|(_, score)| *score > 0)
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    if scores.is_empty() {
+        ephemeral_response(
+            ctx,
+            cmd,
+            "Nobody has scored any points yet. Start with `/quiz`!",
+        )
+        .await;
+        return;
+    }
+
+    let mut description = scores
+        .iter()
+        .take(TOP_PLACES)
+        .enumerate()
+        .map(|(place, (user, score))| format!("**{}.** <@{}> — {}", place + 1, user, score))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    // show the user their own place if they didn't make the top
+    let user = cmd.user.id.get();
+    if let Some(place) = scores.iter().position(|(id, _)| *id == user) {
+        if place >= TOP_PLACES {
+            description.push_str(&format!(
+                "\n\nYou are **#{}** with {} points.",
+                place + 1,
+                scores[place].1
+            ));
+        }
+    }
+
+    let embed = CreateEmbed::new()
+        .title("🏆 Quiz Leaderboard")
+        .description(description)
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} player{} in this server.",
+            scores.len(),
+            if scores.len() == 1 { "" } else { "s" }
+        )));
+
+    let data = CreateInteractionResponseMessage::new().embed(embed);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("leaderboard")
+        .description("See who has the most /quiz points in this server")
+        .dm_permission(false)
+}
@@ -0,0 +1,399 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::verse_navigation::parse_lookup_id;
+use crate::discord_helpers::{ephemeral_button_response, ephemeral_response};
+use crate::memory::{craft_review_embed, due_count, next_due, schedule, send_review};
+use crate::nay;
+use crate::saved_passage::SavedPassage;
+use crate::userfile::{MemoryCard, UserSettings};
+use bible_lib::Bible;
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, ResolvedOption, ResolvedValue, UserId,
+};
+
+// the most verses a deck can hold
+const MAX_CARDS: usize = 50;
+// longer passages are too much to recall in one go
+const MAX_PASSAGE_CHARS: usize = 1000;
+
+const NO_CARDS: &str = "Your memory deck is empty. Add a verse with `/memory add`.";
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+    }) = options.first()
+    else {
+        ephemeral_response(
+            ctx,
+            cmd,
+            "You must choose what to do with your memory deck!",
+        )
+        .await;
+        return;
+    };
+
+    // optional values
+    let mut reference = None;
+    let mut card = None;
+    let mut paused = None;
+    for option in sub_options {
+        match (option.name, &option.value) {
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("card", ResolvedValue::String(value)) => card = Some(*value),
+            ("paused", ResolvedValue::Boolean(value)) => paused = Some(*value),
+            _ => {}
+        }
+    }
+
+    let mut settings = UserSettings::get(&cmd.user.id);
+    let msg = match *subcommand {
+        "add" => {
+            let Some(reference) = reference else {
+                ephemeral_response(ctx, cmd, "You must specify a reference!").await;
+                return;
+            };
+            add(&mut settings, reference, bible, aliases)
+        }
+        "remove" => {
+            let Some(card) = card else {
+                ephemeral_response(ctx, cmd, "You must choose a verse to remove!").await;
+                return;
+            };
+            remove(&mut settings, card, bible, aliases)
+        }
+        "list" => {
+            let Some(embed) = craft_deck_embed(&settings, bible, aliases) else {
+                ephemeral_response(ctx, cmd, NO_CARDS).await;
+                return;
+            };
+            let data = CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .ephemeral(true);
+            if let Err(e) = cmd
+                .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+                .await
+            {
+                nay!("Failed to respond to command: {}", e);
+            }
+            return;
+        }
+        "review" => {
+            // practising early is fine, so this doesn't wait for a card to be due
+            let Some(card) = settings
+                .get_memory_deck()
+                .iter()
+                .min_by_key(|card| card.due)
+                .cloned()
+            else {
+                ephemeral_response(ctx, cmd, NO_CARDS).await;
+                return;
+            };
+            if send_review(ctx, cmd.user.id, &card, bible, aliases).await {
+                format!("Sent **{}** to your DMs.", card.lookup(bible, aliases))
+            } else {
+                "Couldn't send you a DM. Check that you allow direct messages from server members."
+                    .to_string()
+            }
+        }
+        "pause" => {
+            let paused = paused.unwrap_or(!settings.is_memory_paused());
+            settings.set_memory_paused(paused);
+            if paused {
+                "Memory verse reviews are paused. Your deck is kept, and `/memory review` still works."
+                    .to_string()
+            } else {
+                "Memory verse reviews will be sent to your DMs again as they come due.".to_string()
+            }
+        }
+        _ => {
+            nay!("Unknown memory subcommand: {}", subcommand);
+            return;
+        }
+    };
+
+    ephemeral_response(ctx, cmd, msg).await;
+}
+
+fn add(
+    settings: &mut UserSettings,
+    reference: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> String {
+    let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() else {
+        return format!(
+            "No reference found in `{}`. Try something like `John 3:16`",
+            reference
+        );
+    };
+    let Ok(text) = bible.get_verse(lookup.clone(), false) else {
+        return explain_invalid_reference(&lookup, bible, aliases).0;
+    };
+    if text.chars().count() > MAX_PASSAGE_CHARS {
+        return format!(
+            "**{}** is too long to learn in one go. Try splitting it into a few shorter passages.",
+            lookup
+        );
+    }
+    if settings.get_memory_deck().len() >= MAX_CARDS {
+        return format!(
+            "Your memory deck can hold at most {} verses. Remove some with `/memory remove` first.",
+            MAX_CARDS
+        );
+    }
+
+    if !settings.add_memory_card(MemoryCard::new(&lookup, aliases)) {
+        return format!("**{}** is already in your memory deck.", lookup);
+    }
+    let mut msg = format!(
+        "Added **{}** to your memory deck. Reviews will be sent to your DMs, starting within a few minutes.",
+        lookup
+    );
+    if settings.is_memory_paused() {
+        msg.push_str(" Reviews are paused, turn them back on with `/memory pause`.");
+    }
+    msg
+}
+
+fn remove(settings: &mut UserSettings, card: &str, bible: &Bible, aliases: &BookAliases) -> String {
+    // autocomplete gives the card's number, but a reference can be typed too
+    let index = match card.trim().parse::<usize>() {
+        Ok(number) => number.checked_sub(1),
+        Err(_) => detect_verses(card, aliases, bible)
+            .into_iter()
+            .next()
+            .and_then(|lookup| {
+                let target = SavedPassage::new(&lookup, aliases);
+                settings
+                    .get_memory_deck()
+                    .iter()
+                    .position(|existing| existing.passage == target)
+            }),
+    };
+
+    match index.and_then(|index| settings.remove_memory_card(index)) {
+        Some(removed) => format!(
+            "Removed **{}** from your memory deck.",
+            removed.lookup(bible, aliases)
+        ),
+        None => format!("`{}` isn't in your memory deck.", card),
+    }
+}
+
+/// Every card in a user's deck, with when it is next due
+/// Returns None if the deck is empty
+fn craft_deck_embed(
+    settings: &UserSettings,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<CreateEmbed> {
+    let deck = settings.get_memory_deck();
+    if deck.is_empty() {
+        return None;
+    }
+    let now = chrono::Utc::now().timestamp();
+
+    let list = deck
+        .iter()
+        .enumerate()
+        .map(|(x, card)| {
+            let status = if card.sent.is_some() {
+                "waiting for your answer".to_string()
+            } else if card.due <= now {
+                "due now".to_string()
+            } else {
+                format!("due <t:{}:R>", card.due)
+            };
+            format!(
+                "{}. **{}** — {}",
+                x + 1,
+                card.lookup(bible, aliases),
+                status
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let mut footer = format!(
+        "{} verse{}, {} due.",
+        deck.len(),
+        if deck.len() == 1 { "" } else { "s" },
+        due_count(deck, now)
+    );
+    if settings.is_memory_paused() {
+        footer.push_str(" Reviews are paused.");
+    }
+
+    Some(
+        CreateEmbed::new()
+            .title("🧠 Your memory deck")
+            .description(list.join("\n"))
+            .color(Colour::DARK_GREEN)
+            .footer(CreateEmbedFooter::new(footer)),
+    )
+}
+
+/// Records the answer from a `memory:got:` or `memory:missed:` button,
+/// then sends the next review if another card is due
+pub async fn run_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((remembered, lookup)) = args.split_once(':').and_then(|(action, lookup)| {
+        let remembered = match action {
+            "got" => true,
+            "missed" => false,
+            _ => return None,
+        };
+        Some((remembered, parse_lookup_id(lookup)?))
+    }) else {
+        nay!("Invalid memory button: {}", args);
+        return;
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let mut settings = UserSettings::get(&component.user.id);
+    // only reviews waiting for an answer count, so pressing a button twice doesn't double the interval
+    let mut answered = false;
+    let Some(card) = settings.update_memory_card(&SavedPassage::new(&lookup, aliases), |card| {
+        if card.sent.is_some() {
+            schedule(card, remembered, now);
+            answered = true;
+        }
+    }) else {
+        ephemeral_button_response(
+            ctx,
+            component,
+            "That verse isn't in your memory deck anymore.",
+        )
+        .await;
+        return;
+    };
+    if !answered {
+        ephemeral_button_response(ctx, component, "You've already answered this review.").await;
+        return;
+    }
+
+    let content = if remembered {
+        format!("✅ Well done! The next review is <t:{}:R>.", card.due)
+    } else {
+        "❌ Keep at it, this one will come back within the hour.".to_string()
+    };
+    let mut data = CreateInteractionResponseMessage::new()
+        .content(content)
+        .components(Vec::new());
+    if let Some(embed) = craft_review_embed(&card, bible, aliases, false) {
+        data = data.embed(embed);
+    }
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+
+    send_next_review(ctx, component.user.id, bible, aliases).await;
+}
+
+/// Sends a user their next due card straight away, rather than at the next check
+async fn send_next_review(ctx: &Context, user: UserId, bible: &Bible, aliases: &BookAliases) {
+    let settings = UserSettings::get(&user);
+    if settings.is_memory_paused() {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    if let Some(card) = next_due(settings.get_memory_deck(), now) {
+        send_review(ctx, user, card, bible, aliases).await;
+    }
+}
+
+/// The card choices for `/memory remove`, numbered like `/memory list`
+pub fn card_choices(
+    user: UserId,
+    partial: &str,
+    bible: &Bible,
+    aliases: &BookAliases,
+    limit: usize,
+) -> Vec<(String, String)> {
+    let partial = partial.trim().to_lowercase();
+    UserSettings::get(&user)
+        .get_memory_deck()
+        .iter()
+        .enumerate()
+        .map(|(x, card)| (format!("{}. {}", x + 1, card.lookup(bible, aliases)), x))
+        .filter(|(label, _)| label.to_lowercase().contains(&partial))
+        .take(limit)
+        .map(|(label, x)| (label, (x + 1).to_string()))
+        .collect()
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("memory")
+        .description("Learn verses by heart with reviews sent to your DMs")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Add a verse to your memory deck",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "reference",
+                    "The verse or short passage to learn, i.e. Psalm 119:11",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Remove a verse from your memory deck",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(CommandOptionType::String, "card", "The verse to remove")
+                    .set_autocomplete(true)
+                    .required(true),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "See the verses in your memory deck and when they are due",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "review",
+            "Review your next verse now, without waiting for it to be due",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "pause",
+                "Stop or start the reviews sent to your DMs",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "paused",
+                    "Whether reviews are paused (leave out to switch)",
+                )
+                .required(false),
+            ),
+        )
+        .dm_permission(true)
+}
@@ -1,141 +1,306 @@
 use crate::{
-    discord_helpers::{command_response, craft_bible_verse_embed},
+    books::{aliases::BookAliases, fuzzy::explain_invalid_reference},
+    discord_helpers::{
+        command_response, command_response_with_buttons, craft_bible_verse_embed,
+        delivery::{is_long_passage, respond_with_passage, PassagePages},
+        ephemeral_button_response, group_verse_embeds, suggestion_buttons,
+        verse_navigation::{
+            parse_translated_lookup_id, translated_lookup_id, verse_navigation_buttons,
+        },
+        MAX_BUTTON_ROWS,
+    },
+    guildfile::{DeliveryPolicy, GuildSettings},
     nay,
+    translations::Translations,
 };
 use bible_lib::{Bible, BibleLookup};
 use serenity::all::{
-    ChannelId, Colour, CommandInteraction, Context, CreateEmbed, CreateEmbedFooter,
-    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    CommandInteraction, ComponentInteraction, Context, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseFollowup, CreateInteractionResponseMessage, GuildId,
 };
 
+pub(crate) mod autocomplete;
+pub(crate) mod book_alias;
+pub(crate) mod book_info;
+pub(crate) mod bookmark;
 pub(crate) mod chapter;
+pub(crate) mod collection;
+pub(crate) mod commentary;
+pub(crate) mod define;
+pub(crate) mod detection_settings;
+pub(crate) mod export;
+pub(crate) mod leaderboard;
+pub(crate) mod memory;
+pub(crate) mod navigation;
+pub(crate) mod passage;
+pub(crate) mod prayer;
+pub(crate) mod quiz;
 pub(crate) mod random_verse;
 pub(crate) mod reading_calc;
 pub(crate) mod register_channel;
+pub(crate) mod stats;
+pub(crate) mod strongs;
+pub(crate) mod topic;
+pub(crate) mod verse_image;
+pub(crate) mod word;
+pub(crate) mod xref;
+
+/// Whether a guild has turned on the "Commentary" button for verse embeds
+pub fn commentary_enabled(guild: Option<GuildId>) -> bool {
+    guild.is_some_and(|id| {
+        GuildSettings::get(&id)
+            .get_detection_settings()
+            .commentary_button
+    })
+}
+
+/// How a guild wants passages too long for one message to be sent (paginated outside of guilds)
+pub fn long_passage_policy(guild: Option<GuildId>) -> DeliveryPolicy {
+    guild
+        .map(|id| {
+            GuildSettings::get(&id)
+                .get_detection_settings()
+                .long_passages
+        })
+        .unwrap_or_default()
+}
 
 pub async fn send_bible_verse(
     bible_lookup: BibleLookup,
     ctx: &Context,
     cmd: &CommandInteraction,
     bible: &Bible,
+    aliases: &BookAliases,
 ) {
-    let Some(embed) = craft_bible_verse_embed(bible_lookup.clone(), bible) else {
-        command_response(ctx, cmd, format!("Verse not found: {}", bible_lookup)).await;
+    send_bible_verses(vec![bible_lookup], ctx, cmd, bible, aliases, false).await;
+}
+
+/// Responds with the embeds for several verses, followed up with more messages if needed
+/// References that can't be found are explained, with buttons for any suggested books,
+/// and passages too long to go with the other verses are delivered by the guild's policy
+pub async fn send_bible_verses(
+    lookups: Vec<BibleLookup>,
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+    ephemeral: bool,
+) {
+    let mut explanations = Vec::new();
+    let mut buttons = Vec::new();
+    let mut found = Vec::new();
+    let mut long = Vec::new();
+    for lookup in lookups {
+        if is_long_passage(&lookup, bible) {
+            if let Some(pages) = PassagePages::new(&lookup, bible) {
+                long.push(pages);
+                continue;
+            }
+        }
+        if craft_bible_verse_embed(lookup.clone(), bible).is_some() {
+            found.push(lookup);
+            continue;
+        }
+
+        let (explanation, suggestions) = explain_invalid_reference(&lookup, bible, aliases);
+        explanations.push(explanation);
+        // discord allows 5 rows of buttons
+        if buttons.len() < MAX_BUTTON_ROWS {
+            buttons.extend(suggestion_buttons(&suggestions, |book| {
+                verse_button_id(
+                    &BibleLookup {
+                        book: book.to_string(),
+                        ..lookup.clone()
+                    },
+                    bible,
+                )
+            }));
+        }
+    }
+
+    let mut groups = group_verse_embeds(
+        found,
+        bible,
+        MAX_BUTTON_ROWS,
+        commentary_enabled(cmd.guild_id),
+    )
+    .into_iter()
+    .peekable();
+    let policy = long_passage_policy(cmd.guild_id);
+
+    // a long passage on its own is the whole response
+    if explanations.is_empty() && groups.peek().is_none() && !long.is_empty() {
+        let first = long.remove(0);
+        respond_with_passage(ctx, cmd, &first, policy, bible, false, ephemeral).await;
+        for pages in long {
+            respond_with_passage(ctx, cmd, &pages, policy, bible, true, ephemeral).await;
+        }
         return;
-    };
+    }
+
+    // the first message is the response, with any explanations
+    let mut data = CreateInteractionResponseMessage::new()
+        .content(explanations.join("\n"))
+        .ephemeral(ephemeral);
+    if let Some(group) = groups.next() {
+        // suggestions come first, then as many of the verses' buttons as fit
+        buttons.extend(group.buttons);
+        data = data.embeds(group.embeds);
+    }
+    buttons.truncate(MAX_BUTTON_ROWS);
+    data = data.components(buttons);
 
     let msg = cmd
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().embed(embed),
-            ),
-        )
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
         .await;
     if let Err(e) = msg {
         command_response(ctx, cmd, format!("Failed to send message: {}", e)).await;
         nay!("Failed to send message: {}", e);
+        return;
+    }
+
+    for group in groups {
+        let builder = CreateInteractionResponseFollowup::new()
+            .embeds(group.embeds)
+            .components(group.buttons)
+            .ephemeral(ephemeral);
+        if let Err(e) = cmd.create_followup(&ctx.http, builder).await {
+            nay!("Failed to send message: {}", e);
+        }
+    }
+
+    for pages in long {
+        respond_with_passage(ctx, cmd, &pages, policy, bible, true, ephemeral).await;
     }
 }
 
-pub async fn send_bible_chapter<S: Into<String>>(
-    book: S,
-    chapter: u32,
+/// The id of a button that shows a verse in a bible's translation
+pub fn verse_button_id(lookup: &BibleLookup, bible: &Bible) -> String {
+    format!("verse:{}", translated_lookup_id(lookup, bible))
+}
+
+/// Shows the verse from a `verse:` button in place of the message it was on
+pub async fn run_verse_button(
+    args: &str,
     ctx: &Context,
-    cmd: &CommandInteraction,
-    channel: &ChannelId,
-    bible: &Bible,
+    component: &ComponentInteraction,
+    translations: &Translations,
 ) {
-    let book = book.into();
-    let book = book.to_lowercase();
-    let book = book.as_str();
-    let Ok(mut chapter_text) = bible.get_chapter(book, chapter, true) else {
-        command_response(
+    let Some((translation, lookup)) = parse_translated_lookup_id(args) else {
+        nay!("Invalid verse button: {}", args);
+        ephemeral_button_response(ctx, component, "Sorry, that button doesn't work anymore.").await;
+        return;
+    };
+    let bible = &*translations.get_or_default(translation);
+
+    let Some(embed) = craft_bible_verse_embed(lookup.clone(), bible) else {
+        nay!("Verse from a button was not found: {}", lookup);
+        ephemeral_button_response(
             ctx,
-            cmd,
-            format!(
-                "Chapter not found: {} {} (not found in {} books)",
-                book,
-                chapter,
-                bible.get_books().len()
-            ),
+            component,
+            format!("Sorry, I couldn't find {}.", lookup),
         )
         .await;
         return;
     };
 
-    // split the message into multiple messages if it's too long (4096 characters)
-    let mut messages = Vec::new();
-
-    while chapter_text.len() > 4096 {
-        let split_at = chapter_text.char_indices().nth(4096).unwrap().0;
-        let clone = chapter_text.clone();
-        let (msg, rest) = clone.split_at(split_at);
-        chapter_text = rest.to_string();
-        messages.push(msg.to_string());
+    let data = CreateInteractionResponseMessage::new()
+        .content("")
+        .embed(embed)
+        .components(verse_navigation_buttons(
+            0,
+            &lookup,
+            bible,
+            commentary_enabled(component.guild_id),
+        ));
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
     }
-    messages.push(chapter_text);
-
-    // send the first value in messages
-    let title = if messages.len() > 1 {
-        format!(
-            "📖 {} {} (1/{})",
-            BibleLookup::capitalize_book(&book.to_string()),
-            chapter,
-            messages.len()
-        )
-    } else {
-        format!(
-            "📖 {} {}",
-            BibleLookup::capitalize_book(&book.to_string()),
-            chapter
-        )
+}
+
+/// The id of a button that opens a verse in a new message, in a bible's translation
+pub fn open_verse_button_id(lookup: &BibleLookup, bible: &Bible) -> String {
+    format!("open_verse:{}", translated_lookup_id(lookup, bible))
+}
+
+/// Opens the verse from an `open_verse:` button in a message only the user sees,
+/// so the message the button was on stays put
+pub async fn run_open_verse_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    translations: &Translations,
+) {
+    let Some((translation, lookup)) = parse_translated_lookup_id(args) else {
+        nay!("Invalid verse button: {}", args);
+        ephemeral_button_response(ctx, component, "Sorry, that button doesn't work anymore.").await;
+        return;
     };
-    let embed = CreateEmbed::new()
-        .title(title)
-        .description(messages[0].clone())
-        .color(Colour::GOLD)
-        .footer(CreateEmbedFooter::new(
-            "From the King James Version Bible.".to_string(),
-        ));
+    let bible = &*translations.get_or_default(translation);
 
-    let msg = cmd
-        .create_response(
-            &ctx.http,
-            CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().embed(embed),
-            ),
+    let Some(embed) = craft_bible_verse_embed(lookup.clone(), bible) else {
+        nay!("Verse from a button was not found: {}", lookup);
+        ephemeral_button_response(
+            ctx,
+            component,
+            format!("Sorry, I couldn't find {}.", lookup),
         )
         .await;
-    if let Err(e) = &msg {
-        command_response(ctx, cmd, format!("Failed to send message: {}", e)).await;
-        nay!("Failed to send message: {}", e);
-    }
+        return;
+    };
 
-    if messages.len() > 1 {
-        for x in 1..messages.len() {
-            let chunk = &messages[x];
-            let embed = CreateEmbed::new()
-                .title(format!(
-                    "📖 {} {} ({}/{})",
-                    BibleLookup::capitalize_book(&book.to_string()),
-                    chapter,
-                    x + 1,
-                    messages.len()
-                ))
-                .description(chunk)
-                .color(Colour::GOLD)
-                .footer(CreateEmbedFooter::new(
-                    "From the King James Version Bible.".to_string(),
-                ));
-
-            let builder = CreateMessage::new().embed(embed);
-
-            let msg = channel.send_message(&ctx.http, builder).await;
-            if let Err(e) = &msg {
-                command_response(ctx, cmd, format!("Failed to send message: {}", e)).await;
-                nay!("Failed to send message: {}", e);
-            }
-        }
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(verse_navigation_buttons(
+            0,
+            &lookup,
+            bible,
+            commentary_enabled(component.guild_id),
+        ))
+        .ephemeral(true);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
     }
 }
+
+/// Creates the embeds for a chapter, split into pages between verses if it's too long
+/// Returns None if the chapter doesn't exist
+pub fn craft_bible_chapter_embeds(
+    book: &str,
+    chapter: u32,
+    bible: &Bible,
+) -> Option<Vec<CreateEmbed>> {
+    Some(PassagePages::chapter(book, chapter, bible)?.embeds(bible))
+}
+
+/// Responds with a chapter, delivered by the guild's policy if it's too long for one message
+pub async fn send_bible_chapter<S: Into<String>>(
+    book: S,
+    chapter: u32,
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let book = book.into();
+    let book = book.to_lowercase();
+    let book = book.as_str();
+    let Some(pages) = PassagePages::chapter(book, chapter, bible) else {
+        let lookup = BibleLookup::new(book, chapter, 1);
+        let (explanation, suggestions) = explain_invalid_reference(&lookup, bible, aliases);
+        let buttons = suggestion_buttons(&suggestions, |book| {
+            chapter::chapter_button_id(book, chapter)
+        });
+        command_response_with_buttons(ctx, cmd, explanation, buttons).await;
+        return;
+    };
+
+    let policy = long_passage_policy(cmd.guild_id);
+    respond_with_passage(ctx, cmd, &pages, policy, bible, false, false).await;
+}
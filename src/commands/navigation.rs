@@ -0,0 +1,150 @@
+use crate::commands::{commentary_enabled, long_passage_policy};
+use crate::discord_helpers::delivery::{respond_to_button_with_passage, PassagePages};
+use crate::discord_helpers::verse_navigation::{parse_navigation_button, verse_navigation_buttons};
+use crate::discord_helpers::{
+    craft_bible_verse_embed, EMBED_OVERHEAD, MAX_BUTTON_ROWS, MAX_EMBED_CHARS_PER_MESSAGE,
+    MAX_VERSE_EMBED_CHARS,
+};
+use crate::nay;
+use crate::translations::Translations;
+use serenity::all::{
+    ActionRow, ActionRowComponent, Button, ButtonKind, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+
+/// Handles the "Show context", "Previous verse", "Next verse" and "Full chapter" buttons
+/// The verse embed the button belongs to is replaced, leaving the rest of the message as it was
+pub async fn run_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    translations: &Translations,
+) {
+    let Some((index, action, translation, lookup)) = parse_navigation_button(args) else {
+        nay!("Invalid navigation button: {}", args);
+        return;
+    };
+    let bible = &*translations.get_or_default(translation);
+    let message = &component.message;
+    let commentary = commentary_enabled(component.guild_id);
+
+    if action == "chapter" {
+        let Some(pages) = PassagePages::chapter(&lookup.book, lookup.chapter, bible) else {
+            nay!("Chapter from a button was not found: {}", lookup);
+            return;
+        };
+
+        // a lone verse becomes the chapter, otherwise the other verses would be lost
+        let replace = message.embeds.len() <= 1;
+        let policy = long_passage_policy(component.guild_id);
+        respond_to_button_with_passage(ctx, component, &pages, policy, bible, replace).await;
+        return;
+    }
+
+    let Some(embed) = craft_bible_verse_embed(lookup.clone(), bible) else {
+        nay!("Verse from a button was not found: {}", lookup);
+        return;
+    };
+
+    // make sure the message stays within discord's limit with the new verse
+    let length = bible
+        .get_verse(lookup.clone(), true)
+        .map(|text| text.len())
+        .unwrap_or(0)
+        .min(MAX_VERSE_EMBED_CHARS);
+    let others = message
+        .embeds
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, embed)| embed.description.as_ref().map_or(0, |text| text.len()))
+        .sum::<usize>();
+    let fits =
+        others + length + EMBED_OVERHEAD * message.embeds.len() <= MAX_EMBED_CHARS_PER_MESSAGE;
+
+    let response = if index < message.embeds.len() && fits {
+        let mut embeds: Vec<CreateEmbed> = message
+            .embeds
+            .iter()
+            .cloned()
+            .map(CreateEmbed::from)
+            .collect();
+        embeds[index] = embed;
+
+        let buttons = replace_navigation_rows(
+            &message.components,
+            index,
+            verse_navigation_buttons(index, &lookup, bible, commentary),
+        );
+
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .embeds(embeds)
+                .components(buttons),
+        )
+    } else {
+        // too long to fit with the rest of the message, so just show it to whoever asked
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(verse_navigation_buttons(0, &lookup, bible, commentary))
+                .ephemeral(true),
+        )
+    };
+
+    if let Err(e) = component.create_response(&ctx.http, response).await {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// The buttons of a message with the navigation rows of one embed replaced
+/// (its navigation row, and the commentary row after it if it has one)
+fn replace_navigation_rows(
+    rows: &[ActionRow],
+    index: usize,
+    mut replacement: Vec<CreateActionRow>,
+) -> Vec<CreateActionRow> {
+    // the commentary button may have been turned on since the message was sent, leaving no room for it
+    if rows.len() + replacement.len() - 1 > MAX_BUTTON_ROWS {
+        replacement.truncate(1);
+    }
+
+    let prefix = format!("nav:{}:", index);
+    let mut buttons = Vec::new();
+    let mut replaced = false;
+    for row in rows {
+        let row_buttons: Vec<&Button> = row
+            .components
+            .iter()
+            .filter_map(|component| match component {
+                ActionRowComponent::Button(button) => Some(button),
+                _ => None,
+            })
+            .collect();
+        let has_button = |start: &str| {
+            row_buttons.iter().any(|button| match &button.data {
+                ButtonKind::NonLink { custom_id, .. } => custom_id.starts_with(start),
+                _ => false,
+            })
+        };
+
+        if has_button(&prefix) {
+            buttons.extend(replacement.iter().cloned());
+            replaced = true;
+            continue;
+        }
+        if std::mem::take(&mut replaced) && has_button("commentary:") {
+            continue;
+        }
+
+        buttons.push(CreateActionRow::Buttons(
+            row_buttons
+                .into_iter()
+                .cloned()
+                .map(CreateButton::from)
+                .collect(),
+        ));
+    }
+    buttons
+}
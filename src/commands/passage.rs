@@ -0,0 +1,100 @@
+use crate::books::aliases::BookAliases;
+use crate::commands::send_bible_verses;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::command_response;
+use crate::translations::{Translations, TRANSLATION_CHOICES};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    ResolvedOption, ResolvedValue,
+};
+
+// the most references a single `/passage` will show
+const MAX_REFERENCES: usize = 20;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    translations: &Translations,
+    aliases: &BookAliases,
+) {
+    let mut reference = None;
+    let mut translation = None;
+    let mut ephemeral = false;
+    for option in options {
+        match (option.name, &option.value) {
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("translation", ResolvedValue::String(value)) => translation = Some(*value),
+            ("ephemeral", ResolvedValue::Boolean(value)) => ephemeral = *value,
+            _ => {}
+        }
+    }
+
+    let Some(reference) = reference else {
+        command_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+
+    let Some(bible) = translations.get(translation) else {
+        command_response(ctx, cmd, "That translation is not available.").await;
+        return;
+    };
+
+    let found = detect_verses(reference, aliases, &bible);
+    if found.is_empty() {
+        command_response(
+            ctx,
+            cmd,
+            format!(
+                "No references found in `{}`. Try something like `John 3:16` or `Rom 8:28-30; Ps 23:1`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    }
+
+    // repeated references would give repeated button ids, which discord rejects
+    let mut lookups = Vec::new();
+    for lookup in found {
+        if !lookups.contains(&lookup) {
+            lookups.push(lookup);
+        }
+    }
+    lookups.truncate(MAX_REFERENCES);
+
+    send_bible_verses(lookups, ctx, cmd, &bible, aliases, ephemeral).await;
+}
+
+pub fn register() -> CreateCommand {
+    let translation = TRANSLATION_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "translation",
+            "The translation to read from",
+        )
+        .required(false),
+        |option, (name, code)| option.add_string_choice(*name, *code),
+    );
+
+    CreateCommand::new("passage")
+        .description("Look up a passage, i.e. `John 3:16` or `John 3:16; Rom 8:28`")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The reference(s) to look up, separated by semicolons",
+            )
+            .required(true),
+        )
+        .add_option(translation)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "ephemeral",
+                "Only show the passage to you",
+            )
+            .required(false),
+        )
+        .dm_permission(true)
+}
@@ -0,0 +1,572 @@
+use crate::discord_helpers::{ephemeral_button_response, ephemeral_response};
+use crate::guildfile::{GuildSettings, PrayerRequest};
+use crate::nay;
+use serenity::all::{
+    ButtonStyle, ChannelId, Colour, CommandInteraction, CommandOptionType, ComponentInteraction,
+    Context, CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    EditMessage, GuildId, MessageId, ResolvedOption, ResolvedValue, Timestamp, UserId,
+};
+
+const MAX_REQUEST_CHARS: usize = 1000;
+const MAX_FOLLOW_UP_CHARS: usize = 500;
+// the most requests kept per server, older answered requests are removed to make room
+const MAX_REQUESTS: usize = 200;
+// how many requests `/prayer list` shows
+const LIST_LIMIT: usize = 15;
+const SNIPPET_CHARS: usize = 80;
+const DAY: i64 = 24 * 60 * 60;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    guild: &GuildId,
+) {
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+    }) = options.first()
+    else {
+        ephemeral_response(ctx, cmd, "You must choose what to do with prayer requests!").await;
+        return;
+    };
+
+    // optional values
+    let mut text = None;
+    let mut anonymous = false;
+    let mut request = None;
+    let mut follow_up = None;
+    let mut answered = false;
+    let mut days = None;
+    for option in sub_options {
+        match (option.name, &option.value) {
+            ("text", ResolvedValue::String(value)) => text = Some(value.trim()),
+            ("anonymous", ResolvedValue::Boolean(value)) => anonymous = *value,
+            ("request", ResolvedValue::String(value)) => request = Some(*value),
+            ("follow_up", ResolvedValue::String(value)) => follow_up = Some(value.trim()),
+            ("answered", ResolvedValue::Boolean(value)) => answered = *value,
+            ("days", ResolvedValue::Integer(value)) => days = Some(*value),
+            _ => {}
+        }
+    }
+
+    let can_manage = cmd
+        .member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|permissions| permissions.manage_guild());
+
+    let msg = match *subcommand {
+        "request" => {
+            let Some(text) = text.filter(|text| !text.is_empty()) else {
+                ephemeral_response(ctx, cmd, "You must write your prayer request!").await;
+                return;
+            };
+            post_request(ctx, guild, cmd.user.id, text, anonymous).await
+        }
+        "answered" => {
+            let Some(request) = request else {
+                ephemeral_response(ctx, cmd, "You must choose a prayer request!").await;
+                return;
+            };
+            let follow_up = follow_up.filter(|follow_up| !follow_up.is_empty());
+            mark_answered(ctx, guild, cmd.user.id, can_manage, request, follow_up).await
+        }
+        "list" => {
+            let settings = GuildSettings::get(guild);
+            let data = match craft_request_list(&settings, guild, answered) {
+                Some(embed) => CreateInteractionResponseMessage::new().embed(embed),
+                None if answered => CreateInteractionResponseMessage::new()
+                    .content("No prayer requests have been marked answered yet."),
+                None => CreateInteractionResponseMessage::new().content(
+                    "There are no open prayer requests. Share one with `/prayer request`.",
+                ),
+            };
+            if let Err(e) = cmd
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(data.ephemeral(true)),
+                )
+                .await
+            {
+                nay!("Failed to respond to command: {}", e);
+            }
+            return;
+        }
+        "expiry" => {
+            if !can_manage {
+                ephemeral_response(
+                    ctx,
+                    cmd,
+                    "You need the Manage Server permission to change when prayer requests close.",
+                )
+                .await;
+                return;
+            }
+            let mut settings = GuildSettings::get(guild);
+            match days.filter(|days| *days > 0) {
+                Some(days) => {
+                    settings.set_prayer_expiry_days(Some(days as u32));
+                    format!(
+                        "Prayer requests will now close {} day{} after they are posted.",
+                        days,
+                        if days == 1 { "" } else { "s" }
+                    )
+                }
+                None => {
+                    settings.set_prayer_expiry_days(None);
+                    "Prayer requests will stay open until they are marked answered.".to_string()
+                }
+            }
+        }
+        _ => {
+            nay!("Unknown prayer subcommand: {}", subcommand);
+            return;
+        }
+    };
+
+    ephemeral_response(ctx, cmd, msg).await;
+}
+
+/// A link to a message in a guild
+fn message_link(guild: &GuildId, channel: u64, message: u64) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        guild, channel, message
+    )
+}
+
+/// Posts a request to the guild's prayer channel, returning what to tell the user
+async fn post_request(
+    ctx: &Context,
+    guild: &GuildId,
+    user: UserId,
+    text: &str,
+    anonymous: bool,
+) -> String {
+    let mut settings = GuildSettings::get(guild);
+    let Some(channel) = settings.get_prayer_channel() else {
+        return "This server doesn't have a prayer channel yet. An admin can set one with `/register_channel prayer`."
+            .to_string();
+    };
+    if text.chars().count() > MAX_REQUEST_CHARS {
+        return format!(
+            "Prayer requests can be at most {} characters long.",
+            MAX_REQUEST_CHARS
+        );
+    }
+    let open = settings
+        .get_prayer_requests()
+        .iter()
+        .filter(|request| request.answered.is_none())
+        .count();
+    if open >= MAX_REQUESTS {
+        return "The prayer board is full. Ask a server manager to mark some requests answered or set them to close with `/prayer expiry`."
+            .to_string();
+    }
+
+    // the id is taken before the request is posted, as the message shows it
+    let mut request = PrayerRequest {
+        id: settings.reserve_prayer_request_id(),
+        author: user.get(),
+        anonymous,
+        text: text.to_string(),
+        channel: channel.get(),
+        message: 0,
+        praying: Vec::new(),
+        posted: chrono::Utc::now().timestamp(),
+        answered: None,
+    };
+    let (embed, buttons) = craft_prayer_message(&request, false);
+    let message = match channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new().embed(embed).components(buttons),
+        )
+        .await
+    {
+        Ok(message) => message,
+        Err(e) => {
+            nay!("Failed to post prayer request: {}", e);
+            return format!(
+                "Couldn't post in <#{}>. Check that I can send messages there.",
+                channel
+            );
+        }
+    };
+    request.message = message.id.get();
+    settings.add_prayer_request(request, MAX_REQUESTS);
+
+    format!(
+        "Your prayer request was posted in {}{}.",
+        message_link(guild, channel.get(), message.id.get()),
+        if anonymous { " without your name" } else { "" }
+    )
+}
+
+/// The request number from an option, i.e. `3` or `#3`
+fn parse_request_id(request: &str) -> Option<u32> {
+    request.trim().trim_start_matches('#').parse().ok()
+}
+
+/// Marks a request answered and shares the follow-up, returning what to tell the user
+async fn mark_answered(
+    ctx: &Context,
+    guild: &GuildId,
+    user: UserId,
+    can_manage: bool,
+    request: &str,
+    follow_up: Option<&str>,
+) -> String {
+    let mut settings = GuildSettings::get(guild);
+    let Some(existing) = parse_request_id(request)
+        .and_then(|id| settings.get_prayer_request(id))
+        .cloned()
+    else {
+        return format!("`{}` isn't an open prayer request.", request);
+    };
+    if existing.author != user.get() && !can_manage {
+        return "Only whoever asked, or a server manager, can mark a request answered.".to_string();
+    }
+    if existing.answered.is_some() {
+        return format!("Prayer request #{} is already answered.", existing.id);
+    }
+    if follow_up.is_some_and(|follow_up| follow_up.chars().count() > MAX_FOLLOW_UP_CHARS) {
+        return format!(
+            "Follow-ups can be at most {} characters long.",
+            MAX_FOLLOW_UP_CHARS
+        );
+    }
+
+    let Some(request) =
+        settings.answer_prayer_request(existing.id, follow_up.unwrap_or_default().to_string())
+    else {
+        return format!("`{}` isn't an open prayer request.", request);
+    };
+
+    // update the original post, then let the channel know
+    let channel = ChannelId::new(request.channel);
+    let message = MessageId::new(request.message);
+    let (embed, buttons) = craft_prayer_message(&request, false);
+    if let Err(e) = channel
+        .edit_message(
+            &ctx.http,
+            message,
+            EditMessage::new().embed(embed).components(buttons),
+        )
+        .await
+    {
+        nay!("Failed to edit prayer request: {}", e);
+    }
+    let mut announcement = format!("🙌 Prayer request #{} has been answered!", request.id);
+    if let Some(follow_up) = follow_up {
+        announcement.push_str(&format!("\n> {}", follow_up));
+    }
+    if let Err(e) = channel
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .content(announcement)
+                .reference_message((channel, message)),
+        )
+        .await
+    {
+        nay!("Failed to send message: {}", e);
+    }
+
+    format!("Marked prayer request #{} as answered.", request.id)
+}
+
+/// The post for a prayer request, with its "Praying" button while it is open
+/// Closed requests (past the guild's expiry) keep their text but lose the button
+pub fn craft_prayer_message(
+    request: &PrayerRequest,
+    closed: bool,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let from = if request.anonymous {
+        "Shared anonymously".to_string()
+    } else {
+        format!("Shared by <@{}>", request.author)
+    };
+    let praying = request.praying.len();
+
+    let mut embed = CreateEmbed::new()
+        .description(format!("{}\n\n*{}*", request.text, from))
+        .footer(CreateEmbedFooter::new(format!(
+            "{} praying{}",
+            praying,
+            if closed {
+                " · This request has closed"
+            } else {
+                ""
+            }
+        )));
+    if let Ok(timestamp) = Timestamp::from_unix_timestamp(request.posted) {
+        embed = embed.timestamp(timestamp);
+    }
+    embed = match &request.answered {
+        Some(follow_up) => {
+            let embed = embed
+                .title(format!("🙌 Prayer request #{} (answered)", request.id))
+                .color(Colour::GOLD);
+            if follow_up.is_empty() {
+                embed
+            } else {
+                embed.field("Answered", follow_up, false)
+            }
+        }
+        None => embed
+            .title(format!("🙏 Prayer request #{}", request.id))
+            .color(Colour::PURPLE),
+    };
+
+    let buttons = if closed || request.answered.is_some() {
+        Vec::new()
+    } else {
+        vec![CreateActionRow::Buttons(vec![CreateButton::new(format!(
+            "praying:{}",
+            request.id
+        ))
+        .label(format!("Praying 🙏 {}", praying))
+        .style(ButtonStyle::Primary)])]
+    };
+
+    (embed, buttons)
+}
+
+/// The open requests (or answered ones) of a guild, newest first, with links to them
+/// Returns None if there are none
+fn craft_request_list(
+    settings: &GuildSettings,
+    guild: &GuildId,
+    answered: bool,
+) -> Option<CreateEmbed> {
+    let requests: Vec<&PrayerRequest> = settings
+        .get_prayer_requests()
+        .iter()
+        .rev()
+        .filter(|request| request.answered.is_some() == answered)
+        .collect();
+    if requests.is_empty() {
+        return None;
+    }
+
+    let mut list = requests
+        .iter()
+        .take(LIST_LIMIT)
+        .map(|request| {
+            let mut snippet: String = request.text.chars().take(SNIPPET_CHARS).collect();
+            if request.text.chars().count() > SNIPPET_CHARS {
+                snippet.push('…');
+            }
+            format!(
+                "**[#{}]({})** {} — 🙏 {}",
+                request.id,
+                message_link(guild, request.channel, request.message),
+                snippet,
+                request.praying.len()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    if requests.len() > LIST_LIMIT {
+        list.push_str(&format!("\n…and {} more", requests.len() - LIST_LIMIT));
+    }
+
+    let mut footer = format!(
+        "{} {} request{}.",
+        requests.len(),
+        if answered { "answered" } else { "open" },
+        if requests.len() == 1 { "" } else { "s" }
+    );
+    if let Some(days) = settings.get_prayer_expiry_days() {
+        footer.push_str(&format!(" Requests close after {} days.", days));
+    }
+
+    Some(
+        CreateEmbed::new()
+            .title(if answered {
+                "🙌 Answered prayers"
+            } else {
+                "🙏 Prayer requests"
+            })
+            .description(list)
+            .color(if answered {
+                Colour::GOLD
+            } else {
+                Colour::PURPLE
+            })
+            .footer(CreateEmbedFooter::new(footer)),
+    )
+}
+
+/// Adds or removes whoever pressed a `praying:` button from those praying for the request
+pub async fn run_praying_button(args: &str, ctx: &Context, component: &ComponentInteraction) {
+    let Some(guild) = component.guild_id else {
+        return;
+    };
+    let Ok(id) = args.parse::<u32>() else {
+        nay!("Invalid praying button: {}", args);
+        return;
+    };
+
+    let Some(request) = GuildSettings::get(&guild).toggle_praying(id, component.user.id) else {
+        ephemeral_button_response(ctx, component, "This prayer request has closed.").await;
+        return;
+    };
+
+    let (embed, buttons) = craft_prayer_message(&request, false);
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// Closes the requests that are older than their guild's expiry, removing them from the board
+pub async fn close_expired_requests(ctx: &Context, guilds: &[GuildSettings]) {
+    let now = chrono::Utc::now().timestamp();
+    for guild in guilds {
+        let Some(days) = guild.get_prayer_expiry_days() else {
+            continue;
+        };
+        let mut settings = GuildSettings::get(&guild.id);
+        for request in settings.remove_prayer_requests_before(now - days as i64 * DAY) {
+            let (embed, buttons) = craft_prayer_message(&request, true);
+            if let Err(e) = ChannelId::new(request.channel)
+                .edit_message(
+                    &ctx.http,
+                    MessageId::new(request.message),
+                    EditMessage::new().embed(embed).components(buttons),
+                )
+                .await
+            {
+                nay!("Failed to close prayer request: {}", e);
+            }
+        }
+    }
+}
+
+/// The request choices for `/prayer answered`: open requests the user asked for,
+/// or every open request for server managers
+pub fn request_choices(
+    guild: &GuildId,
+    user: UserId,
+    can_manage: bool,
+    partial: &str,
+    limit: usize,
+) -> Vec<(String, String)> {
+    let partial = partial.trim().trim_start_matches('#').to_lowercase();
+    GuildSettings::get(guild)
+        .get_prayer_requests()
+        .iter()
+        .rev()
+        .filter(|request| request.answered.is_none())
+        .filter(|request| can_manage || request.author == user.get())
+        .filter_map(|request| {
+            let mut label = format!("#{} {}", request.id, request.text);
+            if !label.to_lowercase().contains(&partial) {
+                return None;
+            }
+            // discord allows at most 100 characters in a choice
+            if label.chars().count() > 100 {
+                label = label.chars().take(99).collect::<String>() + "…";
+            }
+            Some((label, request.id.to_string()))
+        })
+        .take(limit)
+        .collect()
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("prayer")
+        .description("Share prayer requests with the server")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "request",
+                "Post a prayer request in the server's prayer channel",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "text",
+                    "What you would like prayer for",
+                )
+                .max_length(MAX_REQUEST_CHARS as u16)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "anonymous",
+                    "Leave your name off the request",
+                )
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "answered",
+                "Mark a prayer request as answered",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "request",
+                    "The request that was answered",
+                )
+                .set_autocomplete(true)
+                .required(true),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "follow_up",
+                    "How it was answered",
+                )
+                .max_length(MAX_FOLLOW_UP_CHARS as u16)
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "See the server's open prayer requests",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "answered",
+                    "Show answered prayers instead",
+                )
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "expiry",
+                "Close prayer requests after some days (requires Manage Server)",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "days",
+                    "Days until requests close, leave out or 0 to keep them open",
+                )
+                .min_int_value(0)
+                .max_int_value(365)
+                .required(false),
+            ),
+        )
+        .dm_permission(false)
+}
@@ -0,0 +1,272 @@
+use std::sync::Mutex;
+
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::discord_helpers::{ephemeral_button_response, ephemeral_response};
+use crate::guildfile::GuildSettings;
+use crate::nay;
+use crate::quiz::{
+    choice_letter, generate, random_mode, Answer, Difficulty, Question, QuizMode, QuizSource,
+    QuizTracker, QUESTION_TIME,
+};
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedOption,
+    ResolvedValue,
+};
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    quizzes: &Mutex<QuizTracker>,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let mut mode = None;
+    let mut difficulty = Difficulty::Medium;
+    let mut book = None;
+    let mut collection = None;
+    for option in options {
+        match (option.name, &option.value) {
+            ("mode", ResolvedValue::String(code)) => mode = QuizMode::from_code(code),
+            ("difficulty", ResolvedValue::String(code)) => {
+                difficulty = Difficulty::from_code(code).unwrap_or(Difficulty::Medium)
+            }
+            ("book", ResolvedValue::String(value)) => book = Some(*value),
+            ("collection", ResolvedValue::String(value)) => collection = Some(value.trim()),
+            _ => {}
+        }
+    }
+    let mode = mode.unwrap_or_else(random_mode);
+
+    let source = match (book, collection) {
+        (Some(_), Some(_)) => {
+            ephemeral_response(ctx, cmd, "Choose either a book or a collection, not both!").await;
+            return;
+        }
+        (Some(_), None) if mode == QuizMode::Book => {
+            ephemeral_response(
+                ctx,
+                cmd,
+                "Guessing the book of a verse from a single book would be too easy! Try another mode.",
+            )
+            .await;
+            return;
+        }
+        (Some(book), None) => {
+            let Some(resolved) = aliases.resolve(book, bible) else {
+                let (explanation, _) =
+                    explain_invalid_reference(&BibleLookup::new(book, 1, 1), bible, aliases);
+                ephemeral_response(ctx, cmd, explanation).await;
+                return;
+            };
+            QuizSource::Bible {
+                book: Some(resolved),
+            }
+        }
+        (None, Some(name)) => {
+            let Some(guild) = cmd.guild_id else {
+                ephemeral_response(ctx, cmd, "Collections only work in servers.").await;
+                return;
+            };
+            let settings = GuildSettings::get(&guild);
+            let Some(collection) = settings.get_collection(name) else {
+                ephemeral_response(
+                    ctx,
+                    cmd,
+                    format!(
+                        "There is no collection called **{}**. See them all with `/collection show`.",
+                        name
+                    ),
+                )
+                .await;
+                return;
+            };
+            // questions are about single verses, so passages are split up
+            let verses = collection
+                .passages
+                .iter()
+                .flat_map(|passage| {
+                    let lookup = passage.lookup(bible, aliases);
+                    let last = lookup.thru_verse.unwrap_or(lookup.verse);
+                    (lookup.verse..=last)
+                        .map(move |verse| BibleLookup::new(&lookup.book, lookup.chapter, verse))
+                })
+                .collect();
+            QuizSource::Passages(verses)
+        }
+        (None, None) => QuizSource::Bible { book: None },
+    };
+
+    let Some(question) = generate(mode, difficulty, &source, bible) else {
+        ephemeral_response(
+            ctx,
+            cmd,
+            "Couldn't find a verse to ask about with those options. Try another mode, or a bigger book or collection.",
+        )
+        .await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(craft_question_embed(&question))
+        .components(answer_buttons(cmd.id.get(), &question));
+
+    // tracked before it's sent, so answers straight away aren't told the question has closed
+    match quizzes.lock() {
+        Ok(mut quizzes) => quizzes.track(cmd.id.get(), question),
+        Err(e) => nay!("Failed to lock quiz tracker: {}", e),
+    }
+
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+        match quizzes.lock() {
+            Ok(mut quizzes) => quizzes.forget(cmd.id.get()),
+            Err(e) => nay!("Failed to lock quiz tracker: {}", e),
+        }
+    }
+}
+
+fn craft_question_embed(question: &Question) -> CreateEmbed {
+    // the reference would give away which book it is
+    let title = match question.mode {
+        QuizMode::Book => format!("❓ {}", question.mode),
+        _ => format!("❓ {} — {}", question.mode, question.lookup),
+    };
+
+    let mut description = format!("> {}\n", question.prompt);
+    for (index, choice) in question.choices.iter().enumerate() {
+        description.push_str(&format!("\n**{})** {}", choice_letter(index), choice));
+    }
+
+    let points = question.difficulty.points();
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .color(Colour::BLUE)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} · {} point{} for the right answer · open for {} minutes",
+            question.difficulty,
+            points,
+            if points == 1 { "" } else { "s" },
+            QUESTION_TIME.as_secs() / 60
+        )))
+}
+
+/// One button per choice, formatted as `quiz:{question id}:{choice}`
+fn answer_buttons(id: u64, question: &Question) -> Vec<CreateActionRow> {
+    let buttons = (0..question.choices.len())
+        .map(|index| {
+            CreateButton::new(format!("quiz:{}:{}", id, index))
+                .label(choice_letter(index).to_string())
+                .style(ButtonStyle::Primary)
+        })
+        .collect();
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+pub async fn run_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    quizzes: &Mutex<QuizTracker>,
+) {
+    let Some((id, choice)) = args
+        .split_once(':')
+        .and_then(|(id, choice)| Some((id.parse::<u64>().ok()?, choice.parse::<usize>().ok()?)))
+    else {
+        nay!("Invalid quiz button: {}", args);
+        return;
+    };
+
+    let answer = match quizzes.lock() {
+        Ok(mut quizzes) => quizzes.answer(id, component.user.id, choice),
+        Err(e) => {
+            nay!("Failed to lock quiz tracker: {}", e);
+            return;
+        }
+    };
+
+    let msg = match answer {
+        Answer::Closed => "This question has closed. Ask a new one with `/quiz`.".to_string(),
+        Answer::AlreadyAnswered => "You've already answered this question!".to_string(),
+        Answer::Wrong(question) => {
+            format!("❌ Not quite, the answer was {}.", reveal_answer(&question))
+        }
+        Answer::Correct(question) => {
+            let points = question.difficulty.points();
+            let mut msg = format!("✅ Correct! The answer was {}.", reveal_answer(&question));
+            // scores are only kept for servers
+            if let Some(guild) = component.guild_id {
+                let total = GuildSettings::get(&guild).add_quiz_score(component.user.id, points);
+                msg.push_str(&format!(
+                    " +{} point{}, you have {} in this server.",
+                    points,
+                    if points == 1 { "" } else { "s" },
+                    total
+                ));
+            }
+            msg
+        }
+    };
+
+    ephemeral_button_response(ctx, component, msg).await;
+}
+
+/// The right choice and the verse it came from
+fn reveal_answer(question: &Question) -> String {
+    format!(
+        "**{}) {}** ({})",
+        choice_letter(question.answer),
+        question.choices[question.answer],
+        question.lookup
+    )
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("quiz")
+        .description("Test your memory of the Bible")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "mode", "The kind of question")
+                .add_string_choice("Fill in the blank", "blank")
+                .add_string_choice("Which book is this from?", "book")
+                .add_string_choice("Finish the verse", "finish")
+                .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "difficulty",
+                "How hard the question is (harder questions are worth more points)",
+            )
+            .add_string_choice("Easy", "easy")
+            .add_string_choice("Medium", "medium")
+            .add_string_choice("Hard", "hard")
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "book",
+                "Only ask about verses from this book",
+            )
+            .set_autocomplete(true)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "collection",
+                "Only ask about passages from this server collection",
+            )
+            .set_autocomplete(true)
+            .required(false),
+        )
+        .dm_permission(true)
+}
@@ -1,16 +1,246 @@
-use bible_lib::Bible;
-use serenity::all::{CommandInteraction, Context, CreateCommand};
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::books::metadata::{book_info, category_sections, Testament, CATEGORIES};
 use crate::commands::send_bible_verse;
+use crate::discord_helpers::command_response;
+use bible_lib::{Bible, BibleLookup};
+use rand::seq::IndexedRandom;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    ResolvedOption, ResolvedValue,
+};
+
+// a run of sentences is at least this many verses, unless the chapter ends first
+const SENTENCES_MIN_VERSES: u32 = 3;
+// and at most this many, even if the sentence carries on
+const SENTENCES_MAX_VERSES: u32 = 12;
+// runs stop growing past this many characters so they fit in an embed
+const SENTENCES_MAX_CHARS: usize = 1500;
+
+/// The options that narrow down which verses can be picked
+#[derive(Default)]
+struct Filters {
+    book: Option<String>,
+    testament: Option<Testament>,
+    category: Option<String>,
+    min_length: usize,
+}
+
+impl Filters {
+    fn allows_book(&self, book: &str) -> bool {
+        if self.book.as_ref().is_some_and(|only| only != book) {
+            return false;
+        }
+        if self.testament.is_none() && self.category.is_none() {
+            return true;
+        }
+
+        let Some(info) = book_info(book) else {
+            return false;
+        };
+        if self
+            .testament
+            .is_some_and(|testament| testament != info.testament)
+        {
+            return false;
+        }
+        match &self.category {
+            Some(category) => {
+                category_sections(category).is_some_and(|sections| sections.contains(&info.section))
+            }
+            None => true,
+        }
+    }
+}
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let mut filters = Filters::default();
+    let mut sentences = false;
+    for option in options {
+        match (option.name, &option.value) {
+            ("book", ResolvedValue::String(book)) => {
+                let Some(resolved) = aliases.resolve(book, bible) else {
+                    let (explanation, _) =
+                        explain_invalid_reference(&BibleLookup::new(*book, 1, 1), bible, aliases);
+                    command_response(ctx, cmd, explanation).await;
+                    return;
+                };
+                filters.book = Some(resolved);
+            }
+            ("testament", ResolvedValue::String(testament)) => {
+                filters.testament = match *testament {
+                    "old" => Some(Testament::Old),
+                    "new" => Some(Testament::New),
+                    _ => None,
+                }
+            }
+            ("category", ResolvedValue::String(category)) => {
+                filters.category = Some(category.to_string())
+            }
+            ("min_length", ResolvedValue::Integer(length)) => {
+                filters.min_length = (*length).max(0) as usize
+            }
+            ("whole_sentences", ResolvedValue::Boolean(value)) => sentences = *value,
+            _ => {}
+        }
+    }
+
+    // get a random verse (or run of whole sentences) that matches the filters
+    let lookup = if sentences {
+        random_sentences(bible, &filters)
+    } else {
+        random_verse(bible, &filters)
+    };
+    let Some(lookup) = lookup else {
+        command_response(ctx, cmd, "No verses match those filters!").await;
+        return;
+    };
 
-pub async fn run(ctx: &Context, cmd: &CommandInteraction, bible: &Bible) {
-    // get a random verse
-    let lookup = bible.random_verse();
     // send the embed
-    send_bible_verse(lookup, ctx, cmd, bible).await;
+    send_bible_verse(lookup, ctx, cmd, bible, aliases).await;
+}
+
+/// Every verse in the books allowed by the filters, as (book, chapter, verse, text)
+fn matching_verses<'a>(
+    bible: &'a Bible,
+    filters: &'a Filters,
+) -> impl Iterator<Item = (&'a String, u32, u32, &'a String)> {
+    bible
+        .verses
+        .iter()
+        .filter(|(book, _)| filters.allows_book(book))
+        .flat_map(|(book, chapters)| {
+            chapters.iter().flat_map(move |(chapter, verses)| {
+                verses
+                    .iter()
+                    .map(move |(verse, text)| (book, *chapter, *verse, text))
+            })
+        })
+}
+
+fn random_verse(bible: &Bible, filters: &Filters) -> Option<BibleLookup> {
+    // without filters bible-lib can pick one directly
+    if filters.book.is_none()
+        && filters.testament.is_none()
+        && filters.category.is_none()
+        && filters.min_length == 0
+    {
+        return Some(bible.random_verse());
+    }
+
+    let candidates: Vec<_> = matching_verses(bible, filters)
+        .filter(|(_, _, _, text)| text.len() >= filters.min_length)
+        .collect();
+    let (book, chapter, verse, _) = candidates.choose(&mut rand::rng())?;
+
+    Some(BibleLookup::new(*book, *chapter, *verse))
+}
+
+/// True if a verse finishes a sentence
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['\'', '"', ')'])
+        .ends_with(['.', '?', '!'])
+}
+
+/// A few verses that start and end on sentence boundaries
+/// This only looks at punctuation, so it won't always match the sections a bible is divided into
+/// The minimum length applies to all the verses together
+fn random_sentences(bible: &Bible, filters: &Filters) -> Option<BibleLookup> {
+    // the verses start at the beginning of a sentence
+    let starts: Vec<_> = matching_verses(bible, filters)
+        .filter(|(book, chapter, verse, _)| {
+            *verse == 1
+                || bible
+                    .get_verse(BibleLookup::new(*book, *chapter, verse - 1), false)
+                    .is_ok_and(|previous| ends_sentence(&previous))
+        })
+        .collect();
+    let (book, chapter, start, text) = starts.choose(&mut rand::rng())?;
+
+    let max_verse = bible.get_max_verse(book, *chapter).ok()?;
+    let mut end = *start;
+    let mut length = text.len();
+    let mut last_text = (*text).clone();
+
+    // keep going until there are enough verses and a sentence ends
+    while end < max_verse
+        && end - start + 1 < SENTENCES_MAX_VERSES
+        && length < SENTENCES_MAX_CHARS
+        && (end - start + 1 < SENTENCES_MIN_VERSES
+            || length < filters.min_length
+            || !ends_sentence(&last_text))
+    {
+        end += 1;
+        last_text = bible
+            .get_verse(BibleLookup::new(*book, *chapter, end), false)
+            .unwrap_or_default();
+        length += last_text.len();
+    }
+
+    if end == *start {
+        Some(BibleLookup::new(*book, *chapter, *start))
+    } else {
+        Some(BibleLookup::new_range(*book, *chapter, *start, end))
+    }
 }
 
 pub fn register() -> CreateCommand {
+    let category = CATEGORIES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "category",
+            "Only pick from a group of books",
+        )
+        .required(false),
+        |option, (name, code, _)| option.add_string_choice(*name, *code),
+    );
+
     CreateCommand::new("random_verse")
         .description("Get a random verse from the Bible")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "book",
+                "Only pick from this book",
+            )
+            .set_autocomplete(true)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "testament",
+                "Only pick from one testament",
+            )
+            .add_string_choice("Old Testament", "old")
+            .add_string_choice("New Testament", "new")
+            .required(false),
+        )
+        .add_option(category)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "min_length",
+                "The fewest characters the verse can have",
+            )
+            .min_int_value(1)
+            .max_int_value(500)
+            .required(false),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "whole_sentences",
+                "Pick a few verses that start and end with a whole sentence instead of one verse",
+            )
+            .required(false),
+        )
         .dm_permission(true)
-}
\ No newline at end of file
+}
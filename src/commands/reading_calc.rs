@@ -21,7 +21,7 @@ pub async fn run(
         // MONTH
         value: ResolvedValue::Integer(month),
         ..
-    }) = options.get(0)
+    }) = options.first()
     {
         if let Some(ResolvedOption {
             // DAY
@@ -35,12 +35,12 @@ pub async fn run(
                 ..
             }) = options.get(2)
             {
-                (month.clone(), day.clone(), year.clone() as i32)
+                (*month, *day, *year as i32)
             } else {
                 // NO YEAR SPECIFIED
                 // current year
                 let current_year = chrono::Utc::now().year();
-                (month.clone(), day.clone(), current_year)
+                (*month, *day, current_year)
             }
         } else {
             command_response(ctx, cmd, "You must specify a day!").await;
@@ -78,7 +78,7 @@ pub async fn run(
             } else {
                 CreateEmbed::new()
                                 .title(format!("📖 Daily Reading for {}", date.format("%B %d, %Y")))
-                                .description(format!("No reading for today! You have completed the Bible this year!\nPlease use this time to catch up or reread missed chapters."))
+                                .description("No reading for today! You have completed the Bible this year!\nPlease use this time to catch up or reread missed chapters.")
                                 .color(Colour::GOLD)
                                 .footer(CreateEmbedFooter::new(format!("From the {} Bible.", bible.get_translation())))
             };
@@ -111,10 +111,14 @@ pub fn register() -> CreateCommand {
                 "month",
                 "The month (numeric: 1-12)",
             )
+            .min_int_value(1)
+            .max_int_value(12)
             .required(true),
         )
         .add_option(
             CreateCommandOption::new(CommandOptionType::Integer, "day", "The day (numeric)")
+                .min_int_value(1)
+                .max_int_value(31)
                 .required(true),
         )
         .add_option(
@@ -20,7 +20,7 @@ pub async fn run(
         command_response(
             ctx,
             command,
-            "You must specify an option: `daily_verse`, `reading_schedule`, or `remove`",
+            "You must specify an option: `daily_verse`, `reading_schedule`, `prayer`, or `remove`",
         )
         .await;
         return;
@@ -40,7 +40,7 @@ pub async fn run(
 
     match option.to_lowercase().as_str() {
         "daily_verse" => {
-            guild_file.set_daily_verse_channel(channel.id.clone());
+            guild_file.set_daily_verse_channel(channel.id);
             command_response(
                 ctx,
                 command,
@@ -49,7 +49,7 @@ pub async fn run(
             .await;
         }
         "reading_schedule" => {
-            guild_file.set_reading_schedule_channel(channel.id.clone());
+            guild_file.set_reading_schedule_channel(channel.id);
             command_response(
                 ctx,
                 command,
@@ -57,6 +57,15 @@ pub async fn run(
             )
             .await;
         }
+        "prayer" => {
+            guild_file.set_prayer_channel(channel.id);
+            command_response(
+                ctx,
+                command,
+                "Channel is now registered as the prayer channel, requests from `/prayer request` will be posted there.",
+            )
+            .await;
+        }
         "remove" => {
             let channel_id = channel.id.get();
             guild_file.clear_channel_by_id(channel_id);
@@ -72,7 +81,7 @@ pub async fn run(
             command_response(
                 ctx,
                 command,
-                "You must specify an option: `daily_verse`, `reading_schedule`, or `remove`",
+                "You must specify an option: `daily_verse`, `reading_schedule`, `prayer`, or `remove`",
             )
             .await;
         }
@@ -81,12 +90,12 @@ pub async fn run(
 
 pub fn register() -> CreateCommand {
     CreateCommand::new("register_channel")
-        .description("Register channels for daily verses or reading schedules")
+        .description("Register channels for daily verses, reading schedules or prayer requests")
         .add_option(
             CreateCommandOption::new(
                 CommandOptionType::String,
                 "action",
-                "`daily_verse` (add), `reading_schedule` (add) or `remove` to remove a channel",
+                "`daily_verse`, `reading_schedule` or `prayer` (add), or `remove` to remove a channel",
             )
             .required(true),
         )
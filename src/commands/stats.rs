@@ -0,0 +1,268 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::discord_helpers::command_response;
+use crate::nay;
+use crate::stats::{BibleStats, Measured, Statistics};
+use crate::translations::TRANSLATION_CHOICES;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ResolvedOption, ResolvedValue,
+};
+
+// how many entries are shown in each list
+const TOP_WORDS: usize = 20;
+const TOP_MEASURED: usize = 5;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    statistics: &Statistics,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(sub_options),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must choose what statistics to show!").await;
+        return;
+    };
+
+    // optional values
+    let mut book = None;
+    let mut term = None;
+    let mut translation = None;
+    let mut include_common = false;
+    for option in sub_options {
+        match (option.name, &option.value) {
+            ("book", ResolvedValue::String(value)) => book = Some(*value),
+            ("term", ResolvedValue::String(value)) => term = Some(*value),
+            ("translation", ResolvedValue::String(value)) => translation = Some(*value),
+            ("include_common", ResolvedValue::Boolean(value)) => include_common = *value,
+            _ => {}
+        }
+    }
+
+    let Some(stats) = statistics.get(translation) else {
+        command_response(ctx, cmd, "That translation is not available.").await;
+        return;
+    };
+
+    let embed = match *subcommand {
+        "words" => {
+            let book = match book {
+                Some(book) => match aliases.resolve(book, bible) {
+                    Some(book) => Some(book),
+                    None => {
+                        let lookup = BibleLookup::new(book, 1, 1);
+                        let (explanation, _) = explain_invalid_reference(&lookup, bible, aliases);
+                        command_response(ctx, cmd, explanation).await;
+                        return;
+                    }
+                },
+                None => None,
+            };
+            words_embed(&stats, book.as_deref(), include_common)
+        }
+        "term" => {
+            let Some(term) = term else {
+                command_response(ctx, cmd, "You must specify a word or phrase!").await;
+                return;
+            };
+            term_embed(&stats, term)
+        }
+        "verses" => measured_embed(
+            "📊 Longest and shortest verses",
+            stats.longest_verses(TOP_MEASURED),
+            stats.shortest_verses(TOP_MEASURED),
+            "verses",
+            "characters",
+        ),
+        "chapters" => measured_embed(
+            "📊 Longest and shortest chapters",
+            stats.longest_chapters(TOP_MEASURED),
+            stats.shortest_chapters(TOP_MEASURED),
+            "chapters",
+            "verses",
+        ),
+        "totals" => totals_embed(&stats),
+        _ => {
+            command_response(ctx, cmd, "Unknown statistics!").await;
+            return;
+        }
+    };
+
+    let embed = embed
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "From the {} Bible.",
+            stats.translation
+        )));
+    let data = CreateInteractionResponseMessage::new().embed(embed);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+fn words_embed(stats: &BibleStats, book: Option<&str>, include_common: bool) -> CreateEmbed {
+    let list = stats
+        .top_words(book, include_common, TOP_WORDS)
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (word, count))| format!("{}. **{}** ({})", rank + 1, word, count))
+        .collect::<Vec<String>>();
+
+    let place = match book {
+        Some(book) => BibleLookup::capitalize_book(&book.to_string()),
+        None => "the Bible".to_string(),
+    };
+    let mut description = list.join("\n");
+    if !include_common {
+        description.push_str("\n\n*Common words like \"the\" and \"unto\" are left out.*");
+    }
+
+    CreateEmbed::new()
+        .title(format!("📊 Most common words in {}", place))
+        .description(description)
+}
+
+fn term_embed(stats: &BibleStats, term: &str) -> CreateEmbed {
+    let per_book = stats.verses_containing(term);
+    let title = format!("📊 Verses containing \"{}\"", term);
+    if per_book.is_empty() {
+        return CreateEmbed::new()
+            .title(title)
+            .description("No verses contain that.");
+    }
+
+    let total: usize = per_book.iter().map(|(_, count)| count).sum();
+    let list = per_book
+        .iter()
+        .map(|(book, count)| format!("{}: {}", BibleLookup::capitalize_book(book), count))
+        .collect::<Vec<String>>();
+
+    CreateEmbed::new().title(title).description(format!(
+        "Found in **{}** verse{} across **{}** book{}.\n\n{}",
+        total,
+        if total == 1 { "" } else { "s" },
+        per_book.len(),
+        if per_book.len() == 1 { "" } else { "s" },
+        list.join("\n")
+    ))
+}
+
+fn measured_embed(
+    title: &str,
+    longest: Vec<&Measured>,
+    shortest: Vec<&Measured>,
+    kind: &str,
+    unit: &str,
+) -> CreateEmbed {
+    let list = |measured: Vec<&Measured>| {
+        measured
+            .iter()
+            .map(|m| format!("{} ({} {})", m.reference, m.length, unit))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    CreateEmbed::new()
+        .title(title)
+        .field(format!("Longest {}", kind), list(longest), false)
+        .field(format!("Shortest {}", kind), list(shortest), false)
+}
+
+fn totals_embed(stats: &BibleStats) -> CreateEmbed {
+    let totals = &stats.totals;
+    CreateEmbed::new()
+        .title(format!("📊 The {} Bible in numbers", stats.translation))
+        .fields([
+            ("Books", totals.books.to_string(), true),
+            ("Chapters", totals.chapters.to_string(), true),
+            ("Verses", totals.verses.to_string(), true),
+            ("Words", totals.words.to_string(), true),
+            ("Different Words", totals.unique_words.to_string(), true),
+            ("Characters", totals.characters.to_string(), true),
+        ])
+}
+
+pub fn register() -> CreateCommand {
+    let translation = TRANSLATION_CHOICES.iter().fold(
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "translation",
+            "The translation to count",
+        )
+        .required(false),
+        |option, (name, code)| option.add_string_choice(*name, *code),
+    );
+
+    CreateCommand::new("stats")
+        .description("Statistics about the Bible")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "words",
+                "The most common words",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "book",
+                    "Only count words in this book",
+                )
+                .set_autocomplete(true)
+                .required(false),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "include_common",
+                    "Include common words like \"the\" and \"unto\"",
+                )
+                .required(false),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "term",
+                "How many verses in each book contain a word or phrase",
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "term",
+                    "The word or phrase, i.e. `grace` or `fear not`",
+                )
+                .required(true),
+            ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "verses",
+            "The longest and shortest verses",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "chapters",
+            "The longest and shortest chapters",
+        ))
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "totals",
+                "How many books, chapters, verses and words there are",
+            )
+            .add_sub_option(translation),
+        )
+        .dm_permission(true)
+}
@@ -0,0 +1,195 @@
+use crate::books::aliases::BookAliases;
+use crate::discord_helpers::command_response;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::nay;
+use crate::strongs::{normalize_number, render_verse, Strongs};
+use bible_lib::Bible;
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateActionRow,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
+};
+
+// how many verses are on each page
+const VERSES_PER_PAGE: usize = 10;
+// verses are cut short after about this many characters
+const MAX_VERSE_CHARS: usize = 250;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    strongs: &Strongs,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(number),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a Strong's number!").await;
+        return;
+    };
+
+    if strongs.is_empty() {
+        command_response(ctx, cmd, "No Strong's tagged text has been imported.").await;
+        return;
+    }
+
+    let Some(number) = normalize_number(number) else {
+        command_response(
+            ctx,
+            cmd,
+            "Strong's numbers start with `H` for Hebrew or `G` for Greek, i.e. `H7225` or `G3056`.",
+        )
+        .await;
+        return;
+    };
+
+    let Some((embed, buttons)) = craft_strongs_page(&number, 0, strongs, bible, aliases) else {
+        command_response(ctx, cmd, format!("`{}` isn't used in any verse.", number)).await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The id of a button that lists the verses using a Strong's number
+pub fn strongs_button_id(number: &str) -> String {
+    format!("strongs:{}", number)
+}
+
+/// The id of the page buttons for a Strong's number (the page number is added by `page_buttons`)
+pub fn strongs_page_id(number: &str) -> String {
+    format!("strongs_page:{}", number)
+}
+
+/// A single page of the verses using a Strong's number, with the word it translates in bold
+/// Returns None if no verse uses it
+pub fn craft_strongs_page(
+    number: &str,
+    page: usize,
+    strongs: &Strongs,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let usage = strongs.usage(number);
+    if usage.is_empty() {
+        return None;
+    }
+    let count = usage.len().div_ceil(VERSES_PER_PAGE);
+    let page = page.min(count - 1);
+
+    let list = usage
+        .iter()
+        .skip(page * VERSES_PER_PAGE)
+        .take(VERSES_PER_PAGE)
+        .filter_map(|key| {
+            let words = strongs.verse(key)?;
+            Some(format!(
+                "**{}** {}",
+                key.to_lookup(bible, aliases),
+                render_verse(words, Some(number), MAX_VERSE_CHARS)
+            ))
+        })
+        .collect::<Vec<String>>();
+
+    let header = match strongs.entry(number) {
+        Some(entry) => format!("{}\n\n", entry.summary()),
+        None => String::new(),
+    };
+    let title = if count > 1 {
+        format!("🔤 {} ({}/{})", number, page + 1, count)
+    } else {
+        format!("🔤 {}", number)
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(format!("{}{}", header, list.join("\n")))
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "Used in {} verse{} of the Strong's tagged text.",
+            usage.len(),
+            if usage.len() == 1 { "" } else { "s" }
+        )));
+
+    Some((embed, page_buttons(&strongs_page_id(number), page, count)))
+}
+
+/// Lists the verses for a Strong's number from a `strongs:` button, only to whoever pressed it
+pub async fn run_button(
+    number: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    strongs: &Strongs,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((embed, buttons)) = craft_strongs_page(number, 0, strongs, bible, aliases) else {
+        nay!("Strong's number from a button was not found: {}", number);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons)
+        .ephemeral(true);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// Moves a Strong's verse list to the page from a `strongs_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    strongs: &Strongs,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((number, page)) = parse_page_button(args) else {
+        nay!("Invalid Strong's page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_strongs_page(number, page, strongs, bible, aliases) else {
+        nay!("Strong's number from a button was not found: {}", number);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("strongs")
+        .description("List every verse that uses a Hebrew or Greek word")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "number",
+                "The Strong's number, i.e. `H7225` or `G3056`",
+            )
+            .required(true),
+        )
+        .dm_permission(true)
+}
@@ -0,0 +1,168 @@
+use crate::books::aliases::BookAliases;
+use crate::discord_helpers::command_response;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::nay;
+use crate::topics::TopicalIndex;
+use bible_lib::Bible;
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateActionRow,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
+};
+
+// how many verses are on each page
+const VERSES_PER_PAGE: usize = 8;
+// long passages are cut short so a full page fits in an embed
+const MAX_VERSE_CHARS: usize = 400;
+// how many topics are suggested for a name that doesn't exist
+const MAX_SUGGESTIONS: usize = 5;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    topics: &TopicalIndex,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(name),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a topic!").await;
+        return;
+    };
+
+    if topics.is_empty() {
+        command_response(ctx, cmd, "No topics have been imported.").await;
+        return;
+    }
+
+    let Some((embed, buttons)) = craft_topic_page(name, 0, topics, bible, aliases) else {
+        let suggestions = topics.suggest(name, MAX_SUGGESTIONS);
+        let msg = if suggestions.is_empty() {
+            format!("There is no topic called \"{}\".", name)
+        } else {
+            format!(
+                "There is no topic called \"{}\". Did you mean {}?",
+                name,
+                suggestions
+                    .iter()
+                    .map(|topic| format!("\"{}\"", topic))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        };
+        command_response(ctx, cmd, msg).await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The id of the page buttons for a topic (the page number is added by `page_buttons`)
+pub fn topic_page_id(topic: &str) -> String {
+    format!("topic_page:{}", topic)
+}
+
+/// A single page of a topic's verses with buttons to move between pages
+/// Returns None if the topic doesn't exist
+pub fn craft_topic_page(
+    name: &str,
+    page: usize,
+    topics: &TopicalIndex,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let topic = topics.get(name)?;
+    let lookups = topic.lookups(bible, aliases);
+    let count = lookups.len().div_ceil(VERSES_PER_PAGE).max(1);
+    let page = page.min(count - 1);
+
+    let list = lookups
+        .iter()
+        .skip(page * VERSES_PER_PAGE)
+        .take(VERSES_PER_PAGE)
+        .map(|lookup| {
+            let text = bible.get_verse(lookup.clone(), false).unwrap_or_default();
+            let text = text.trim();
+            let text = if text.chars().count() > MAX_VERSE_CHARS {
+                let cut: String = text.chars().take(MAX_VERSE_CHARS).collect();
+                let cut = cut.rsplit_once(' ').map(|(start, _)| start).unwrap_or(&cut);
+                format!("{}…", cut)
+            } else {
+                text.to_string()
+            };
+            format!("**{}**\n> {}", lookup, text)
+        })
+        .collect::<Vec<String>>();
+
+    let title = if count > 1 {
+        format!("🏷️ {} ({}/{})", topic.name, page + 1, count)
+    } else {
+        format!("🏷️ {}", topic.name)
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(list.join("\n"))
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} references. From the {} Bible.",
+            lookups.len(),
+            bible.get_translation()
+        )));
+
+    Some((
+        embed,
+        page_buttons(&topic_page_id(&topic.name), page, count),
+    ))
+}
+
+/// Moves a topic's verse list to the page from a `topic_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    topics: &TopicalIndex,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some((name, page)) = parse_page_button(args) else {
+        nay!("Invalid topic page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_topic_page(name, page, topics, bible, aliases) else {
+        nay!("Topic from a button was not found: {}", name);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("topic")
+        .description("Verses about a topic, i.e. forgiveness or patience")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "topic", "The topic")
+                .set_autocomplete(true)
+                .required(true),
+        )
+        .dm_permission(true)
+}
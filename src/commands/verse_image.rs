@@ -0,0 +1,153 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::{attachment_name, ephemeral_response, passage_text};
+use crate::nay;
+use crate::verse_image::{render_card, CardError, CardFont, CardStyle};
+use bible_lib::Bible;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+    CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+
+// passages longer than this can't fit on a card, so they aren't drawn at all
+const MAX_PASSAGE_CHARS: usize = 900;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let mut reference = None;
+    let mut style = CardStyle::Parchment;
+    let mut font = CardFont::Serif;
+    for option in options {
+        match (option.name, &option.value) {
+            ("reference", ResolvedValue::String(value)) => reference = Some(*value),
+            ("style", ResolvedValue::String(value)) => {
+                style = CardStyle::from_code(value).unwrap_or(style)
+            }
+            ("font", ResolvedValue::String(value)) => {
+                font = CardFont::from_code(value).unwrap_or(font)
+            }
+            _ => {}
+        }
+    }
+
+    let Some(reference) = reference else {
+        ephemeral_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+    let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() else {
+        ephemeral_response(
+            ctx,
+            cmd,
+            format!(
+                "No reference found in `{}`. Try something like `John 3:16`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    };
+    let Some(text) = passage_text(&lookup, bible) else {
+        ephemeral_response(
+            ctx,
+            cmd,
+            explain_invalid_reference(&lookup, bible, aliases).0,
+        )
+        .await;
+        return;
+    };
+    if text.chars().count() > MAX_PASSAGE_CHARS {
+        ephemeral_response(ctx, cmd, too_long_message(&lookup.to_string())).await;
+        return;
+    }
+
+    // drawing takes a moment, so discord is told the response is coming
+    if let Err(e) = cmd
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new()),
+        )
+        .await
+    {
+        nay!("Failed to defer command: {}", e);
+        return;
+    }
+
+    let reference = lookup.to_string();
+    let translation = bible.get_translation().to_string();
+    let card_reference = reference.clone();
+    let rendered = tokio::task::spawn_blocking(move || {
+        render_card(&text, &card_reference, &translation, style, font)
+    })
+    .await;
+
+    let response = match rendered {
+        Ok(Ok(png)) => EditInteractionResponse::new().new_attachment(CreateAttachment::bytes(
+            png,
+            attachment_name(&reference, "png"),
+        )),
+        Ok(Err(CardError::TooLong)) => {
+            EditInteractionResponse::new().content(too_long_message(&reference))
+        }
+        Ok(Err(CardError::Render)) => {
+            nay!("Failed to draw verse image for {}", reference);
+            EditInteractionResponse::new().content("Sorry, I couldn't draw that verse.")
+        }
+        Err(e) => {
+            nay!("Verse image task failed: {}", e);
+            EditInteractionResponse::new().content("Sorry, I couldn't draw that verse.")
+        }
+    };
+    if let Err(e) = cmd.edit_response(&ctx.http, response).await {
+        nay!("Failed to send verse image: {}", e);
+    }
+}
+
+fn too_long_message(reference: &str) -> String {
+    format!(
+        "**{}** is too long to fit on an image. Try a few verses at a time.",
+        reference
+    )
+}
+
+pub fn register() -> CreateCommand {
+    let mut style = CreateCommandOption::new(
+        CommandOptionType::String,
+        "style",
+        "The background of the image (parchment by default)",
+    )
+    .required(false);
+    for card_style in CardStyle::ALL {
+        style = style.add_string_choice(card_style.to_string(), card_style.code());
+    }
+
+    let mut font = CreateCommandOption::new(
+        CommandOptionType::String,
+        "font",
+        "The font of the verse (serif by default)",
+    )
+    .required(false);
+    for card_font in CardFont::ALL {
+        font = font.add_string_choice(card_font.to_string(), card_font.code());
+    }
+
+    CreateCommand::new("verse_image")
+        .description("Get a verse as an image to share")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The verse or short passage to draw, i.e. Psalm 23:1-3",
+            )
+            .required(true),
+        )
+        .add_option(style)
+        .add_option(font)
+        .dm_permission(true)
+}
@@ -0,0 +1,342 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::commands::strongs::strongs_button_id;
+use crate::cross_references::VerseKey;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::multipage_embed::{page_buttons, parse_page_button};
+use crate::discord_helpers::verse_navigation::{lookup_id, parse_lookup_id};
+use crate::discord_helpers::{command_response, MAX_BUTTON_ROWS};
+use crate::nay;
+use crate::strongs::Strongs;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedOption,
+    ResolvedValue,
+};
+
+// how much of the word list is on each page
+const PAGE_CHARS: usize = 3000;
+// the most verses a single `/word` goes through
+const MAX_VERSES: u32 = 10;
+const BUTTONS_PER_ROW: usize = 5;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    strongs: &Strongs,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(reference),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+
+    if strongs.is_empty() {
+        command_response(ctx, cmd, "No Strong's tagged text has been imported.").await;
+        return;
+    }
+
+    let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() else {
+        command_response(
+            ctx,
+            cmd,
+            format!(
+                "No reference found in `{}`. Try something like `John 3:16`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    };
+    if bible.get_verse(lookup.clone(), false).is_err() {
+        let (explanation, _) = explain_invalid_reference(&lookup, bible, aliases);
+        command_response(ctx, cmd, explanation).await;
+        return;
+    }
+
+    // long passages are cut down to the first few verses
+    let (lookup, content) = match lookup.thru_verse {
+        Some(thru_verse) if thru_verse >= lookup.verse + MAX_VERSES => (
+            BibleLookup::new_range(
+                &lookup.book,
+                lookup.chapter,
+                lookup.verse,
+                lookup.verse + MAX_VERSES - 1,
+            ),
+            format!(
+                "{} is too long to go through word by word, so only the first {} verses are shown.",
+                lookup, MAX_VERSES
+            ),
+        ),
+        _ => (lookup, String::new()),
+    };
+
+    let Some((embed, buttons)) = craft_word_page(&lookup, 0, strongs, aliases) else {
+        command_response(
+            ctx,
+            cmd,
+            format!("{} isn't in the Strong's tagged text.", lookup),
+        )
+        .await;
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .content(content)
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+/// The id of the page buttons for the words of a reference (the page number is added by `page_buttons`)
+pub fn word_page_id(lookup: &BibleLookup) -> String {
+    format!("word_page:{}", lookup_id(lookup))
+}
+
+/// A page of the word list and the Strong's numbers on it
+struct WordPage {
+    text: String,
+    numbers: Vec<String>,
+}
+
+/// One line for each word (or group of words) of the verses, with its Strong's numbers and meanings,
+/// split into pages of about `PAGE_CHARS` characters
+fn word_pages(lookup: &BibleLookup, strongs: &Strongs, aliases: &BookAliases) -> Vec<WordPage> {
+    let first = VerseKey::from_lookup(lookup, aliases);
+    let last = lookup.thru_verse.unwrap_or(lookup.verse);
+
+    let mut pages = Vec::new();
+    let mut page = WordPage {
+        text: String::new(),
+        numbers: Vec::new(),
+    };
+    let mut add_line = |line: String, numbers: &[String]| {
+        if !page.text.is_empty() && page.text.len() + 1 + line.len() > PAGE_CHARS {
+            pages.push(std::mem::replace(
+                &mut page,
+                WordPage {
+                    text: String::new(),
+                    numbers: Vec::new(),
+                },
+            ));
+        }
+        if !page.text.is_empty() {
+            page.text.push('\n');
+        }
+        page.text.push_str(&line);
+        for number in numbers {
+            if !page.numbers.contains(number) {
+                page.numbers.push(number.clone());
+            }
+        }
+    };
+
+    for verse in lookup.verse..=last {
+        let Some(words) = strongs.verse(&VerseKey {
+            verse,
+            ..first.clone()
+        }) else {
+            continue;
+        };
+        if last > lookup.verse {
+            add_line(format!("__Verse {}__", verse), &[]);
+        }
+
+        for word in words {
+            let text = word.text.trim();
+            // skip bits of punctuation between tagged words
+            if !text.chars().any(|c| c.is_alphanumeric()) {
+                continue;
+            }
+            if word.numbers.is_empty() {
+                add_line(format!("*{}*", text), &[]);
+                continue;
+            }
+
+            let meanings = word
+                .numbers
+                .iter()
+                .map(|number| match strongs.entry(number) {
+                    Some(entry) => format!("`{}` {}", number, entry.summary()),
+                    None => format!("`{}`", number),
+                })
+                .collect::<Vec<String>>()
+                .join("; ");
+            add_line(format!("**{}** — {}", text, meanings), &word.numbers);
+        }
+    }
+
+    if !page.text.is_empty() {
+        pages.push(page);
+    }
+    pages
+}
+
+/// A single page of the words of a reference, with buttons to list the verses using each number
+/// Returns None if none of the verses are in the tagged text
+pub fn craft_word_page(
+    lookup: &BibleLookup,
+    page: usize,
+    strongs: &Strongs,
+    aliases: &BookAliases,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let pages = word_pages(lookup, strongs, aliases);
+    if pages.is_empty() {
+        return None;
+    }
+    let count = pages.len();
+    let page = page.min(count - 1);
+    let WordPage { text, numbers } = &pages[page];
+
+    let title = if count > 1 {
+        format!("🔤 {} word by word ({}/{})", lookup, page + 1, count)
+    } else {
+        format!("🔤 {} word by word", lookup)
+    };
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(text)
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(
+            "Words from the Strong's tagged text. Use the buttons to see every verse with a word.",
+        ));
+
+    // a button for each number on this page, with room left for the page buttons
+    let page_rows = page_buttons(&word_page_id(lookup), page, count);
+    let mut rows: Vec<CreateActionRow> = numbers
+        .chunks(BUTTONS_PER_ROW)
+        .take(MAX_BUTTON_ROWS - page_rows.len())
+        .map(|chunk| {
+            CreateActionRow::Buttons(
+                chunk
+                    .iter()
+                    .map(|number| {
+                        CreateButton::new(strongs_button_id(number))
+                            .label(number)
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    rows.extend(page_rows);
+
+    Some((embed, rows))
+}
+
+/// Moves a word list to the page from a `word_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    strongs: &Strongs,
+    aliases: &BookAliases,
+) {
+    let Some((lookup, page)) = parse_page_button(args) else {
+        nay!("Invalid word page button: {}", args);
+        return;
+    };
+    let Some(lookup) = parse_lookup_id(lookup) else {
+        nay!("Invalid word page button: {}", args);
+        return;
+    };
+    let Some((embed, buttons)) = craft_word_page(&lookup, page, strongs, aliases) else {
+        nay!("Words from a button were not found: {}", lookup);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("word")
+        .description("Show the Hebrew or Greek behind each word of a verse")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The verse or passage, i.e. `John 1:1`",
+            )
+            .required(true),
+        )
+        .dm_permission(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strongs(text: &str, lexicon: &str) -> Strongs {
+        Strongs::import(text, lexicon, &BookAliases::new()).0
+    }
+
+    #[test]
+    fn numbers_come_from_the_tagged_words() {
+        let strongs = strongs(
+            "Gen.1.1\tIn the beginning{H7225} God{H430} created{H1254}{H853}.",
+            "H7225\tרֵאשִׁית\t`the first`, `beginning`\nH430\tאֱלֹהִים\tGod",
+        );
+        let pages = word_pages(
+            &BibleLookup::new("Genesis", 1, 1),
+            &strongs,
+            &BookAliases::new(),
+        );
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].numbers, vec!["H7225", "H430", "H1254", "H853"]);
+    }
+
+    #[test]
+    fn each_page_has_its_own_numbers() {
+        let verse = (1..=300)
+            .map(|number| format!("word{{H{}}}", number))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let strongs = strongs(&format!("Gen.1.1\t{}", verse), "");
+        let pages = word_pages(
+            &BibleLookup::new("Genesis", 1, 1),
+            &strongs,
+            &BookAliases::new(),
+        );
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert!(page.text.len() <= PAGE_CHARS);
+            for number in &page.numbers {
+                assert!(page.text.contains(&format!("`{}`", number)));
+            }
+        }
+        let total: usize = pages.iter().map(|page| page.numbers.len()).sum();
+        assert_eq!(total, 300);
+    }
+
+    #[test]
+    fn verses_not_in_the_tagged_text() {
+        let strongs = strongs("Gen.1.1\tIn the beginning{H7225}", "");
+        let pages = word_pages(
+            &BibleLookup::new("Exodus", 1, 1),
+            &strongs,
+            &BookAliases::new(),
+        );
+        assert!(pages.is_empty());
+    }
+}
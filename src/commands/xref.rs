@@ -0,0 +1,144 @@
+use crate::books::aliases::BookAliases;
+use crate::books::fuzzy::explain_invalid_reference;
+use crate::commands::open_verse_button_id;
+use crate::cross_references::CrossReferences;
+use crate::detection::detection_handler::detect_verses;
+use crate::discord_helpers::{command_response, verse_preview};
+use crate::nay;
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, Context, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedOption, ResolvedValue,
+};
+
+// how many cross references are listed, with a button each (two rows of five)
+const MAX_CROSS_REFERENCES: usize = 10;
+const BUTTONS_PER_ROW: usize = 5;
+// how much of each verse is quoted in the list
+const PREVIEW_CHARS: usize = 100;
+
+pub async fn run(
+    options: &[ResolvedOption<'_>],
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    cross_references: &CrossReferences,
+    bible: &Bible,
+    aliases: &BookAliases,
+) {
+    let Some(ResolvedOption {
+        value: ResolvedValue::String(reference),
+        ..
+    }) = options.first()
+    else {
+        command_response(ctx, cmd, "You must specify a reference!").await;
+        return;
+    };
+
+    if cross_references.is_empty() {
+        command_response(ctx, cmd, "No cross references have been imported.").await;
+        return;
+    }
+
+    let Some(lookup) = detect_verses(reference, aliases, bible).into_iter().next() else {
+        command_response(
+            ctx,
+            cmd,
+            format!(
+                "No reference found in `{}`. Try something like `John 3:16`",
+                reference
+            ),
+        )
+        .await;
+        return;
+    };
+    if bible.get_verse(lookup.clone(), false).is_err() {
+        let (explanation, _) = explain_invalid_reference(&lookup, bible, aliases);
+        command_response(ctx, cmd, explanation).await;
+        return;
+    }
+
+    // passages running into the next chapter are cut short, which can make two the same
+    let mut related: Vec<(BibleLookup, i32)> = Vec::new();
+    for reference in cross_references.top(&lookup, aliases, MAX_CROSS_REFERENCES) {
+        let Some(target) = reference.to_lookup(bible, aliases) else {
+            continue;
+        };
+        if !related.iter().any(|(existing, _)| *existing == target) {
+            related.push((target, reference.votes));
+        }
+    }
+    if related.is_empty() {
+        command_response(
+            ctx,
+            cmd,
+            format!("No cross references found for {}.", lookup),
+        )
+        .await;
+        return;
+    }
+
+    let list = related
+        .iter()
+        .enumerate()
+        .map(|(x, (related, votes))| {
+            format!(
+                "**{}. {}** ({} vote{})\n{}",
+                x + 1,
+                related,
+                votes,
+                if *votes == 1 { "" } else { "s" },
+                verse_preview(related, bible, PREVIEW_CHARS)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let embed = CreateEmbed::new()
+        .title(format!("🔗 Cross references for {}", lookup))
+        .description(list.join("\n"))
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "From the {} Bible.",
+            bible.get_translation()
+        )));
+
+    let buttons = related
+        .chunks(BUTTONS_PER_ROW)
+        .map(|chunk| {
+            CreateActionRow::Buttons(
+                chunk
+                    .iter()
+                    .map(|(related, _)| {
+                        CreateButton::new(open_verse_button_id(related, bible))
+                            .label(related.to_string())
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = cmd
+        .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+        .await
+    {
+        nay!("Failed to respond to command: {}", e);
+    }
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("xref")
+        .description("Find verses related to a verse")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "reference",
+                "The verse or passage, i.e. `John 3:16`",
+            )
+            .required(true),
+        )
+        .dm_permission(true)
+}
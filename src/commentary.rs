@@ -0,0 +1,181 @@
+// Public domain commentaries (like Matthew Henry's Concise Commentary), for `/commentary`.
+//
+// The file is a commentary, or a list of them, as JSON:
+// `{"source": "Matthew Henry's Concise Commentary", "sections": [{"book": "Genesis", "chapter": 1, "start": 1, "end": 2, "text": "..."}]}`
+// `start` and `end` are the verses a section covers; without them it covers the whole chapter
+// (or from `start` to the end of the chapter).
+
+use std::collections::HashMap;
+
+use bible_lib::{Bible, BibleLookup};
+
+use crate::{
+    books::aliases::{normalize_book_name, BookAliases},
+    hey, nay, say,
+};
+
+pub const PATH: &str = "./data/commentary.json";
+
+#[derive(serde::Deserialize)]
+struct CommentaryFile {
+    source: String,
+    sections: Vec<SectionFile>,
+}
+
+#[derive(serde::Deserialize)]
+struct SectionFile {
+    book: String,
+    chapter: u32,
+    #[serde(default)]
+    start: Option<u32>,
+    #[serde(default)]
+    end: Option<u32>,
+    text: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum CommentariesFile {
+    One(CommentaryFile),
+    Many(Vec<CommentaryFile>),
+}
+
+/// A commentary on some verses of a chapter
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// the book's full name as given by `BookAliases`
+    pub book: String,
+    pub chapter: u32,
+    pub start: u32,
+    /// None if the section runs to the end of the chapter
+    pub end: Option<u32>,
+    pub text: String,
+    /// the commentary it comes from, i.e. `Matthew Henry's Concise Commentary`
+    pub source: String,
+}
+
+impl Section {
+    /// The verses the section covers, i.e. `Genesis 1:1-2` or `Genesis 1` for a whole chapter
+    pub fn reference(&self, bible: &Bible, aliases: &BookAliases) -> String {
+        let book = aliases
+            .resolve(&self.book, bible)
+            .unwrap_or_else(|| self.book.clone());
+        match self.end {
+            None if self.start <= 1 => {
+                format!("{} {}", BibleLookup::capitalize_book(&book), self.chapter)
+            }
+            None => {
+                let end = bible
+                    .get_max_verse(&book, self.chapter)
+                    .unwrap_or(self.start);
+                BibleLookup::new_range(book, self.chapter, self.start, end).to_string()
+            }
+            Some(end) if end > self.start => {
+                BibleLookup::new_range(book, self.chapter, self.start, end).to_string()
+            }
+            Some(_) => BibleLookup::new(book, self.chapter, self.start).to_string(),
+        }
+    }
+
+    fn covers(&self, first: u32, last: u32) -> bool {
+        self.start <= last && self.end.is_none_or(|end| end >= first)
+    }
+}
+
+/// Every imported section, indexed by book and chapter
+pub struct Commentaries {
+    sections: HashMap<(String, u32), Vec<Section>>,
+}
+
+impl Commentaries {
+    /// Import the commentary at `PATH`, if there is one
+    pub fn load(aliases: &BookAliases) -> Self {
+        let Ok(data) = std::fs::read_to_string(PATH) else {
+            hey!(
+                "No commentary found at {}, `/commentary` will have nothing to show",
+                PATH
+            );
+            return Self {
+                sections: HashMap::new(),
+            };
+        };
+
+        match Self::import(&data, aliases) {
+            Ok((commentaries, skipped)) => {
+                say!(
+                    "Loaded commentary on {} chapters ({} sections skipped)",
+                    commentaries.sections.len(),
+                    skipped
+                );
+                commentaries
+            }
+            Err(e) => {
+                nay!("Failed to read the commentary at {}: {}", PATH, e);
+                Self {
+                    sections: HashMap::new(),
+                }
+            }
+        }
+    }
+
+    /// Read the commentaries from JSON, returning them and how many sections were skipped (unknown books or no text)
+    pub fn import(data: &str, aliases: &BookAliases) -> Result<(Self, usize), serde_json::Error> {
+        let files = match serde_json::from_str(data)? {
+            CommentariesFile::One(file) => vec![file],
+            CommentariesFile::Many(files) => files,
+        };
+
+        let mut sections: HashMap<(String, u32), Vec<Section>> = HashMap::new();
+        let mut skipped = 0;
+        for file in files {
+            for section in file.sections {
+                let Some(book) = aliases.canonical_name(&section.book) else {
+                    skipped += 1;
+                    continue;
+                };
+                let text = section.text.trim().to_string();
+                if text.is_empty() {
+                    skipped += 1;
+                    continue;
+                }
+
+                sections
+                    .entry((book.clone(), section.chapter))
+                    .or_default()
+                    .push(Section {
+                        book,
+                        chapter: section.chapter,
+                        start: section.start.unwrap_or(1),
+                        end: section.end,
+                        text,
+                        source: file.source.clone(),
+                    });
+            }
+        }
+
+        for chapter in sections.values_mut() {
+            chapter.sort_by_key(|section| section.start);
+        }
+
+        Ok((Self { sections }, skipped))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// The sections about any of the verses of a reference, in order
+    pub fn sections_for(&self, lookup: &BibleLookup, aliases: &BookAliases) -> Vec<&Section> {
+        let book = aliases
+            .canonical_name(&lookup.book)
+            .unwrap_or_else(|| normalize_book_name(&lookup.book));
+        let last = lookup.thru_verse.unwrap_or(lookup.verse);
+
+        self.sections
+            .get(&(book, lookup.chapter))
+            .into_iter()
+            .flatten()
+            .filter(|section| section.covers(lookup.verse, last))
+            .collect()
+    }
+}
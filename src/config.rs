@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::{self, OpenOptions},
     io::Write,
     path::Path,
@@ -19,9 +20,9 @@ enum SerializableTranslation {
     Custom { name: String, path: String },
 }
 
-impl Into<Translation> for SerializableTranslation {
-    fn into(self) -> Translation {
-        match self {
+impl From<SerializableTranslation> for Translation {
+    fn from(val: SerializableTranslation) -> Self {
+        match val {
             SerializableTranslation::AmericanStandard => Translation::AmericanStandard,
             SerializableTranslation::AmericanKingJames => Translation::AmericanKingJames,
             SerializableTranslation::KingJames => Translation::KingJames,
@@ -61,17 +62,21 @@ impl From<&Translation> for SerializableTranslation {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigSettings {
     translation: SerializableTranslation,
+    /// extra book aliases for every guild (alias -> book)
+    #[serde(default)]
+    book_aliases: HashMap<String, String>,
 }
 
 impl ConfigSettings {
     pub fn new(translation: &Translation) -> Self {
         Self {
             translation: SerializableTranslation::from(translation),
+            book_aliases: HashMap::new(),
         }
     }
 
     pub fn get() -> Self {
-        let raw_path = format!("./config.json");
+        let raw_path = "./config.json".to_string();
         let path = std::path::Path::new(&raw_path);
 
         if !path.exists() {
@@ -84,16 +89,11 @@ impl ConfigSettings {
             return Self::new(&DEFAULT_TRANSLATION);
         };
 
-        let cfg: ConfigSettings = serde_json::from_str(data.as_str())
-            .expect(format!("failed to deserialize config data").as_str());
-
-        Self {
-            translation: cfg.translation,
-        }
+        serde_json::from_str(data.as_str()).expect("failed to deserialize config data")
     }
 
     fn generate() {
-        let raw_path = format!("./config.json");
+        let raw_path = "./config.json".to_string();
         let path = Path::new(raw_path.as_str());
 
         if path.exists() {
@@ -105,6 +105,7 @@ impl ConfigSettings {
             .read(false)
             .write(true)
             .create(true)
+            .truncate(false)
             .append(false)
             .open(path)
         else {
@@ -127,4 +128,8 @@ impl ConfigSettings {
     pub fn get_translation(&self) -> Translation {
         self.translation.clone().into()
     }
+
+    pub fn get_book_aliases(&self) -> &HashMap<String, String> {
+        &self.book_aliases
+    }
 }
@@ -0,0 +1,308 @@
+// Cross references imported from a Treasury of Scripture Knowledge style dataset, for `/xref`.
+//
+// The dataset is a TSV file of `from verse`, `to verse(s)` and `votes`, like the one from
+// openbible.info: `Gen.1.1	Prov.8.22-Prov.8.30	59`. References can also be written out,
+// i.e. `Genesis 1:1	Proverbs 8:22-Proverbs 8:30	59`. Lines that can't be read (like the header) are skipped.
+
+use std::collections::HashMap;
+
+use bible_lib::{Bible, BibleLookup};
+
+use crate::{
+    books::aliases::{normalize_book_name, BookAliases},
+    hey, say,
+};
+
+pub const PATH: &str = "./data/cross_references.txt";
+
+/// A verse, with the book's full name as given by `BookAliases` so every translation can share it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VerseKey {
+    pub book: String,
+    pub chapter: u32,
+    pub verse: u32,
+}
+
+impl VerseKey {
+    /// Read a reference like `Gen.1.1`, `1Sam.3.10` or `Genesis 1:1`
+    /// Returns None if the book isn't known
+    pub fn parse(text: &str, aliases: &BookAliases) -> Option<Self> {
+        let text = text.trim();
+        let (book, chapter, verse) = match text.rsplit_once(':') {
+            Some((rest, verse)) => {
+                let (book, chapter) = rest.trim().rsplit_once(' ')?;
+                (book, chapter, verse)
+            }
+            None => {
+                let mut parts = text.rsplitn(3, '.');
+                let verse = parts.next()?;
+                let chapter = parts.next()?;
+                (parts.next()?, chapter, verse)
+            }
+        };
+
+        Some(Self {
+            book: aliases.canonical_name(book)?,
+            chapter: chapter.trim().parse().ok()?,
+            verse: verse.trim().parse().ok()?,
+        })
+    }
+
+    /// The key for a verse of the loaded bible
+    pub fn from_lookup(lookup: &BibleLookup, aliases: &BookAliases) -> Self {
+        Self {
+            book: aliases
+                .canonical_name(&lookup.book)
+                .unwrap_or_else(|| normalize_book_name(&lookup.book)),
+            chapter: lookup.chapter,
+            verse: lookup.verse,
+        }
+    }
+
+    /// The verse in the loaded bible, keeping the book name as it is if the bible doesn't have it
+    pub fn to_lookup(&self, bible: &Bible, aliases: &BookAliases) -> BibleLookup {
+        let book = aliases
+            .resolve(&self.book, bible)
+            .unwrap_or_else(|| self.book.clone());
+        BibleLookup::new(book, self.chapter, self.verse)
+    }
+}
+
+/// A verse or passage related to another verse, and how many people agreed they're related
+#[derive(Debug, Clone)]
+pub struct CrossReference {
+    pub start: VerseKey,
+    pub end: Option<VerseKey>,
+    pub votes: i32,
+}
+
+impl CrossReference {
+    /// The reference in the loaded bible
+    /// Passages that run into the next chapter stop at the end of the first one
+    pub fn to_lookup(&self, bible: &Bible, aliases: &BookAliases) -> Option<BibleLookup> {
+        let book = aliases.resolve(&self.start.book, bible)?;
+        let thru_verse = match &self.end {
+            Some(end) if end.book == self.start.book && end.chapter == self.start.chapter => {
+                Some(end.verse)
+            }
+            Some(end) if end.book == self.start.book && end.chapter > self.start.chapter => {
+                bible.get_max_verse(&book, self.start.chapter).ok()
+            }
+            _ => None,
+        };
+
+        match thru_verse {
+            Some(thru_verse) if thru_verse > self.start.verse => Some(BibleLookup::new_range(
+                book,
+                self.start.chapter,
+                self.start.verse,
+                thru_verse,
+            )),
+            _ => Some(BibleLookup::new(book, self.start.chapter, self.start.verse)),
+        }
+    }
+}
+
+/// Every imported cross reference, keyed by the verse it's from
+pub struct CrossReferences {
+    references: HashMap<VerseKey, Vec<CrossReference>>,
+}
+
+impl CrossReferences {
+    /// Import the dataset at `PATH`, if there is one
+    pub fn load(aliases: &BookAliases) -> Self {
+        let Ok(data) = std::fs::read_to_string(PATH) else {
+            hey!(
+                "No cross references found at {}, `/xref` will have nothing to show",
+                PATH
+            );
+            return Self {
+                references: HashMap::new(),
+            };
+        };
+
+        let (references, skipped) = Self::import(&data, aliases);
+        say!(
+            "Loaded cross references for {} verses ({} lines skipped)",
+            references.references.len(),
+            skipped
+        );
+        references
+    }
+
+    /// Read the TSV, returning the cross references and how many lines couldn't be read
+    pub fn import(data: &str, aliases: &BookAliases) -> (Self, usize) {
+        let mut references: HashMap<VerseKey, Vec<CrossReference>> = HashMap::new();
+        let mut skipped = 0;
+
+        for line in data.lines().filter(|line| !line.trim().is_empty()) {
+            let mut columns = line.split('\t');
+            let (Some(from), Some(to)) = (columns.next(), columns.next()) else {
+                skipped += 1;
+                continue;
+            };
+            let votes = columns
+                .next()
+                .and_then(|votes| votes.trim().parse().ok())
+                .unwrap_or(0);
+
+            let (start, end) = match to.split_once('-') {
+                Some((start, end)) => (start, Some(end)),
+                None => (to, None),
+            };
+            let (Some(from), Some(start)) = (
+                VerseKey::parse(from, aliases),
+                VerseKey::parse(start, aliases),
+            ) else {
+                skipped += 1;
+                continue;
+            };
+            // the end can be a full reference or just a verse in the same chapter
+            let end = end.and_then(|end| {
+                VerseKey::parse(end, aliases).or_else(|| {
+                    Some(VerseKey {
+                        verse: end.trim().parse().ok()?,
+                        ..start.clone()
+                    })
+                })
+            });
+
+            references
+                .entry(from)
+                .or_default()
+                .push(CrossReference { start, end, votes });
+        }
+
+        // most votes first
+        for list in references.values_mut() {
+            list.sort_by_key(|reference| std::cmp::Reverse(reference.votes));
+        }
+
+        (Self { references }, skipped)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// The most voted cross references for a verse, or every verse of a range
+    /// References back into the range itself are left out
+    pub fn top(
+        &self,
+        lookup: &BibleLookup,
+        aliases: &BookAliases,
+        limit: usize,
+    ) -> Vec<CrossReference> {
+        let first = VerseKey::from_lookup(lookup, aliases);
+        let last = lookup.thru_verse.unwrap_or(lookup.verse);
+        let in_range = |key: &VerseKey| {
+            key.book == first.book
+                && key.chapter == first.chapter
+                && (lookup.verse..=last).contains(&key.verse)
+        };
+
+        // a passage can have the same cross reference from several verses, so add up the votes
+        let mut merged: Vec<CrossReference> = Vec::new();
+        for verse in lookup.verse..=last {
+            let key = VerseKey {
+                verse,
+                ..first.clone()
+            };
+            for reference in self.references.get(&key).into_iter().flatten() {
+                if in_range(&reference.start) {
+                    continue;
+                }
+                match merged
+                    .iter_mut()
+                    .find(|r| r.start == reference.start && r.end == reference.end)
+                {
+                    Some(existing) => existing.votes += reference.votes,
+                    None => merged.push(reference.clone()),
+                }
+            }
+        }
+
+        merged.sort_by_key(|reference| std::cmp::Reverse(reference.votes));
+        merged.truncate(limit);
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(data: &str) -> (CrossReferences, usize) {
+        CrossReferences::import(data, &BookAliases::new())
+    }
+
+    fn key(book: &str, chapter: u32, verse: u32) -> VerseKey {
+        VerseKey::parse(
+            &format!("{} {}:{}", book, chapter, verse),
+            &BookAliases::new(),
+        )
+        .unwrap()
+    }
+
+    fn references(index: &CrossReferences, from: &VerseKey) -> Vec<CrossReference> {
+        index.references.get(from).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn abbreviated_and_written_out_references_match() {
+        let aliases = BookAliases::new();
+        assert_eq!(
+            VerseKey::parse("Gen.1.1", &aliases),
+            Some(key("Genesis", 1, 1))
+        );
+        assert_eq!(
+            VerseKey::parse("Gen.1.1", &aliases),
+            VerseKey::parse("Genesis 1:1", &aliases)
+        );
+        assert!(VerseKey::parse("1Sam.3.10", &aliases).is_some());
+        assert_eq!(
+            VerseKey::parse("1Sam.3.10", &aliases),
+            VerseKey::parse("1 Samuel 3:10", &aliases)
+        );
+        assert_eq!(VerseKey::parse("Nothing.1.1", &aliases), None);
+        assert_eq!(VerseKey::parse("Gen.one.1", &aliases), None);
+    }
+
+    #[test]
+    fn ranges_can_end_with_a_reference_or_a_verse() {
+        let (index, skipped) = import(
+            "Gen.1.1\tProv.8.22-Prov.8.30\t59\nGenesis 1:1\tJohn 1:1-3\t40\nGen.1.1\tPs.33.6\t20\n",
+        );
+        assert_eq!(skipped, 0);
+
+        let list = references(&index, &key("Genesis", 1, 1));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list[0].start, key("Proverbs", 8, 22));
+        assert_eq!(list[0].end, Some(key("Proverbs", 8, 30)));
+        assert_eq!(list[1].start, key("John", 1, 1));
+        assert_eq!(list[1].end, Some(key("John", 1, 3)));
+        assert_eq!(list[2].end, None);
+    }
+
+    #[test]
+    fn header_and_bad_lines_are_skipped() {
+        let (index, skipped) = import(
+            "From Verse\tTo Verse\tVotes\t#www.openbible.info CC-BY 2011-08-30\n\nno tabs\nGen.1.1\tNothing.1.1\t3\nGen.1.1\tJohn.1.1\t5\n",
+        );
+        assert_eq!(skipped, 3);
+        assert_eq!(references(&index, &key("Genesis", 1, 1)).len(), 1);
+    }
+
+    #[test]
+    fn missing_and_negative_votes() {
+        let (index, skipped) =
+            import("Gen.1.1\tJohn.1.1\nGen.1.1\tHeb.11.3\t-2\nGen.1.1\tPs.33.6\t4\n");
+        assert_eq!(skipped, 0);
+
+        let votes: Vec<i32> = references(&index, &key("Genesis", 1, 1))
+            .iter()
+            .map(|reference| reference.votes)
+            .collect();
+        assert_eq!(votes, vec![4, 0, -2]);
+    }
+}
@@ -1,10 +1,13 @@
 use bible_lib::{Bible, BibleLookup};
-use chrono::{Local, Timelike};
+use chrono::{Datelike, Local, NaiveDate, Timelike};
 use serenity::all::{Colour, Context, CreateEmbed, CreateEmbedFooter, CreateMessage, GetMessages};
 
 use crate::{
-    daily_messages::daily_verse::DailyVerseHandler, daily_messages::reading_scheudle::Reading,
-    guildfile::GuildSettings, nay,
+    books::{aliases::BookAliases, metadata::book_info},
+    daily_messages::daily_verse::DailyVerseHandler,
+    daily_messages::reading_scheudle::Reading,
+    guildfile::{Collection, GuildSettings},
+    nay,
 };
 
 /// uses local timezone
@@ -25,10 +28,27 @@ pub fn get_time_until_7am() -> Option<std::time::Duration> {
     Some(duration)
 }
 
+/// Today's passage from a collection used for the daily verse
+/// The passages take turns by date, so restarts don't change the day's verse
+fn collection_verse(
+    collection: &Collection,
+    today: NaiveDate,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> Option<BibleLookup> {
+    let count = collection.passages.len();
+    if count == 0 {
+        return None;
+    }
+    let index = today.num_days_from_ce().unsigned_abs() as usize % count;
+    Some(collection.passages[index].lookup(bible, aliases))
+}
+
 pub async fn spam_daily_verse(
     ctx: &Context,
     verse: &BibleLookup,
     bible: &Bible,
+    aliases: &BookAliases,
     guilds: &Vec<GuildSettings>,
 ) {
     let mut verse = verse.clone();
@@ -59,7 +79,9 @@ pub async fn spam_daily_verse(
                             .unwrap_or(false)
                     })
                 {
-                    if let Some(title) = &embed.title {
+                    // guilds taking the daily verse from a collection don't share it with the rest
+                    let shared = guild.get_daily_verse_collection().is_none();
+                    if let Some(title) = embed.title.as_ref().filter(|_| shared) {
                         let old_verse = title.replace("📖 ", "");
                         let mut handler = DailyVerseHandler::get(bible);
                         let lookup = BibleLookup::detect_from_string(old_verse);
@@ -88,9 +110,28 @@ pub async fn spam_daily_verse(
             if found_sent.contains(&channel_id) {
                 continue; // already sent in this channel
             }
+
+            let collection = guild.get_daily_verse_collection();
+            let today = Local::now().date_naive();
+            let collection_verse = collection
+                .and_then(|collection| collection_verse(collection, today, bible, aliases));
+            let (verse, footer) = match (collection, collection_verse) {
+                (Some(collection), Some(verse)) => (
+                    verse,
+                    format!(
+                        "Daily verse from {} in the {} Bible.",
+                        collection.name,
+                        bible.get_translation()
+                    ),
+                ),
+                _ => (
+                    verse.clone(),
+                    format!("Daily verse from the {} Bible.", bible.get_translation()),
+                ),
+            };
             let Ok(verse_text) = bible.get_verse(verse.clone(), true) else {
                 nay!("Failed to get verse text for daily verse spam.");
-                return;
+                continue;
             };
 
             // create the embed and send it
@@ -98,10 +139,7 @@ pub async fn spam_daily_verse(
                 .title(format!("📖 {}", verse))
                 .description(verse_text.clone())
                 .color(Colour::GOLD)
-                .footer(CreateEmbedFooter::new(format!(
-                    "Daily verse from the {} Bible.",
-                    bible.get_translation()
-                )));
+                .footer(CreateEmbedFooter::new(footer));
             let builder = CreateMessage::new().embed(embed);
 
             let msg = channel_id.send_message(&ctx.http, builder).await;
@@ -128,7 +166,7 @@ pub async fn spam_reading_schedule(
             if let Ok(msgs) = messages {
                 let now = chrono::Utc::now().date_naive();
 
-                if let Some(_) = msgs
+                if msgs
                     .iter()
                     .filter(|m| m.author.bot && m.timestamp.date_naive() == now)
                     .filter_map(|m| m.embeds.first())
@@ -139,6 +177,7 @@ pub async fn spam_reading_schedule(
                             .unwrap_or(&String::new())
                             .contains("📖 Daily Reading")
                     })
+                    .is_some()
                 {
                     // hey!(
                     //     "Found reading schedule message for {}, not sending another message.",
@@ -167,15 +206,31 @@ pub async fn spam_reading_schedule(
                     )
                 };
 
+                // introduce any books that start today
+                let introductions = books_started(&reading, bible)
+                    .into_iter()
+                    .filter_map(|book| {
+                        let info = book_info(&book)?;
+                        Some((
+                            format!("Starting {}", BibleLookup::capitalize_book(&book)),
+                            format!(
+                                "{}\n*{} · {} · {}*",
+                                info.summary, info.section, info.author, info.date
+                            ),
+                            false,
+                        ))
+                    });
+
                 CreateEmbed::new()
-                    .title(format!("📖 Daily Reading"))
+                    .title("📖 Daily Reading")
                     .description(description)
+                    .fields(introductions)
                     .color(Colour::GOLD)
                     .footer(CreateEmbedFooter::new("Come read with us!"))
             } else {
                 CreateEmbed::new()
-                .title(format!("📖 Daily Reading"))
-                .description(format!("No reading for today! You have completed the Bible this year!\nPlease use this time to catch up or reread missed chapters."))
+                .title("📖 Daily Reading")
+                .description("No reading for today! You have completed the Bible this year!\nPlease use this time to catch up or reread missed chapters.")
                 .color(Colour::GOLD)
                 .footer(CreateEmbedFooter::new(format!("From the {} Bible.", bible.get_translation())))
             };
@@ -190,3 +245,21 @@ pub async fn spam_reading_schedule(
         }
     }
 }
+
+/// The books that begin within a reading, in order
+fn books_started(reading: &Reading, bible: &Bible) -> Vec<String> {
+    let books = bible.get_sorted_books();
+    let first = books.iter().position(|book| *book == reading.start.book);
+    let last = books.iter().position(|book| *book == reading.end.book);
+    let (Some(first), Some(last)) = (first, last) else {
+        return Vec::new();
+    };
+
+    books
+        .get(first..=last)
+        .unwrap_or_default()
+        .iter()
+        .filter(|book| **book != reading.start.book || reading.start.chapter == 1)
+        .cloned()
+        .collect()
+}
@@ -25,12 +25,12 @@ impl From<BibleLookup> for LookupStore {
     }
 }
 
-impl Into<BibleLookup> for LookupStore {
-    fn into(self) -> BibleLookup {
+impl From<LookupStore> for BibleLookup {
+    fn from(val: LookupStore) -> Self {
         BibleLookup {
-            book: self.book,
-            chapter: self.chapter,
-            verse: self.verse,
+            book: val.book,
+            chapter: val.chapter,
+            verse: val.verse,
             thru_verse: None,
         }
     }
@@ -48,7 +48,7 @@ impl DailyVerseHandler {
     }
 
     pub fn get(bible: &Bible) -> Self {
-        let raw_path = format!("{}", PATH);
+        let raw_path = PATH.to_string();
         let path = std::path::Path::new(&raw_path);
 
         if !path.exists() {
@@ -78,7 +78,7 @@ impl DailyVerseHandler {
     }
 
     pub fn generate(bible: &Bible) -> Option<Self> {
-        let raw_path = format!("{}", PATH);
+        let raw_path = PATH.to_string();
         let path = Path::new(raw_path.as_str());
 
         if path.exists() {
@@ -90,6 +90,7 @@ impl DailyVerseHandler {
             .read(false)
             .write(true)
             .create(true)
+            .truncate(false)
             .append(false)
             .open(path)
         else {
@@ -112,7 +113,7 @@ impl DailyVerseHandler {
     }
 
     fn update(&self, bible: &Bible) {
-        let raw_path = format!("{}", PATH);
+        let raw_path = PATH.to_string();
         let path = Path::new(raw_path.as_str());
 
         if !path.exists() {
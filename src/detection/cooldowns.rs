@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::all::{ChannelId, UserId};
+
+use crate::guildfile::DetectionSettings;
+
+// entries older than this are dropped so the maps don't grow forever
+const PRUNE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Tracks when the bot last replied to detected verses per channel and per user
+#[derive(Default)]
+pub struct Cooldowns {
+    channels: HashMap<ChannelId, Instant>,
+    users: HashMap<UserId, Instant>,
+}
+
+impl Cooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if a reply is allowed, and starts the cooldowns if so
+    pub fn try_trigger(
+        &mut self,
+        channel: ChannelId,
+        user: UserId,
+        settings: &DetectionSettings,
+    ) -> bool {
+        self.try_trigger_at(channel, user, settings, Instant::now())
+    }
+
+    fn try_trigger_at(
+        &mut self,
+        channel: ChannelId,
+        user: UserId,
+        settings: &DetectionSettings,
+        now: Instant,
+    ) -> bool {
+        let channel_cooldown = Duration::from_secs(settings.channel_cooldown);
+        if let Some(last) = self.channels.get(&channel) {
+            if now.duration_since(*last) < channel_cooldown {
+                return false;
+            }
+        }
+
+        let user_cooldown = Duration::from_secs(settings.user_cooldown);
+        if let Some(last) = self.users.get(&user) {
+            if now.duration_since(*last) < user_cooldown {
+                return false;
+            }
+        }
+
+        self.prune(now);
+        self.channels.insert(channel, now);
+        self.users.insert(user, now);
+        true
+    }
+
+    fn prune(&mut self, now: Instant) {
+        self.channels
+            .retain(|_, last| now.duration_since(*last) < PRUNE_AFTER);
+        self.users
+            .retain(|_, last| now.duration_since(*last) < PRUNE_AFTER);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(channel_cooldown: u64, user_cooldown: u64) -> DetectionSettings {
+        DetectionSettings {
+            channel_cooldown,
+            user_cooldown,
+            ..DetectionSettings::default()
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn no_cooldowns() {
+        let mut cooldowns = Cooldowns::new();
+        let settings = settings(0, 0);
+        let now = Instant::now();
+        assert!(cooldowns.try_trigger_at(ChannelId::new(1), UserId::new(1), &settings, now));
+        assert!(cooldowns.try_trigger_at(ChannelId::new(1), UserId::new(1), &settings, now));
+    }
+
+    #[test]
+    fn user_cooldown() {
+        let mut cooldowns = Cooldowns::new();
+        let settings = settings(0, 5);
+        let now = Instant::now();
+        let (channel, user) = (ChannelId::new(1), UserId::new(1));
+        assert!(cooldowns.try_trigger_at(channel, user, &settings, now));
+        assert!(!cooldowns.try_trigger_at(channel, user, &settings, now + secs(4)));
+        // other users aren't held back
+        assert!(cooldowns.try_trigger_at(channel, UserId::new(2), &settings, now + secs(4)));
+        assert!(cooldowns.try_trigger_at(channel, user, &settings, now + secs(5)));
+    }
+
+    #[test]
+    fn channel_cooldown() {
+        let mut cooldowns = Cooldowns::new();
+        let settings = settings(10, 0);
+        let now = Instant::now();
+        let channel = ChannelId::new(1);
+        assert!(cooldowns.try_trigger_at(channel, UserId::new(1), &settings, now));
+        assert!(!cooldowns.try_trigger_at(channel, UserId::new(2), &settings, now + secs(9)));
+        // other channels aren't held back
+        assert!(cooldowns.try_trigger_at(ChannelId::new(2), UserId::new(2), &settings, now));
+        assert!(cooldowns.try_trigger_at(channel, UserId::new(2), &settings, now + secs(10)));
+    }
+
+    #[test]
+    fn blocked_replies_do_not_restart_cooldowns() {
+        let mut cooldowns = Cooldowns::new();
+        let settings = settings(0, 5);
+        let now = Instant::now();
+        let (channel, user) = (ChannelId::new(1), UserId::new(1));
+        assert!(cooldowns.try_trigger_at(channel, user, &settings, now));
+        assert!(!cooldowns.try_trigger_at(channel, user, &settings, now + secs(3)));
+        assert!(cooldowns.try_trigger_at(channel, user, &settings, now + secs(5)));
+    }
+
+    #[test]
+    fn old_entries_are_pruned() {
+        let mut cooldowns = Cooldowns::new();
+        let settings = settings(1, 1);
+        let now = Instant::now();
+        assert!(cooldowns.try_trigger_at(ChannelId::new(1), UserId::new(1), &settings, now));
+        let later = now + PRUNE_AFTER + secs(1);
+        assert!(cooldowns.try_trigger_at(ChannelId::new(2), UserId::new(2), &settings, later));
+        assert_eq!(cooldowns.channels.len(), 1);
+        assert_eq!(cooldowns.users.len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ChannelId, Colour, Context, CreateEmbed, CreateMessage, EditMessage, MessageId,
+};
+
+use crate::{
+    books::aliases::BookAliases,
+    detection::reply_tracker::TrackedReply,
+    discord_helpers::{
+        craft_book_overview_embed,
+        delivery::{
+            delete_passage, is_long_passage, reply_with_passage, PassagePages, SentPassage,
+        },
+        group_verse_embeds, EmbedGroup,
+    },
+    guildfile::DetectionSettings,
+    nay,
+    reference_parser::{parse_references, parse_whole_reference, WholeReference},
+};
+
+/// Detects the verses in a message, understanding book aliases like `Jn` or `1 Cor`
+/// and lists like `John 3:16,18` or `Matt 5:3-12; 6:9-13`
+pub fn detect_verses(content: &str, aliases: &BookAliases, bible: &Bible) -> Vec<BibleLookup> {
+    parse_references(content, aliases)
+        .into_iter()
+        .map(|span| {
+            // match the book names used by the loaded translation
+            let book = aliases
+                .resolve(&span.book, bible)
+                .unwrap_or_else(|| span.book.clone());
+            span.to_lookup(&book, bible)
+        })
+        .collect()
+}
+
+/// Detects a reference to a whole chapter or book, if the guild has enabled them
+/// The book is resolved to the name used by the loaded bible; references that don't exist are ignored
+pub fn detect_whole_reference(
+    content: &str,
+    aliases: &BookAliases,
+    bible: &Bible,
+    settings: &DetectionSettings,
+) -> Option<WholeReference> {
+    if !settings.whole_chapters && !settings.whole_books {
+        return None;
+    }
+
+    match parse_whole_reference(content, aliases)? {
+        WholeReference::Chapter { book, chapter } if settings.whole_chapters => {
+            let book = aliases.resolve(&book, bible)?;
+            bible.get_max_verse(&book, chapter).ok()?;
+            Some(WholeReference::Chapter { book, chapter })
+        }
+        WholeReference::Book(book) if settings.whole_books => {
+            Some(WholeReference::Book(aliases.resolve(&book, bible)?))
+        }
+        _ => None,
+    }
+}
+
+/// Removes repeated references (keeping the first occurrence) and caps the list
+/// Returns the references to show and how many were left out
+pub fn limit_references(
+    found: Vec<BibleLookup>,
+    settings: &DetectionSettings,
+) -> (Vec<BibleLookup>, usize) {
+    let mut unique: Vec<BibleLookup> = Vec::new();
+    for lookup in found {
+        if !unique.contains(&lookup) {
+            unique.push(lookup);
+        }
+    }
+
+    let max = settings.max_references.max(1);
+    let overflow = unique.len().saturating_sub(max);
+    unique.truncate(max);
+
+    (unique, overflow)
+}
+
+/// Builds the embeds for every reply to a message, ending with a notice for any overflow
+/// Passages too long to go with the other verses are returned separately, to be delivered by the guild's policy
+pub fn build_replies(
+    verses: Vec<BibleLookup>,
+    bible: &Bible,
+    settings: &DetectionSettings,
+) -> (Vec<EmbedGroup>, Vec<PassagePages>) {
+    let (verses, overflow) = limit_references(verses, settings);
+
+    let mut long = Vec::new();
+    let mut verses = verses;
+    verses.retain(|lookup| {
+        let pages = is_long_passage(lookup, bible)
+            .then(|| PassagePages::new(lookup, bible))
+            .flatten();
+        match pages {
+            Some(pages) => {
+                long.push(pages);
+                false
+            }
+            None => true,
+        }
+    });
+
+    let mut replies = group_verse_embeds(
+        verses,
+        bible,
+        settings.verses_per_reply(),
+        settings.commentary_button,
+    );
+
+    if overflow > 0 {
+        let embed = CreateEmbed::new()
+            .description(format!(
+                "{} more reference{} in this message {} not shown (limit of {} per message).",
+                overflow,
+                if overflow == 1 { "" } else { "s" },
+                if overflow == 1 { "was" } else { "were" },
+                settings.max_references.max(1)
+            ))
+            .color(Colour::GOLD);
+        replies.push(EmbedGroup::new(vec![embed]));
+    }
+
+    (replies, long)
+}
+
+/// Replies to a message with the verses detected in it
+/// Returns the ids of the replies that were sent, and the long passages delivered on their own
+pub async fn reply_with_verses(
+    ctx: &Context,
+    channel: ChannelId,
+    source: MessageId,
+    verses: Vec<BibleLookup>,
+    bible: &Bible,
+    settings: &DetectionSettings,
+) -> (Vec<MessageId>, Vec<SentPassage>) {
+    let mut sent = Vec::new();
+    let (replies, long) = build_replies(verses, bible, settings);
+
+    for group in replies {
+        let builder = CreateMessage::new()
+            .embeds(group.embeds)
+            .components(group.buttons)
+            .reference_message((channel, source));
+        match channel.send_message(&ctx.http, builder).await {
+            Ok(reply) => sent.push(reply.id),
+            Err(e) => nay!("Failed to send message: {}", e),
+        }
+    }
+
+    let mut passages = Vec::new();
+    for pages in long {
+        passages.extend(
+            reply_with_passage(ctx, channel, source, &pages, settings.long_passages, bible).await,
+        );
+    }
+
+    (sent, passages)
+}
+
+/// Replies to a message with a whole chapter (delivered by the guild's policy) or a book overview
+/// Returns the ids of the replies that were sent, with a chapter as a passage delivered on its own
+pub async fn reply_with_whole_reference(
+    ctx: &Context,
+    channel: ChannelId,
+    source: MessageId,
+    reference: &WholeReference,
+    bible: &Bible,
+    settings: &DetectionSettings,
+) -> (Vec<MessageId>, Vec<SentPassage>) {
+    let builder = match reference {
+        WholeReference::Chapter { book, chapter } => {
+            let Some(pages) = PassagePages::chapter(book, *chapter, bible) else {
+                return (Vec::new(), Vec::new());
+            };
+            let sent =
+                reply_with_passage(ctx, channel, source, &pages, settings.long_passages, bible)
+                    .await;
+            return (Vec::new(), sent.into_iter().collect());
+        }
+        WholeReference::Book(book) => {
+            let Some(embed) = craft_book_overview_embed(book, bible) else {
+                return (Vec::new(), Vec::new());
+            };
+            CreateMessage::new().embed(embed)
+        }
+    };
+
+    match channel
+        .send_message(&ctx.http, builder.reference_message((channel, source)))
+        .await
+    {
+        Ok(reply) => (vec![reply.id], Vec::new()),
+        Err(e) => {
+            nay!("Failed to send message: {}", e);
+            (Vec::new(), Vec::new())
+        }
+    }
+}
+
+/// Edits the existing replies to a message so they match its new verses
+/// Extra replies are sent or deleted as needed, and long passages are only sent (or deleted) when
+/// they are new to (or gone from) the message; returns the replies and passages that remain
+pub async fn update_replies(
+    ctx: &Context,
+    source: MessageId,
+    tracked: &TrackedReply,
+    verses: Vec<BibleLookup>,
+    bible: &Bible,
+    settings: &DetectionSettings,
+) -> (Vec<MessageId>, Vec<SentPassage>) {
+    let channel = tracked.channel;
+    let existing = &tracked.replies;
+    let mut kept = Vec::new();
+    let (replies, long) = build_replies(verses, bible, settings);
+    let reply_count = replies.len();
+
+    for (index, group) in replies.into_iter().enumerate() {
+        if let Some(reply) = existing.get(index) {
+            let builder = EditMessage::new()
+                .embeds(group.embeds)
+                .components(group.buttons);
+            match channel.edit_message(&ctx.http, *reply, builder).await {
+                Ok(_) => kept.push(*reply),
+                Err(e) => nay!("Failed to edit message: {}", e),
+            }
+        } else {
+            let builder = CreateMessage::new()
+                .embeds(group.embeds)
+                .components(group.buttons)
+                .reference_message((channel, source));
+            match channel.send_message(&ctx.http, builder).await {
+                Ok(reply) => kept.push(reply.id),
+                Err(e) => nay!("Failed to send message: {}", e),
+            }
+        }
+    }
+
+    // the message now has fewer replies than before
+    if existing.len() > reply_count {
+        delete_replies(ctx, channel, &existing[reply_count..]).await;
+    }
+
+    // long passages can take several messages or a thread, so they aren't edited in place;
+    // the ones still in the message are left as they are
+    for passage in &tracked.passages {
+        if !long.iter().any(|pages| pages.lookup == passage.lookup) {
+            delete_passage(ctx, channel, passage).await;
+        }
+    }
+    let mut passages = Vec::new();
+    for pages in long {
+        match tracked
+            .passages
+            .iter()
+            .find(|passage| passage.lookup == pages.lookup)
+        {
+            Some(passage) => passages.push(passage.clone()),
+            None => passages.extend(
+                reply_with_passage(ctx, channel, source, &pages, settings.long_passages, bible)
+                    .await,
+            ),
+        }
+    }
+
+    (kept, passages)
+}
+
+/// Deletes the bot's replies to a message
+pub async fn delete_replies(ctx: &Context, channel: ChannelId, replies: &[MessageId]) {
+    for reply in replies {
+        if let Err(e) = channel.delete_message(&ctx.http, *reply).await {
+            nay!("Failed to delete message: {}", e);
+        }
+    }
+}
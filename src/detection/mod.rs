@@ -0,0 +1,3 @@
+pub mod cooldowns;
+pub mod detection_handler;
+pub mod reply_tracker;
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, VecDeque};
+
+use bible_lib::BibleLookup;
+use serenity::all::{ChannelId, MessageId, UserId};
+
+use crate::{discord_helpers::delivery::SentPassage, reference_parser::WholeReference};
+
+// how many source messages are remembered before the oldest are forgotten
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// The bot's replies to a single message containing verses
+#[derive(Clone)]
+pub struct TrackedReply {
+    pub author: UserId,
+    pub channel: ChannelId,
+    pub verses: Vec<BibleLookup>,
+    /// set instead of `verses` when the reply is a whole chapter or book
+    pub whole: Option<WholeReference>,
+    /// the replies with the verses, edited in place when the message changes
+    pub replies: Vec<MessageId>,
+    /// passages too long to go with the other verses, which were delivered on their own
+    pub passages: Vec<SentPassage>,
+}
+
+impl TrackedReply {
+    fn contains(&self, reply: &MessageId) -> bool {
+        self.replies.contains(reply)
+            || self
+                .passages
+                .iter()
+                .any(|passage| passage.replies.contains(reply))
+    }
+}
+
+/// A bounded map of source messages to the bot's replies
+/// Used to keep replies in sync when the source message is edited or deleted
+pub struct ReplyTracker {
+    capacity: usize,
+    order: VecDeque<MessageId>,
+    entries: HashMap<MessageId, TrackedReply>,
+}
+
+impl ReplyTracker {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Remember the replies to a message, forgetting the oldest message if full
+    pub fn track(&mut self, source: MessageId, reply: TrackedReply) {
+        if self.entries.insert(source, reply).is_some() {
+            // already tracked, keep its place in line
+            return;
+        }
+
+        self.order.push_back(source);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, source: &MessageId) -> Option<&TrackedReply> {
+        self.entries.get(source)
+    }
+
+    pub fn remove(&mut self, source: &MessageId) -> Option<TrackedReply> {
+        let removed = self.entries.remove(source)?;
+        self.order.retain(|id| id != source);
+        Some(removed)
+    }
+
+    /// Stop tracking one reply, forgetting the source message once it has none left
+    pub fn forget_reply(&mut self, source: &MessageId, reply: &MessageId) {
+        let Some(tracked) = self.entries.get_mut(source) else {
+            return;
+        };
+        tracked.replies.retain(|id| id != reply);
+        for passage in tracked.passages.iter_mut() {
+            passage.replies.retain(|id| id != reply);
+        }
+        tracked
+            .passages
+            .retain(|passage| !passage.replies.is_empty());
+        if tracked.replies.is_empty() && tracked.passages.is_empty() {
+            self.remove(source);
+        }
+    }
+
+    /// Find the source message of one of the bot's replies
+    pub fn find_source(&self, reply: &MessageId) -> Option<MessageId> {
+        self.entries
+            .iter()
+            .find(|(_, tracked)| tracked.contains(reply))
+            .map(|(source, _)| *source)
+    }
+}
+
+impl Default for ReplyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracked(replies: &[u64], passage: &[u64]) -> TrackedReply {
+        TrackedReply {
+            author: UserId::new(1),
+            channel: ChannelId::new(1),
+            verses: Vec::new(),
+            whole: None,
+            replies: replies.iter().map(|id| MessageId::new(*id)).collect(),
+            passages: vec![SentPassage {
+                lookup: BibleLookup::new_range("psalms", 119, 1, 176),
+                replies: passage.iter().map(|id| MessageId::new(*id)).collect(),
+                thread: Some(ChannelId::new(50)),
+            }],
+        }
+    }
+
+    #[test]
+    fn passage_replies_are_found() {
+        let mut tracker = ReplyTracker::new();
+        tracker.track(MessageId::new(1), tracked(&[10], &[20, 21]));
+        assert_eq!(
+            tracker.find_source(&MessageId::new(10)),
+            Some(MessageId::new(1))
+        );
+        assert_eq!(
+            tracker.find_source(&MessageId::new(21)),
+            Some(MessageId::new(1))
+        );
+        assert_eq!(tracker.find_source(&MessageId::new(30)), None);
+    }
+
+    #[test]
+    fn forgetting_replies() {
+        let mut tracker = ReplyTracker::new();
+        let source = MessageId::new(1);
+        tracker.track(source, tracked(&[10], &[20]));
+
+        // the passage is still there after the verses are gone
+        tracker.forget_reply(&source, &MessageId::new(10));
+        assert_eq!(tracker.get(&source).unwrap().passages.len(), 1);
+
+        tracker.forget_reply(&source, &MessageId::new(20));
+        assert!(tracker.get(&source).is_none());
+    }
+
+    #[test]
+    fn oldest_are_forgotten() {
+        let mut tracker = ReplyTracker::with_capacity(2);
+        for id in 1..=3 {
+            tracker.track(MessageId::new(id), tracked(&[id * 10], &[]));
+        }
+        assert!(tracker.get(&MessageId::new(1)).is_none());
+        assert!(tracker.get(&MessageId::new(3)).is_some());
+    }
+}
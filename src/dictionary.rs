@@ -0,0 +1,199 @@
+// A Bible dictionary imported from an Easton's style JSON file, for `/define`.
+//
+// The file can either be a list of entries, `[{"term": "Aaron", "definition": "..."}]`
+// (`word` or `name` work for the term, `text` or `description` for the definition),
+// or a map of terms to definitions, `{"Aaron": "..."}`.
+
+use std::collections::HashMap;
+
+use levenshtein::levenshtein;
+
+use crate::{hey, nay, say};
+
+pub const PATH: &str = "./data/dictionary.json";
+
+// how far a misspelled term can be from a real one to still be matched
+const MAX_FUZZY_DISTANCE: usize = 3;
+// terms go into `define_page:{term}:{page}` button ids and autocomplete choices,
+// which discord limits to 100 characters
+pub const MAX_TERM_CHARS: usize = 80;
+
+/// A term and what it means
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Entry {
+    #[serde(alias = "word", alias = "name")]
+    pub term: String,
+    #[serde(alias = "text", alias = "description")]
+    pub definition: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum DictionaryFile {
+    List(Vec<Entry>),
+    Map(HashMap<String, String>),
+}
+
+/// Every imported entry, sorted by term
+pub struct Dictionary {
+    entries: Vec<Entry>,
+}
+
+impl Dictionary {
+    /// Import the dictionary at `PATH`, if there is one
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read_to_string(PATH) else {
+            hey!(
+                "No dictionary found at {}, `/define` will have nothing to show",
+                PATH
+            );
+            return Self {
+                entries: Vec::new(),
+            };
+        };
+
+        match Self::import(&data) {
+            Ok(dictionary) => {
+                say!("Loaded {} dictionary entries", dictionary.entries.len());
+                dictionary
+            }
+            Err(e) => {
+                nay!("Failed to read the dictionary at {}: {}", PATH, e);
+                Self {
+                    entries: Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// Read the dictionary from JSON
+    /// Terms longer than `MAX_TERM_CHARS` are cut short
+    pub fn import(data: &str) -> Result<Self, serde_json::Error> {
+        let entries = match serde_json::from_str(data)? {
+            DictionaryFile::List(entries) => entries,
+            DictionaryFile::Map(map) => map
+                .into_iter()
+                .map(|(term, definition)| Entry { term, definition })
+                .collect(),
+        };
+
+        let mut entries: Vec<Entry> = entries
+            .into_iter()
+            .map(|entry| Entry {
+                term: entry
+                    .term
+                    .trim()
+                    .chars()
+                    .take(MAX_TERM_CHARS)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string(),
+                definition: entry.definition.trim().to_string(),
+            })
+            .filter(|entry| !entry.term.is_empty() && !entry.definition.is_empty())
+            .collect();
+        entries.sort_by_key(|entry| entry.term.to_lowercase());
+
+        Ok(Self { entries })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find an entry by its term, ignoring case
+    pub fn get(&self, term: &str) -> Option<&Entry> {
+        let term = term.trim();
+        self.entries
+            .iter()
+            .find(|entry| entry.term.eq_ignore_ascii_case(term))
+    }
+
+    /// Find an entry, falling back to the closest term if it's misspelled
+    pub fn find(&self, term: &str) -> Option<&Entry> {
+        if let Some(entry) = self.get(term) {
+            return Some(entry);
+        }
+
+        let term = term.trim().to_lowercase();
+        self.entries
+            .iter()
+            .map(|entry| (levenshtein(&term, &entry.term.to_lowercase()), entry))
+            .filter(|(distance, _)| *distance <= MAX_FUZZY_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, entry)| entry)
+    }
+
+    /// Terms for autocomplete: ones starting with the text first, then ones containing it
+    pub fn complete(&self, partial: &str, limit: usize) -> Vec<&str> {
+        let partial = partial.trim().to_lowercase();
+        let starting = self
+            .entries
+            .iter()
+            .filter(|entry| entry.term.to_lowercase().starts_with(&partial));
+        let containing = self.entries.iter().filter(|entry| {
+            let term = entry.term.to_lowercase();
+            !term.starts_with(&partial) && term.contains(&partial)
+        });
+
+        starting
+            .chain(containing)
+            .take(limit)
+            .map(|entry| entry.term.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_of_entries() {
+        let dictionary = Dictionary::import(
+            r#"[{"term": "Aaron", "definition": "Brother of Moses."}, {"word": "Abba", "text": "Father."}]"#,
+        )
+        .unwrap();
+        assert_eq!(dictionary.complete("", 10), vec!["Aaron", "Abba"]);
+        assert_eq!(dictionary.get("abba").unwrap().definition, "Father.");
+    }
+
+    #[test]
+    fn map_of_terms() {
+        let dictionary = Dictionary::import(
+            r#"{"Zion": "A hill in Jerusalem.", "Atonement": "Reconciliation."}"#,
+        )
+        .unwrap();
+        assert_eq!(dictionary.complete("", 10), vec!["Atonement", "Zion"]);
+    }
+
+    #[test]
+    fn blank_entries_are_skipped() {
+        let dictionary =
+            Dictionary::import(r#"{" ": "Nothing.", "Empty": "  ", " Grace ": " Favour. "}"#)
+                .unwrap();
+        assert_eq!(dictionary.complete("", 10), vec!["Grace"]);
+        assert_eq!(dictionary.get("grace").unwrap().definition, "Favour.");
+    }
+
+    #[test]
+    fn long_terms_are_cut_short() {
+        let term = "Name ".repeat(40);
+        let dictionary = Dictionary::import(&format!(r#"{{"{}": "Someone."}}"#, term)).unwrap();
+        let terms = dictionary.complete("", 10);
+        assert!(terms[0].chars().count() <= MAX_TERM_CHARS);
+        assert!(!terms[0].ends_with(' '));
+    }
+
+    #[test]
+    fn misspellings_are_matched() {
+        let dictionary = Dictionary::import(r#"{"Atonement": "Reconciliation."}"#).unwrap();
+        assert_eq!(dictionary.find("attonement").unwrap().term, "Atonement");
+        assert!(dictionary.find("zebra").is_none());
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(Dictionary::import("not json").is_err());
+    }
+}
@@ -1,33 +1,223 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use bible_lib::{Bible, BibleLookup};
+use bible_lib::Bible;
 use chrono::Local;
 use serenity::{
     all::{
-        ActivityData, Context, CreateMessage, EventHandler, Interaction, Message, OnlineStatus,
-        Ready, ResumedEvent,
+        ActivityData, ChannelId, Context, EventHandler, GuildId, Interaction, Message, MessageId,
+        MessageUpdateEvent, OnlineStatus, Reaction, ReactionType, Ready, ResumedEvent, UserId,
     },
     async_trait,
 };
 
 use crate::{
+    books::aliases::BookAliases,
     commands,
+    commentary::Commentaries,
+    cross_references::CrossReferences,
     daily_messages::{
         daily_msg_handler::{get_time_until_7am, spam_daily_verse, spam_reading_schedule},
         daily_verse::DailyVerseHandler,
         reading_scheudle,
     },
-    discord_helpers::{command_response, craft_bible_verse_embed, register_command},
-    guildfile::GuildSettings,
-    nay, say, yay,
+    detection::{
+        cooldowns::Cooldowns,
+        detection_handler::{
+            delete_replies, detect_verses, detect_whole_reference, reply_with_verses,
+            reply_with_whole_reference, update_replies,
+        },
+        reply_tracker::{ReplyTracker, TrackedReply},
+    },
+    dictionary::Dictionary,
+    discord_helpers::{
+        command_response,
+        delivery::{self, delete_passage},
+        register_command,
+    },
+    guildfile::{DetectionSettings, GuildSettings},
+    memory::{send_due_reviews, CHECK_INTERVAL},
+    nay,
+    quiz::QuizTracker,
+    say,
+    stats::Statistics,
+    strongs::Strongs,
+    topics::TopicalIndex,
+    translations::Translations,
+    yay,
 };
 
+// reacting to a verse reply with this emoji lets the original author dismiss it
+const DISMISS_EMOJI: &str = "🗑";
+// edits to messages the bot has no reply for are only answered this long (in seconds) after sending
+const UNTRACKED_EDIT_MAX_AGE: i64 = 5 * 60;
+
 pub(crate) struct Handler {
     pub bible: Arc<Bible>,
     pub subprocess_running: Arc<AtomicBool>,
+    pub cooldowns: Arc<Mutex<Cooldowns>>,
+    pub replies: Arc<Mutex<ReplyTracker>>,
+    pub quizzes: Arc<Mutex<QuizTracker>>,
+    pub aliases: Arc<BookAliases>,
+    // each guild's aliases merged with the built-in ones, dropped when the guild changes them
+    pub guild_alias_cache: Arc<Mutex<HashMap<GuildId, Arc<BookAliases>>>>,
+    // each guild's detection settings, so messages don't read the guild file until they change
+    pub detection_settings_cache: Arc<Mutex<HashMap<GuildId, DetectionSettings>>>,
+    pub translations: Arc<Translations>,
+    pub statistics: Arc<Statistics>,
+    pub cross_references: Arc<CrossReferences>,
+    pub topics: Arc<TopicalIndex>,
+    pub dictionary: Arc<Dictionary>,
+    pub commentaries: Arc<Commentaries>,
+    pub strongs: Arc<Strongs>,
+}
+
+impl Handler {
+    /// The book aliases for a guild, including its custom ones
+    fn guild_aliases(&self, guild: Option<GuildId>) -> Arc<BookAliases> {
+        let Some(guild) = guild else {
+            return self.aliases.clone();
+        };
+        let mut cache = match self.guild_alias_cache.lock() {
+            Ok(cache) => cache,
+            Err(e) => {
+                nay!("Failed to lock book alias cache: {}", e);
+                return Arc::new(self.aliases.with_guild(&GuildSettings::get(&guild)));
+            }
+        };
+        cache
+            .entry(guild)
+            .or_insert_with(|| Arc::new(self.aliases.with_guild(&GuildSettings::get(&guild))))
+            .clone()
+    }
+
+    /// Drops the cached aliases for a guild after it changes them
+    fn forget_guild_aliases(&self, guild: GuildId) {
+        match self.guild_alias_cache.lock() {
+            Ok(mut cache) => {
+                cache.remove(&guild);
+            }
+            Err(e) => nay!("Failed to lock book alias cache: {}", e),
+        }
+    }
+
+    /// The detection settings for a guild (DMs use the defaults)
+    fn detection_settings(&self, guild: Option<GuildId>) -> DetectionSettings {
+        let Some(guild) = guild else {
+            return DetectionSettings::default();
+        };
+        let mut cache = match self.detection_settings_cache.lock() {
+            Ok(cache) => cache,
+            Err(e) => {
+                nay!("Failed to lock detection settings cache: {}", e);
+                return GuildSettings::get(&guild).get_detection_settings();
+            }
+        };
+        *cache
+            .entry(guild)
+            .or_insert_with(|| GuildSettings::get(&guild).get_detection_settings())
+    }
+
+    /// Drops the cached detection settings for a guild after it changes them
+    fn forget_detection_settings(&self, guild: GuildId) {
+        match self.detection_settings_cache.lock() {
+            Ok(mut cache) => {
+                cache.remove(&guild);
+            }
+            Err(e) => nay!("Failed to lock detection settings cache: {}", e),
+        }
+    }
+
+    /// Reply to the verses detected in a message and remember the replies
+    async fn reply_to_message(
+        &self,
+        ctx: &Context,
+        channel: ChannelId,
+        source: MessageId,
+        author: UserId,
+        guild: Option<GuildId>,
+        content: &str,
+    ) {
+        // get the detection limits for this guild (DMs use the defaults)
+        let settings = self.detection_settings(guild);
+
+        // detect bible verses, or a whole chapter or book if there are none
+        let aliases = self.guild_aliases(guild);
+        let verses = detect_verses(content, &aliases, &self.bible);
+        let whole = if verses.is_empty() {
+            detect_whole_reference(content, &aliases, &self.bible, &settings)
+        } else {
+            None
+        };
+        if verses.is_empty() && whole.is_none() {
+            return;
+        }
+
+        // respect the channel and user cooldowns
+        let allowed = match self.cooldowns.lock() {
+            Ok(mut cooldowns) => cooldowns.try_trigger(channel, author, &settings),
+            Err(e) => {
+                nay!("Failed to lock cooldowns: {}", e);
+                true
+            }
+        };
+        if !allowed {
+            return;
+        }
+
+        // send the verses
+        let (replies, passages) = match &whole {
+            Some(reference) => {
+                reply_with_whole_reference(ctx, channel, source, reference, &self.bible, &settings)
+                    .await
+            }
+            None => {
+                reply_with_verses(ctx, channel, source, verses.clone(), &self.bible, &settings)
+                    .await
+            }
+        };
+        if replies.is_empty() && passages.is_empty() {
+            return;
+        }
+
+        match self.replies.lock() {
+            Ok(mut tracker) => tracker.track(
+                source,
+                TrackedReply {
+                    author,
+                    channel,
+                    verses,
+                    whole,
+                    replies,
+                    passages,
+                },
+            ),
+            Err(e) => nay!("Failed to lock reply tracker: {}", e),
+        }
+    }
+
+    /// Delete the replies to a message that was deleted or dismissed
+    async fn remove_replies(&self, ctx: &Context, source: MessageId) {
+        let tracked = match self.replies.lock() {
+            Ok(mut tracker) => tracker.remove(&source),
+            Err(e) => {
+                nay!("Failed to lock reply tracker: {}", e);
+                return;
+            }
+        };
+
+        if let Some(tracked) = tracked {
+            delete_replies(ctx, tracked.channel, &tracked.replies).await;
+            for passage in &tracked.passages {
+                delete_passage(ctx, tracked.channel, passage).await;
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -38,19 +228,186 @@ impl EventHandler for Handler {
             return;
         }
 
-        // detect bible verses
-        let verses = BibleLookup::detect_from_string(&msg.content);
-        // send the verses
-        for verse in verses {
-            // create the embed with the bible verse
-            if let Some(embed) = craft_bible_verse_embed(verse.clone(), &self.bible) {
-                let builder = CreateMessage::new().embed(embed).reference_message(&msg);
-
-                let msg = msg.channel_id.send_message(&ctx.http, builder).await;
-                if let Err(e) = msg {
-                    nay!("Failed to send message: {}", e);
+        self.reply_to_message(
+            &ctx,
+            msg.channel_id,
+            msg.id,
+            msg.author.id,
+            msg.guild_id,
+            &msg.content,
+        )
+        .await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        old: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // updates without content (such as link previews loading) can't change the verses
+        let Some(content) = event.content else {
+            return;
+        };
+
+        let tracked = match self.replies.lock() {
+            Ok(tracker) => tracker.get(&event.id).cloned(),
+            Err(e) => {
+                nay!("Failed to lock reply tracker: {}", e);
+                return;
+            }
+        };
+
+        // the bot isn't tracking a reply to this message, which may have been answered before a
+        // restart or forgotten since, so only reply if it's recent and the edit added references
+        let Some(tracked) = tracked else {
+            let Some(author) = event.author else {
+                return;
+            };
+            if author.bot {
+                return;
+            }
+            let age = chrono::Utc::now().timestamp() - event.id.created_at().unix_timestamp();
+            if age > UNTRACKED_EDIT_MAX_AGE {
+                return;
+            }
+            if let Some(old) = &old {
+                let aliases = self.guild_aliases(event.guild_id);
+                let before = detect_verses(&old.content, &aliases, &self.bible);
+                let after = detect_verses(&content, &aliases, &self.bible);
+                if !after.is_empty() && after.iter().all(|lookup| before.contains(lookup)) {
+                    return;
                 }
             }
+            self.reply_to_message(
+                &ctx,
+                event.channel_id,
+                event.id,
+                author.id,
+                event.guild_id,
+                &content,
+            )
+            .await;
+            return;
+        };
+
+        let settings = self.detection_settings(event.guild_id);
+
+        let aliases = self.guild_aliases(event.guild_id);
+        let verses = detect_verses(&content, &aliases, &self.bible);
+        let whole = if verses.is_empty() {
+            detect_whole_reference(&content, &aliases, &self.bible, &settings)
+        } else {
+            None
+        };
+        if verses == tracked.verses && whole == tracked.whole {
+            return;
+        }
+
+        // switching to or from a whole chapter or book starts the replies over
+        if whole.is_some() || tracked.whole.is_some() {
+            self.remove_replies(&ctx, event.id).await;
+            self.reply_to_message(
+                &ctx,
+                tracked.channel,
+                event.id,
+                tracked.author,
+                event.guild_id,
+                &content,
+            )
+            .await;
+            return;
+        }
+
+        let (replies, passages) = update_replies(
+            &ctx,
+            event.id,
+            &tracked,
+            verses.clone(),
+            &self.bible,
+            &settings,
+        )
+        .await;
+
+        match self.replies.lock() {
+            Ok(mut tracker) => {
+                if replies.is_empty() && passages.is_empty() {
+                    tracker.remove(&event.id);
+                } else {
+                    tracker.track(
+                        event.id,
+                        TrackedReply {
+                            verses,
+                            replies,
+                            passages,
+                            ..tracked
+                        },
+                    );
+                }
+            }
+            Err(e) => nay!("Failed to lock reply tracker: {}", e),
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        // if one of the bot's replies was deleted, stop tracking it
+        match self.replies.lock() {
+            Ok(mut tracker) => {
+                if let Some(source) = tracker.find_source(&deleted_message_id) {
+                    tracker.forget_reply(&source, &deleted_message_id);
+                }
+            }
+            Err(e) => nay!("Failed to lock reply tracker: {}", e),
+        }
+
+        self.remove_replies(&ctx, deleted_message_id).await;
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        for id in multiple_deleted_messages_ids {
+            self.message_delete(ctx.clone(), channel_id, id, guild_id)
+                .await;
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let ReactionType::Unicode(emoji) = &reaction.emoji else {
+            return;
+        };
+        // some clients send the emoji with a variation selector
+        if emoji.trim_end_matches('\u{fe0f}') != DISMISS_EMOJI {
+            return;
+        }
+        let Some(user) = reaction.user_id else {
+            return;
+        };
+
+        // only the author of the original message can dismiss the reply
+        let source = match self.replies.lock() {
+            Ok(tracker) => tracker
+                .find_source(&reaction.message_id)
+                .filter(|source| tracker.get(source).is_some_and(|t| t.author == user)),
+            Err(e) => {
+                nay!("Failed to lock reply tracker: {}", e);
+                return;
+            }
+        };
+
+        if let Some(source) = source {
+            self.remove_replies(&ctx, source).await;
         }
     }
 
@@ -68,6 +425,25 @@ impl EventHandler for Handler {
         register_command(&ctx, commands::chapter::register()).await;
         register_command(&ctx, commands::register_channel::register()).await;
         register_command(&ctx, commands::reading_calc::register()).await;
+        register_command(&ctx, commands::detection_settings::register()).await;
+        register_command(&ctx, commands::book_alias::register()).await;
+        register_command(&ctx, commands::passage::register()).await;
+        register_command(&ctx, commands::book_info::register()).await;
+        register_command(&ctx, commands::stats::register()).await;
+        register_command(&ctx, commands::xref::register()).await;
+        register_command(&ctx, commands::topic::register()).await;
+        register_command(&ctx, commands::define::register()).await;
+        register_command(&ctx, commands::commentary::register()).await;
+        register_command(&ctx, commands::word::register()).await;
+        register_command(&ctx, commands::strongs::register()).await;
+        register_command(&ctx, commands::bookmark::register()).await;
+        register_command(&ctx, commands::collection::register()).await;
+        register_command(&ctx, commands::quiz::register()).await;
+        register_command(&ctx, commands::leaderboard::register()).await;
+        register_command(&ctx, commands::memory::register()).await;
+        register_command(&ctx, commands::prayer::register()).await;
+        register_command(&ctx, commands::verse_image::register()).await;
+        register_command(&ctx, commands::export::register()).await;
 
         yay!("{} is connected!", ready.user.name);
 
@@ -80,6 +456,8 @@ impl EventHandler for Handler {
         let ctx = Arc::new(ctx);
         // bible reference
         let bible = Arc::clone(&self.bible);
+        // aliases reference (for collections used as the daily verse and memory verses)
+        let aliases = Arc::clone(&self.aliases);
         // running reference
         //let subprocess_running = Arc::clone(&self.subprocess_running);
 
@@ -87,6 +465,18 @@ impl EventHandler for Handler {
             // store that we are running
             self.subprocess_running.store(true, Ordering::Relaxed);
 
+            // memory verse reviews are checked far more often than the daily messages
+            // due times are kept in the user files, so nothing is lost between restarts
+            let review_ctx = Arc::clone(&ctx);
+            let review_bible = Arc::clone(&bible);
+            let review_aliases = Arc::clone(&aliases);
+            tokio::spawn(async move {
+                loop {
+                    send_due_reviews(&review_ctx, &review_bible, &review_aliases).await;
+                    tokio::time::sleep(CHECK_INTERVAL).await;
+                }
+            });
+
             tokio::spawn(async move {
                 // main loop, run until subprocess_running is false
                 loop {
@@ -103,7 +493,7 @@ impl EventHandler for Handler {
                     // update the daily verse
                     verse_handler.set_new_verse(&bible);
                     let mut daily_verse = verse_handler.get_verse();
-                    spam_daily_verse(&ctx, &daily_verse, &bible, &guilds).await;
+                    spam_daily_verse(&ctx, &daily_verse, &bible, &aliases, &guilds).await;
 
                     // refresh in case spam_daily_verse modified it
                     verse_handler.refresh(&bible);
@@ -121,6 +511,9 @@ impl EventHandler for Handler {
                     // reading schedule
                     spam_reading_schedule(&ctx, &guilds, reading, &bible).await;
 
+                    // close prayer requests past their guild's expiry
+                    commands::prayer::close_expired_requests(&ctx, &guilds).await;
+
                     // wait until the next 7am
                     let Some(wait_duration) = get_time_until_7am() else {
                         nay!("Failed to get duration until 7am, skipping this iteration!");
@@ -151,19 +544,38 @@ impl EventHandler for Handler {
                 let guild = command.guild_id;
                 //let guild_id = guild.unwrap();
                 let command_options = &command.data.options();
-                let channel = command.channel_id;
 
                 match command_name {
                     "random_verse" => {
-                        commands::random_verse::run(&ctx, &command, &self.bible).await;
+                        let aliases = self.guild_aliases(guild);
+                        commands::random_verse::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
                     }
                     "chapter" => {
+                        let aliases = self.guild_aliases(guild);
                         commands::chapter::run(
                             command_options,
                             &ctx,
                             &command,
-                            &channel,
                             &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "passage" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::passage::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.translations,
+                            &aliases,
                         )
                         .await;
                     }
@@ -180,11 +592,387 @@ impl EventHandler for Handler {
                         )
                         .await;
                     }
+                    "book_info" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::book_info::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "stats" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::stats::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.statistics,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "xref" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::xref::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.cross_references,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "topic" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::topic::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.topics,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "define" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::define::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.dictionary,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "commentary" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::commentary::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.commentaries,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "word" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::word::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.strongs,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "strongs" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::strongs::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.strongs,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "bookmark" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::bookmark::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "collection" => {
+                        let Some(guild) = guild else {
+                            command_response(&ctx, &command, "Collections only work in servers.")
+                                .await;
+                            return;
+                        };
+                        let aliases = self.guild_aliases(Some(guild));
+                        commands::collection::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &guild,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "quiz" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::quiz::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.quizzes,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "leaderboard" => {
+                        let Some(guild) = guild else {
+                            command_response(&ctx, &command, "Leaderboards only work in servers.")
+                                .await;
+                            return;
+                        };
+                        commands::leaderboard::run(&ctx, &command, &guild).await;
+                    }
+                    "memory" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::memory::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "prayer" => {
+                        let Some(guild) = guild else {
+                            command_response(
+                                &ctx,
+                                &command,
+                                "Prayer requests only work in servers.",
+                            )
+                            .await;
+                            return;
+                        };
+                        commands::prayer::run(command_options, &ctx, &command, &guild).await;
+                    }
+                    "verse_image" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::verse_image::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "export" => {
+                        let aliases = self.guild_aliases(guild);
+                        commands::export::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &self.bible,
+                            &aliases,
+                        )
+                        .await;
+                    }
+                    "book_alias" => {
+                        let guild = guild.unwrap();
+                        commands::book_alias::run(
+                            command_options,
+                            &ctx,
+                            &command,
+                            &guild,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                        self.forget_guild_aliases(guild);
+                    }
+                    "detection_settings" => {
+                        let guild = guild.unwrap();
+                        commands::detection_settings::run(command_options, &ctx, &command, &guild)
+                            .await;
+                        self.forget_detection_settings(guild);
+                    }
                     _ => {
                         command_response(&ctx, &command, "Unknown command!").await;
                     }
                 }
             }
+            Interaction::Autocomplete(autocomplete) => {
+                let aliases = self.guild_aliases(autocomplete.guild_id);
+                commands::autocomplete::run(
+                    &ctx,
+                    &autocomplete,
+                    &self.bible,
+                    &aliases,
+                    &self.topics,
+                    &self.dictionary,
+                )
+                .await;
+            }
+            Interaction::Component(component) => {
+                // button ids are formatted as `kind:args`
+                let custom_id = component.data.custom_id.as_str();
+                let (kind, args) = custom_id.split_once(':').unwrap_or((custom_id, ""));
+
+                match kind {
+                    "book_info" => {
+                        commands::book_info::run_button(args, &ctx, &component, &self.bible).await;
+                    }
+                    "bookmark_page" => {
+                        commands::bookmark::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "chapter" => {
+                        commands::chapter::run_button(args, &ctx, &component, &self.bible).await;
+                    }
+                    "chapter_page" => {
+                        commands::chapter::run_page_button(args, &ctx, &component, &self.bible)
+                            .await;
+                    }
+                    "collection_page" => {
+                        commands::collection::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "commentary" => {
+                        commands::commentary::run_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.commentaries,
+                            &self.translations,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "commentary_page" => {
+                        commands::commentary::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.commentaries,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "define_page" => {
+                        commands::define::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.dictionary,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "memory" => {
+                        commands::memory::run_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "nav" => {
+                        commands::navigation::run_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.translations,
+                        )
+                        .await;
+                    }
+                    "open_verse" => {
+                        commands::run_open_verse_button(args, &ctx, &component, &self.translations)
+                            .await;
+                    }
+                    "passage_page" => {
+                        delivery::run_page_button(args, &ctx, &component, &self.translations).await;
+                    }
+                    "praying" => {
+                        commands::prayer::run_praying_button(args, &ctx, &component).await;
+                    }
+                    "quiz" => {
+                        commands::quiz::run_button(args, &ctx, &component, &self.quizzes).await;
+                    }
+                    "save" => {
+                        commands::bookmark::run_save_button(args, &ctx, &component, &self.aliases)
+                            .await;
+                    }
+                    "strongs" => {
+                        commands::strongs::run_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.strongs,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "strongs_page" => {
+                        commands::strongs::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.strongs,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "topic_page" => {
+                        commands::topic::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.topics,
+                            &self.bible,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    "verse" => {
+                        commands::run_verse_button(args, &ctx, &component, &self.translations)
+                            .await;
+                    }
+                    "word_page" => {
+                        commands::word::run_page_button(
+                            args,
+                            &ctx,
+                            &component,
+                            &self.strongs,
+                            &self.aliases,
+                        )
+                        .await;
+                    }
+                    _ => {
+                        nay!("Unknown button: {}", custom_id);
+                    }
+                }
+            }
             _ => {}
         }
     }
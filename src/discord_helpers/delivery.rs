@@ -0,0 +1,507 @@
+/*
+ * Passages too long for a single embed, like whole chapters or long ranges.
+ * They are split into pages between verses, then sent the way the guild has chosen (`DeliveryPolicy`),
+ * the same for commands, buttons and references detected in messages:
+ * - paginate: one message with page buttons,
+ *   `passage_page:{translation}:{book}:{chapter}:{verse}[:{thru_verse}]:{page}`
+ * - split: a message for each page
+ * - thread: a message that starts a thread, with the pages inside it
+ * - file: a message with the passage attached as a text file
+ * Threads can't be started from DMs or messages only one user sees, so those are paginated instead.
+ * Replies to messages remember their thread (`SentPassage`), so it can be deleted with the replies.
+ */
+
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ChannelId, Colour, CommandInteraction, ComponentInteraction, Context, CreateActionRow,
+    CreateAttachment, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+    CreateThread, MessageId,
+};
+
+use crate::{
+    discord_helpers::{
+        attachment_name,
+        multipage_embed::{page_buttons, parse_page_button},
+        verse_navigation::{parse_translated_lookup_id, translated_lookup_id},
+        MAX_VERSE_EMBED_CHARS,
+    },
+    export::{export, ExportFormat, ExportPassage},
+    guildfile::DeliveryPolicy,
+    nay,
+    translations::Translations,
+};
+
+// the most text on one page, leaving room under discord's 4096 character limit for embeds
+pub const MAX_PAGE_CHARS: usize = 4000;
+
+/// Whether a passage is too long to be shown with other verses, and should be delivered on its own
+pub fn is_long_passage(lookup: &BibleLookup, bible: &Bible) -> bool {
+    bible
+        .get_verse(lookup.clone(), true)
+        .is_ok_and(|text| text.len() > MAX_VERSE_EMBED_CHARS)
+}
+
+/// A passage split into pages between verses
+pub struct PassagePages {
+    pub lookup: BibleLookup,
+    /// the reference, or just the book and chapter for a whole chapter (i.e. `Psalm 119`)
+    pub title: String,
+    pub pages: Vec<String>,
+}
+
+impl PassagePages {
+    /// Returns None if any verse of the passage can't be found
+    pub fn new(lookup: &BibleLookup, bible: &Bible) -> Option<Self> {
+        let last = lookup.thru_verse.unwrap_or(lookup.verse).max(lookup.verse);
+        let max_verse = bible.get_max_verse(&lookup.book, lookup.chapter).ok()?;
+        let title = if lookup.verse == 1 && last == max_verse {
+            format!(
+                "{} {}",
+                BibleLookup::capitalize_book(&lookup.book),
+                lookup.chapter
+            )
+        } else {
+            lookup.to_string()
+        };
+
+        let mut pages = Vec::new();
+        let mut page = String::new();
+        for verse in lookup.verse..=last {
+            let single = BibleLookup::new(&lookup.book, lookup.chapter, verse);
+            let text = bible.get_verse(single, true).ok()?;
+            let text = text.trim();
+            if !page.is_empty() && page.len() + 1 + text.len() > MAX_PAGE_CHARS {
+                pages.push(std::mem::take(&mut page));
+            }
+            if !page.is_empty() {
+                page.push(' ');
+            }
+            page.push_str(text);
+        }
+        pages.push(page);
+
+        Some(Self {
+            lookup: lookup.clone(),
+            title,
+            pages,
+        })
+    }
+
+    /// A whole chapter, or None if it doesn't exist
+    pub fn chapter(book: &str, chapter: u32, bible: &Bible) -> Option<Self> {
+        let max_verse = bible.get_max_verse(book, chapter).ok()?;
+        let lookup = if max_verse > 1 {
+            BibleLookup::new_range(book, chapter, 1, max_verse)
+        } else {
+            BibleLookup::new(book, chapter, 1)
+        };
+        Self::new(&lookup, bible)
+    }
+
+    /// The embed for a page (starting at 0), numbered if there is more than one
+    pub fn embed(&self, page: usize, bible: &Bible) -> CreateEmbed {
+        let count = self.pages.len();
+        let title = if count > 1 {
+            format!("📖 {} ({}/{})", self.title, page + 1, count)
+        } else {
+            format!("📖 {}", self.title)
+        };
+        CreateEmbed::new()
+            .title(title)
+            .description(self.pages.get(page).cloned().unwrap_or_default())
+            .color(Colour::GOLD)
+            .footer(CreateEmbedFooter::new(format!(
+                "From the {} Bible.",
+                bible.get_translation()
+            )))
+    }
+
+    /// Every page as an embed
+    pub fn embeds(&self, bible: &Bible) -> Vec<CreateEmbed> {
+        (0..self.pages.len())
+            .map(|page| self.embed(page, bible))
+            .collect()
+    }
+
+    /// The passage as a text file, for the `file` policy
+    fn attachment(&self, bible: &Bible) -> Option<CreateAttachment> {
+        let mut passage = ExportPassage::from_lookup(&self.lookup, bible)?;
+        passage.title = self.title.clone();
+        let translation = bible.get_translation().to_string();
+        let data = export(&[passage], &translation, ExportFormat::Text)?;
+        Some(CreateAttachment::bytes(
+            data,
+            attachment_name(&self.title, ExportFormat::Text.extension()),
+        ))
+    }
+}
+
+/// The id of the page buttons for a passage in a bible's translation
+/// (the page number is added by `page_buttons`)
+pub fn passage_page_id(lookup: &BibleLookup, bible: &Bible) -> String {
+    format!("passage_page:{}", translated_lookup_id(lookup, bible))
+}
+
+/// A single page of a passage with buttons to move between pages
+/// Returns None if the passage doesn't exist
+pub fn craft_passage_page(
+    lookup: &BibleLookup,
+    page: usize,
+    bible: &Bible,
+) -> Option<(CreateEmbed, Vec<CreateActionRow>)> {
+    let pages = PassagePages::new(lookup, bible)?;
+    let count = pages.pages.len();
+    let page = page.min(count.saturating_sub(1));
+    Some((
+        pages.embed(page, bible),
+        page_buttons(&passage_page_id(lookup, bible), page, count),
+    ))
+}
+
+/// Moves a paginated passage to the page from a `passage_page:` button
+pub async fn run_page_button(
+    args: &str,
+    ctx: &Context,
+    component: &ComponentInteraction,
+    translations: &Translations,
+) {
+    let Some(((translation, lookup), page)) = parse_page_button(args)
+        .and_then(|(lookup, page)| Some((parse_translated_lookup_id(lookup)?, page)))
+    else {
+        nay!("Invalid passage page button: {}", args);
+        return;
+    };
+    let bible = &*translations.get_or_default(translation);
+    let Some((embed, buttons)) = craft_passage_page(&lookup, page, bible) else {
+        nay!("Passage from a button was not found: {}", lookup);
+        return;
+    };
+
+    let data = CreateInteractionResponseMessage::new()
+        .embed(embed)
+        .components(buttons);
+    if let Err(e) = component
+        .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(data))
+        .await
+    {
+        nay!("Failed to respond to button: {}", e);
+    }
+}
+
+/// What is sent for a passage under a policy
+struct Delivery {
+    /// the first message, sent where the passage was asked for
+    embed: CreateEmbed,
+    buttons: Vec<CreateActionRow>,
+    file: Option<CreateAttachment>,
+    /// the pages after the first message
+    rest: Vec<CreateEmbed>,
+    /// the name of the thread to start from the first message, with the rest of the pages in it
+    thread: Option<String>,
+}
+
+impl Delivery {
+    fn new(pages: &PassagePages, policy: DeliveryPolicy, bible: &Bible) -> Self {
+        let mut embeds = pages.embeds(bible);
+        let first = embeds.remove(0);
+        let notice = |description: &str| {
+            CreateEmbed::new()
+                .title(format!("📖 {}", pages.title))
+                .description(description)
+                .color(Colour::GOLD)
+                .footer(CreateEmbedFooter::new(format!(
+                    "From the {} Bible.",
+                    bible.get_translation()
+                )))
+        };
+        let delivery = Self {
+            embed: first,
+            buttons: Vec::new(),
+            file: None,
+            rest: Vec::new(),
+            thread: None,
+        };
+
+        // passages that fit on one page are sent as they are
+        if embeds.is_empty() {
+            return delivery;
+        }
+        match policy {
+            DeliveryPolicy::Paginate => Self {
+                buttons: page_buttons(&passage_page_id(&pages.lookup, bible), 0, embeds.len() + 1),
+                ..delivery
+            },
+            DeliveryPolicy::Split => Self {
+                rest: embeds,
+                ..delivery
+            },
+            DeliveryPolicy::Thread => {
+                let mut rest = vec![delivery.embed];
+                rest.extend(embeds);
+                Self {
+                    embed: notice(
+                        "This passage is too long for one message, so it is in the thread below.",
+                    ),
+                    buttons: Vec::new(),
+                    file: None,
+                    rest,
+                    thread: Some(pages.title.clone()),
+                }
+            }
+            DeliveryPolicy::File => match pages.attachment(bible) {
+                Some(file) => Self {
+                    embed: notice(
+                        "This passage is too long for one message, so it is attached as a file.",
+                    ),
+                    file: Some(file),
+                    ..delivery
+                },
+                // a passage that can't be written out is still worth paginating
+                None => Self::new(pages, DeliveryPolicy::Paginate, bible),
+            },
+        }
+    }
+}
+
+/// Starts a thread from a message and sends the pages into it
+/// Returns the thread, or None if it couldn't be started
+async fn send_to_thread(
+    ctx: &Context,
+    channel: ChannelId,
+    message: MessageId,
+    name: &str,
+    pages: Vec<CreateEmbed>,
+) -> Option<ChannelId> {
+    // thread names can be at most 100 characters
+    let name: String = name.chars().take(100).collect();
+    let thread = match channel
+        .create_thread_from_message(&ctx.http, message, CreateThread::new(name))
+        .await
+    {
+        Ok(thread) => thread,
+        Err(e) => {
+            nay!("Failed to start a thread for a passage: {}", e);
+            return None;
+        }
+    };
+    for page in pages {
+        if let Err(e) = thread
+            .id
+            .send_message(&ctx.http, CreateMessage::new().embed(page))
+            .await
+        {
+            nay!("Failed to send message: {}", e);
+        }
+    }
+    Some(thread.id)
+}
+
+/// Sends a long passage in answer to a command: as its response, or as a follow-up if it has one
+pub async fn respond_with_passage(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    pages: &PassagePages,
+    policy: DeliveryPolicy,
+    bible: &Bible,
+    followup: bool,
+    ephemeral: bool,
+) {
+    let policy = if policy == DeliveryPolicy::Thread && (ephemeral || cmd.guild_id.is_none()) {
+        DeliveryPolicy::Paginate
+    } else {
+        policy
+    };
+    let delivery = Delivery::new(pages, policy, bible);
+
+    let message = if followup {
+        let mut builder = CreateInteractionResponseFollowup::new()
+            .embed(delivery.embed)
+            .components(delivery.buttons)
+            .ephemeral(ephemeral);
+        if let Some(file) = delivery.file {
+            builder = builder.add_file(file);
+        }
+        cmd.create_followup(&ctx.http, builder).await
+    } else {
+        let mut data = CreateInteractionResponseMessage::new()
+            .embed(delivery.embed)
+            .components(delivery.buttons)
+            .ephemeral(ephemeral);
+        if let Some(file) = delivery.file {
+            data = data.add_file(file);
+        }
+        match cmd
+            .create_response(&ctx.http, CreateInteractionResponse::Message(data))
+            .await
+        {
+            // the message is only needed to start a thread from
+            Ok(()) if delivery.thread.is_some() => cmd.get_response(&ctx.http).await,
+            Ok(()) => return send_followups(ctx, cmd, delivery.rest, ephemeral).await,
+            Err(e) => Err(e),
+        }
+    };
+    let message = match message {
+        Ok(message) => message,
+        Err(e) => {
+            nay!("Failed to send passage: {}", e);
+            return;
+        }
+    };
+
+    match delivery.thread {
+        Some(name) => {
+            let pages = delivery.rest.clone();
+            let thread = send_to_thread(ctx, message.channel_id, message.id, &name, pages).await;
+            if thread.is_none() {
+                send_followups(ctx, cmd, delivery.rest, ephemeral).await;
+            }
+        }
+        None => send_followups(ctx, cmd, delivery.rest, ephemeral).await,
+    }
+}
+
+/// Answers a button with a long passage, replacing the button's message with it if `replace`,
+/// or else in messages only whoever pressed it sees
+pub async fn respond_to_button_with_passage(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    pages: &PassagePages,
+    policy: DeliveryPolicy,
+    bible: &Bible,
+    replace: bool,
+) {
+    let policy = if policy == DeliveryPolicy::Thread && (!replace || component.guild_id.is_none()) {
+        DeliveryPolicy::Paginate
+    } else {
+        policy
+    };
+    let delivery = Delivery::new(pages, policy, bible);
+
+    let mut data = CreateInteractionResponseMessage::new()
+        .embed(delivery.embed)
+        .components(delivery.buttons);
+    if let Some(file) = delivery.file {
+        data = data.add_file(file);
+    }
+    let response = if replace {
+        CreateInteractionResponse::UpdateMessage(data)
+    } else {
+        CreateInteractionResponse::Message(data.ephemeral(true))
+    };
+    if let Err(e) = component.create_response(&ctx.http, response).await {
+        nay!("Failed to respond to button: {}", e);
+        return;
+    }
+
+    if let Some(name) = &delivery.thread {
+        let message = &component.message;
+        let pages = delivery.rest.clone();
+        if send_to_thread(ctx, message.channel_id, message.id, name, pages)
+            .await
+            .is_some()
+        {
+            return;
+        }
+    }
+    for page in delivery.rest {
+        let builder = CreateInteractionResponseFollowup::new()
+            .embed(page)
+            .ephemeral(!replace);
+        if let Err(e) = component.create_followup(&ctx.http, builder).await {
+            nay!("Failed to send message: {}", e);
+        }
+    }
+}
+
+async fn send_followups(
+    ctx: &Context,
+    cmd: &CommandInteraction,
+    pages: Vec<CreateEmbed>,
+    ephemeral: bool,
+) {
+    for page in pages {
+        let builder = CreateInteractionResponseFollowup::new()
+            .embed(page)
+            .ephemeral(ephemeral);
+        if let Err(e) = cmd.create_followup(&ctx.http, builder).await {
+            nay!("Failed to send message: {}", e);
+        }
+    }
+}
+
+/// The messages sent for a long passage in reply to a message
+#[derive(Clone)]
+pub struct SentPassage {
+    pub lookup: BibleLookup,
+    /// the replies in the channel, starting with the first page
+    pub replies: Vec<MessageId>,
+    /// the thread the pages were sent into, if one was started
+    pub thread: Option<ChannelId>,
+}
+
+/// Replies to a message with a long passage
+/// Returns None if nothing could be sent
+pub async fn reply_with_passage(
+    ctx: &Context,
+    channel: ChannelId,
+    source: MessageId,
+    pages: &PassagePages,
+    policy: DeliveryPolicy,
+    bible: &Bible,
+) -> Option<SentPassage> {
+    let delivery = Delivery::new(pages, policy, bible);
+
+    let mut builder = CreateMessage::new()
+        .embed(delivery.embed)
+        .components(delivery.buttons)
+        .reference_message((channel, source));
+    if let Some(file) = delivery.file {
+        builder = builder.add_file(file);
+    }
+    let first = match channel.send_message(&ctx.http, builder).await {
+        Ok(reply) => reply.id,
+        Err(e) => {
+            nay!("Failed to send message: {}", e);
+            return None;
+        }
+    };
+
+    let mut sent = SentPassage {
+        lookup: pages.lookup.clone(),
+        replies: vec![first],
+        thread: None,
+    };
+    if let Some(name) = &delivery.thread {
+        let pages = delivery.rest.clone();
+        sent.thread = send_to_thread(ctx, channel, first, name, pages).await;
+        if sent.thread.is_some() {
+            return Some(sent);
+        }
+    }
+    // the rest of the pages follow in the channel
+    for page in delivery.rest {
+        match channel
+            .send_message(&ctx.http, CreateMessage::new().embed(page))
+            .await
+        {
+            Ok(reply) => sent.replies.push(reply.id),
+            Err(e) => nay!("Failed to send message: {}", e),
+        }
+    }
+    Some(sent)
+}
+
+/// Deletes the replies sent for a passage, and the thread its pages were sent into
+pub async fn delete_passage(ctx: &Context, channel: ChannelId, passage: &SentPassage) {
+    for reply in &passage.replies {
+        if let Err(e) = channel.delete_message(&ctx.http, *reply).await {
+            nay!("Failed to delete message: {}", e);
+        }
+    }
+    if let Some(thread) = passage.thread {
+        if let Err(e) = thread.delete(&ctx.http).await {
+            nay!("Failed to delete thread: {}", e);
+        }
+    }
+}
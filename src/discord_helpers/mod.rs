@@ -1,13 +1,29 @@
 use bible_lib::{Bible, BibleLookup};
 use serenity::all::{
-    Colour, Command, CommandInteraction, CreateCommand, CreateEmbed, CreateEmbedFooter,
+    ButtonStyle, Colour, Command, CommandInteraction, ComponentInteraction, CreateActionRow,
+    CreateButton, CreateCommand, CreateEmbed, CreateEmbedFooter,
 };
 use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage};
 use serenity::client::Context;
 
-use crate::nay;
+use crate::{
+    books::metadata::{book_info, canonical_position, BOOKS},
+    discord_helpers::verse_navigation::verse_navigation_buttons,
+    nay,
+};
 
+pub mod delivery;
 pub mod multipage_embed;
+pub mod verse_navigation;
+
+// discord limits for a single message
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+pub const MAX_EMBED_CHARS_PER_MESSAGE: usize = 6000;
+pub const MAX_BUTTON_ROWS: usize = 5;
+// rough allowance for the title and footer of a verse embed
+pub const EMBED_OVERHEAD: usize = 100;
+// passages longer than this are delivered on their own (see `delivery`)
+pub const MAX_VERSE_EMBED_CHARS: usize = 2048;
 
 pub fn craft_bible_verse_embed(verse: BibleLookup, bible: &Bible) -> Option<CreateEmbed> {
     if let Ok(max_verse) = bible.get_max_verse(&verse.book, verse.chapter) {
@@ -27,11 +43,14 @@ pub fn craft_bible_verse_embed(verse: BibleLookup, bible: &Bible) -> Option<Crea
     }
 
     if let Ok(verse_text) = bible.get_verse(verse.clone(), true) {
-        if verse_text.len() > 2048 {
+        if verse_text.len() > MAX_VERSE_EMBED_CHARS {
             return Some(
                 CreateEmbed::new()
                     .title(format!("📖 {}", verse))
-                    .description("I am sorry but that would be too long for a message!")
+                    .description(format!(
+                        "I am sorry but that would be too long for a message! Use `/export {}` to get it as a Markdown, plain text or PDF file instead.",
+                        verse
+                    ))
                     .color(Colour::GOLD)
                     .footer(CreateEmbedFooter::new(
                         "Tip: use `/chapter <book> <chapter>` to show a full chapter".to_string(),
@@ -39,7 +58,7 @@ pub fn craft_bible_verse_embed(verse: BibleLookup, bible: &Bible) -> Option<Crea
             );
         }
 
-        if let Some(thru_verse) = verse.thru_verse.clone() {
+        if let Some(thru_verse) = verse.thru_verse {
             if thru_verse > bible.get_max_verse(&verse.book, verse.chapter).unwrap_or(0) {
                 return Some(CreateEmbed::new()
                         .title(format!("📖 {}", verse))
@@ -64,6 +83,167 @@ pub fn craft_bible_verse_embed(verse: BibleLookup, bible: &Bible) -> Option<Crea
     }
 }
 
+/// An overview of a book: what it's about, where it sits in the canon and how long it is
+/// Returns None if the book doesn't exist
+pub fn craft_book_overview_embed(book: &str, bible: &Bible) -> Option<CreateEmbed> {
+    let chapters = bible.get_chapters(book).ok()?;
+    let verses: u32 = chapters
+        .iter()
+        .filter_map(|chapter| bible.get_max_verse(book, *chapter).ok())
+        .sum();
+
+    let mut embed = CreateEmbed::new()
+        .title(format!(
+            "📖 {}",
+            BibleLookup::capitalize_book(&book.to_string())
+        ))
+        .color(Colour::GOLD)
+        .footer(CreateEmbedFooter::new(format!(
+            "From the {} Bible.",
+            bible.get_translation()
+        )));
+    if let Some(info) = book_info(book) {
+        embed = embed.description(info.summary).fields([
+            ("Testament", info.testament.to_string(), true),
+            ("Genre", info.section.to_string(), true),
+            (
+                "Book",
+                format!(
+                    "{} of {}",
+                    canonical_position(book).unwrap_or_default(),
+                    BOOKS.len()
+                ),
+                true,
+            ),
+            ("Traditional Author", info.author.to_string(), true),
+            ("Written", info.date.to_string(), true),
+        ]);
+    }
+
+    Some(
+        embed
+            .field("Chapters", chapters.len().to_string(), true)
+            .field("Verses", verses.to_string(), true),
+    )
+}
+
+/// The embeds for a single message and the buttons under them
+pub struct EmbedGroup {
+    pub embeds: Vec<CreateEmbed>,
+    pub buttons: Vec<CreateActionRow>,
+}
+
+impl EmbedGroup {
+    /// A group with no buttons
+    pub fn new(embeds: Vec<CreateEmbed>) -> Self {
+        Self {
+            embeds,
+            buttons: Vec::new(),
+        }
+    }
+}
+
+/// The plain text of a verse or range, with a space between each verse
+/// Returns None if any verse of it can't be found
+pub fn passage_text(lookup: &BibleLookup, bible: &Bible) -> Option<String> {
+    // ranges from the bible run verses together, so they are looked up one at a time
+    let last = lookup.thru_verse.unwrap_or(lookup.verse).max(lookup.verse);
+    let verses = (lookup.verse..=last)
+        .map(|verse| {
+            let single = BibleLookup::new(&lookup.book, lookup.chapter, verse);
+            bible
+                .get_verse(single, false)
+                .ok()
+                .map(|text| text.trim().to_string())
+        })
+        .collect::<Option<Vec<String>>>()?;
+    Some(verses.join(" "))
+}
+
+/// A file name for an attachment about a passage, i.e. `John_3_16.png`
+pub fn attachment_name(title: &str, extension: &str) -> String {
+    let name = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_");
+    format!("{}.{}", name, extension)
+}
+
+/// The start of a verse's text as a quote, cut at a word if it's longer than `max_chars`
+pub fn verse_preview(lookup: &BibleLookup, bible: &Bible, max_chars: usize) -> String {
+    let text = bible.get_verse(lookup.clone(), false).unwrap_or_default();
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return format!("> {}", text);
+    }
+
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = cut.rsplit_once(' ').map(|(start, _)| start).unwrap_or(&cut);
+    format!("> {}…", cut)
+}
+
+/// Groups verse embeds into messages, staying within discord's embed limits
+/// Each verse gets a row of navigation buttons, so a message holds at most 5 verses
+/// (or 2 with `commentary`, which adds a second row with a "Commentary" button to each verse)
+/// References that can't be found are skipped
+pub fn group_verse_embeds(
+    lookups: Vec<BibleLookup>,
+    bible: &Bible,
+    per_message: usize,
+    commentary: bool,
+) -> Vec<EmbedGroup> {
+    let rows_per_verse = if commentary { 2 } else { 1 };
+    let per_message = per_message.clamp(
+        1,
+        MAX_EMBEDS_PER_MESSAGE.min(MAX_BUTTON_ROWS / rows_per_verse),
+    );
+
+    let mut groups = Vec::new();
+    let mut current = EmbedGroup::new(Vec::new());
+    let mut current_len = 0;
+
+    for lookup in lookups {
+        let Some(embed) = craft_bible_verse_embed(lookup.clone(), bible) else {
+            continue;
+        };
+        // verses over MAX_VERSE_EMBED_CHARS are replaced with a short notice by craft_bible_verse_embed
+        let text = bible.get_verse(lookup.clone(), true);
+        let len = text
+            .as_ref()
+            .map(|text| text.len())
+            .unwrap_or(0)
+            .min(MAX_VERSE_EMBED_CHARS)
+            + EMBED_OVERHEAD;
+
+        if !current.embeds.is_empty()
+            && (current.embeds.len() >= per_message
+                || current_len + len > MAX_EMBED_CHARS_PER_MESSAGE)
+        {
+            groups.push(std::mem::replace(&mut current, EmbedGroup::new(Vec::new())));
+            current_len = 0;
+        }
+
+        // only verses that exist can be navigated from
+        if text.is_ok() {
+            current.buttons.extend(verse_navigation_buttons(
+                current.embeds.len(),
+                &lookup,
+                bible,
+                commentary,
+            ));
+        }
+        current.embeds.push(embed);
+        current_len += len;
+    }
+
+    if !current.embeds.is_empty() {
+        groups.push(current);
+    }
+
+    groups
+}
+
 pub async fn command_response<S: Into<String>>(
     ctx: &Context,
     command: &CommandInteraction,
@@ -76,6 +256,73 @@ pub async fn command_response<S: Into<String>>(
     }
 }
 
+/// Responds to a command with a message only the user who ran it can see
+pub async fn ephemeral_response<S: Into<String>>(
+    ctx: &Context,
+    command: &CommandInteraction,
+    msg: S,
+) {
+    let data = CreateInteractionResponseMessage::new()
+        .content(msg.into())
+        .ephemeral(true);
+    let builder = CreateInteractionResponse::Message(data);
+    if let Err(err) = command.create_response(&ctx.http, builder).await {
+        nay!("Failed to respond to command: {}", err)
+    }
+}
+
+/// Responds to a button with a message only the user who pressed it can see
+pub async fn ephemeral_button_response<S: Into<String>>(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    msg: S,
+) {
+    let data = CreateInteractionResponseMessage::new()
+        .content(msg.into())
+        .ephemeral(true);
+    let builder = CreateInteractionResponse::Message(data);
+    if let Err(err) = component.create_response(&ctx.http, builder).await {
+        nay!("Failed to respond to button: {}", err)
+    }
+}
+
+pub async fn command_response_with_buttons<S: Into<String>>(
+    ctx: &Context,
+    command: &CommandInteraction,
+    msg: S,
+    buttons: Vec<CreateActionRow>,
+) {
+    let data = CreateInteractionResponseMessage::new()
+        .content(msg.into())
+        .components(buttons);
+    let builder = CreateInteractionResponse::Message(data);
+    if let Err(err) = command.create_response(&ctx.http, builder).await {
+        nay!("Failed to respond to command: {}", err)
+    }
+}
+
+/// A row of buttons for "did you mean" suggestions, one per suggested book
+/// `custom_id` creates the id of the button for a book
+pub fn suggestion_buttons<F: Fn(&str) -> String>(
+    books: &[String],
+    custom_id: F,
+) -> Vec<CreateActionRow> {
+    if books.is_empty() {
+        return Vec::new();
+    }
+
+    let buttons = books
+        .iter()
+        .map(|book| {
+            CreateButton::new(custom_id(book))
+                .label(BibleLookup::capitalize_book(book))
+                .style(ButtonStyle::Primary)
+        })
+        .collect();
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
 pub async fn register_command(ctx: &Context, cmd: CreateCommand) {
     if let Err(e) = Command::create_global_command(&ctx.http, cmd).await {
         nay!("Failed to register a command: {}", e);
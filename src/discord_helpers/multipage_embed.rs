@@ -1,12 +1,97 @@
 /*
- * This will be a module for creating embeds with multiple traversable pages.
- * Each page will be an embed, and users can navigate through them using buttons.
- * Some issues to overcome before creating this are:
- *  1. Every one of these will require storage of the state and the button interactions.
- *  2. The interaction handlers will be destroyed on restart, and persistance would mean more resources eaten up
+ * Embeds with multiple traversable pages.
+ * Each page is an embed, and users can navigate through them using buttons.
  *
- * To solve these issues, maybe the interaction handlers can be cleaned up after a set time and the
- * enum can be changed to remove the buttons, putting a note in the footer that it is no longer interactive.
+ * Rather than storing the state of every paginated message (which would be lost on restart),
+ * each button's id holds everything needed to rebuild the page it leads to: `{id}:{page}`.
+ * Whatever handles `{id}` crafts that page again and updates the message in place.
  */
 
-// todo
+use serenity::all::{ButtonStyle, CreateActionRow, CreateButton};
+
+/// Previous and next buttons for page `page` (starting at 0) of `count` pages
+/// Returns no buttons if there is only one page
+pub fn page_buttons(id: &str, page: usize, count: usize) -> Vec<CreateActionRow> {
+    if count <= 1 {
+        return Vec::new();
+    }
+
+    // disabled buttons still need ids that are unique within the message
+    let previous = page.saturating_sub(1);
+    let next = (page + 1).min(count - 1);
+    let (previous_id, next_id) = if page == 0 {
+        (0, 1)
+    } else if page + 1 >= count {
+        (previous, page)
+    } else {
+        (previous, next)
+    };
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}:{}", id, previous_id))
+            .label("◀ Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(format!("{}:{}", id, next_id))
+            .label("Next ▶")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= count),
+    ])]
+}
+
+/// Splits the arguments of a page button into the page id and the page number
+pub fn parse_page_button(args: &str) -> Option<(&str, usize)> {
+    let (id, page) = args.rsplit_once(':')?;
+    Some((id, page.parse().ok()?))
+}
+
+/// Splits long text into pages of at most `max_chars` characters
+/// Pages break between paragraphs where possible, then between sentences, then between words
+pub fn split_into_pages(text: &str, max_chars: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut rest = text.trim();
+
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(index, _)| index)
+            .unwrap_or(rest.len());
+        let window = &rest[..limit];
+
+        // don't break so early that the page is mostly empty
+        let earliest = window.len() / 2;
+        let split_at = [
+            window.rfind("\n\n").map(|index| index + 2),
+            window.rfind('\n').map(|index| index + 1),
+            sentence_break(window),
+            window.rfind(' ').map(|index| index + 1),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|index| *index > earliest)
+        .unwrap_or(limit);
+
+        pages.push(rest[..split_at].trim_end().to_string());
+        rest = rest[split_at..].trim_start();
+    }
+    if !rest.is_empty() || pages.is_empty() {
+        pages.push(rest.to_string());
+    }
+
+    pages
+}
+
+/// Where the last sentence in some text ends
+/// A sentence only ends if the next one starts with a capital, so abbreviations like `Num. 20:22` aren't split
+fn sentence_break(text: &str) -> Option<usize> {
+    text.match_indices(". ")
+        .map(|(index, _)| index + 2)
+        .filter(|index| {
+            text[*index..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_uppercase())
+        })
+        .last()
+}
@@ -0,0 +1,204 @@
+/*
+ * Buttons under verse embeds for reading around a verse:
+ * "Show context", "Previous verse", "Next verse" and "Full chapter", plus "Save" to bookmark it
+ * (`save:{book}:{chapter}:{verse}[:{thru_verse}]`).
+ * Guilds that enable it also get a "Commentary" button on a second row,
+ * `commentary:{translation}:{book}:{chapter}:{verse}[:{thru_verse}]`.
+ *
+ * Like the page buttons in `multipage_embed`, nothing is stored; each button's id holds the
+ * reference it leads to and the translation it's read in:
+ * `nav:{embed}:{action}:{translation}:{book}:{chapter}:{verse}[:{thru_verse}]`
+ * where `embed` is the index of the embed in the message the button acts on.
+ * Buttons sent before the translation was added to their ids use the configured translation.
+ */
+
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{ButtonStyle, CreateActionRow, CreateButton};
+
+use crate::translations::translation_code;
+
+// how many verses either side of a reference "Show context" adds
+pub const CONTEXT_VERSES: u32 = 3;
+
+/// A reference as it is written in a button id: `{book}:{chapter}:{verse}[:{thru_verse}]`
+pub fn lookup_id(lookup: &BibleLookup) -> String {
+    match lookup.thru_verse {
+        Some(thru_verse) => format!(
+            "{}:{}:{}:{}",
+            lookup.book, lookup.chapter, lookup.verse, thru_verse
+        ),
+        None => format!("{}:{}:{}", lookup.book, lookup.chapter, lookup.verse),
+    }
+}
+
+/// Read a reference written by `lookup_id`
+pub fn parse_lookup_id(id: &str) -> Option<BibleLookup> {
+    let mut parts = id.split(':');
+    let book = parts.next()?;
+    let numbers = parts
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .ok()?;
+    match numbers.as_slice() {
+        [chapter, verse] => Some(BibleLookup::new(book, *chapter, *verse)),
+        [chapter, verse, thru_verse] => {
+            Some(BibleLookup::new_range(book, *chapter, *verse, *thru_verse))
+        }
+        _ => None,
+    }
+}
+
+/// A reference and the translation it's read in, as written in a button id:
+/// `{translation}:{book}:{chapter}:{verse}[:{thru_verse}]`
+/// The translation is left out for bibles users can't pick
+pub fn translated_lookup_id(lookup: &BibleLookup, bible: &Bible) -> String {
+    match translation_code(bible) {
+        Some(code) => format!("{}:{}", code, lookup_id(lookup)),
+        None => lookup_id(lookup),
+    }
+}
+
+/// Read a reference written by `translated_lookup_id`, along with its translation code
+/// The code is None if the id has no translation
+pub fn parse_translated_lookup_id(id: &str) -> Option<(Option<&str>, BibleLookup)> {
+    if let Some(lookup) = parse_lookup_id(id) {
+        return Some((None, lookup));
+    }
+    let (code, lookup) = id.split_once(':')?;
+    Some((Some(code), parse_lookup_id(lookup)?))
+}
+
+/// The action, translation and reference of a navigation button, from the arguments after `nav:`
+pub fn parse_navigation_button(args: &str) -> Option<(usize, &str, Option<&str>, BibleLookup)> {
+    let mut parts = args.splitn(3, ':');
+    let index = parts.next()?.parse().ok()?;
+    let action = parts.next()?;
+    let (translation, lookup) = parse_translated_lookup_id(parts.next()?)?;
+    Some((index, action, translation, lookup))
+}
+
+fn navigation_button_id(index: usize, action: &str, lookup: &BibleLookup, bible: &Bible) -> String {
+    format!(
+        "nav:{}:{}:{}",
+        index,
+        action,
+        translated_lookup_id(lookup, bible)
+    )
+}
+
+/// The verse before a reference, going back into the previous chapter if needed
+fn previous_verse(lookup: &BibleLookup, bible: &Bible) -> Option<BibleLookup> {
+    if lookup.verse > 1 {
+        return Some(BibleLookup::new(
+            &lookup.book,
+            lookup.chapter,
+            lookup.verse - 1,
+        ));
+    }
+    let chapter = lookup.chapter.checked_sub(1)?;
+    let verse = bible.get_max_verse(&lookup.book, chapter).ok()?;
+    Some(BibleLookup::new(&lookup.book, chapter, verse))
+}
+
+/// The verse after a reference, going on into the next chapter if needed
+fn next_verse(lookup: &BibleLookup, bible: &Bible) -> Option<BibleLookup> {
+    let last = lookup.thru_verse.unwrap_or(lookup.verse);
+    let max_verse = bible.get_max_verse(&lookup.book, lookup.chapter).ok()?;
+    if last < max_verse {
+        return Some(BibleLookup::new(&lookup.book, lookup.chapter, last + 1));
+    }
+    let chapter = lookup.chapter + 1;
+    bible.get_max_verse(&lookup.book, chapter).ok()?;
+    Some(BibleLookup::new(&lookup.book, chapter, 1))
+}
+
+/// The reference with `CONTEXT_VERSES` added on either side, within the same chapter
+fn with_context(lookup: &BibleLookup, bible: &Bible) -> Option<BibleLookup> {
+    let max_verse = bible.get_max_verse(&lookup.book, lookup.chapter).ok()?;
+    let start = lookup.verse.saturating_sub(CONTEXT_VERSES).max(1);
+    let end = (lookup.thru_verse.unwrap_or(lookup.verse) + CONTEXT_VERSES).min(max_verse);
+    let expanded = BibleLookup::new_range(&lookup.book, lookup.chapter, start, end);
+    (expanded != *lookup && end > start).then_some(expanded)
+}
+
+/// The navigation buttons for the verse embed at `index` in a message
+/// Buttons that would lead nowhere (i.e. "Previous verse" on Genesis 1:1) are disabled
+/// `commentary` adds a second row with a button that shows the commentary on the verse
+pub fn verse_navigation_buttons(
+    index: usize,
+    lookup: &BibleLookup,
+    bible: &Bible,
+    commentary: bool,
+) -> Vec<CreateActionRow> {
+    let button = |action: &str, label: &str, target: Option<BibleLookup>| {
+        let disabled = target.is_none();
+        let target = target.unwrap_or_else(|| lookup.clone());
+        CreateButton::new(navigation_button_id(index, action, &target, bible))
+            .label(label)
+            .style(ButtonStyle::Secondary)
+            .disabled(disabled)
+    };
+
+    let mut rows = vec![CreateActionRow::Buttons(vec![
+        button("context", "Show context", with_context(lookup, bible)),
+        button(
+            "previous",
+            "◀ Previous verse",
+            previous_verse(lookup, bible),
+        ),
+        button("next", "Next verse ▶", next_verse(lookup, bible)),
+        button("chapter", "Full chapter", Some(lookup.clone())),
+        CreateButton::new(format!("save:{}", lookup_id(lookup)))
+            .label("Save")
+            .style(ButtonStyle::Secondary),
+    ])];
+    // discord only allows 5 buttons in a row
+    if commentary {
+        rows.push(CreateActionRow::Buttons(vec![CreateButton::new(format!(
+            "commentary:{}",
+            translated_lookup_id(lookup, bible)
+        ))
+        .label("Commentary")
+        .style(ButtonStyle::Secondary)]));
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bible_lib::Translation;
+    use std::sync::OnceLock;
+
+    fn bible() -> &'static Bible {
+        static BIBLE: OnceLock<Bible> = OnceLock::new();
+        BIBLE.get_or_init(|| Bible::new(Translation::KingJames).unwrap())
+    }
+
+    #[test]
+    fn translation_round_trips_through_button_ids() {
+        let lookup = BibleLookup::new_range("1 john", 4, 7, 8);
+        let id = navigation_button_id(2, "next", &lookup, bible());
+        assert_eq!(id, "nav:2:next:kjv:1 john:4:7:8");
+
+        let args = id.strip_prefix("nav:").unwrap();
+        assert_eq!(
+            parse_navigation_button(args),
+            Some((2, "next", Some("kjv"), lookup.clone()))
+        );
+    }
+
+    #[test]
+    fn ids_without_a_translation_still_parse() {
+        assert_eq!(
+            parse_navigation_button("0:previous:genesis:1:2"),
+            Some((0, "previous", None, BibleLookup::new("genesis", 1, 2)))
+        );
+        assert_eq!(
+            parse_translated_lookup_id("psalms:23:1"),
+            Some((None, BibleLookup::new("psalms", 23, 1)))
+        );
+        assert_eq!(parse_translated_lookup_id("kjv:psalms:twenty"), None);
+    }
+}
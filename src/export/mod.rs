@@ -0,0 +1,191 @@
+// Passages as files for `/export`: Markdown, plain text or PDF, with numbered verses and the
+// translation they come from, for handouts and notes.
+
+use bible_lib::{Bible, BibleLookup};
+
+pub mod pdf;
+
+/// A passage ready to be written out, with the text of each verse
+#[derive(Debug, Clone)]
+pub struct ExportPassage {
+    pub title: String,
+    pub verses: Vec<(u32, String)>,
+}
+
+impl ExportPassage {
+    /// A verse or range, or None if any verse of it can't be found
+    pub fn from_lookup(lookup: &BibleLookup, bible: &Bible) -> Option<Self> {
+        let last = lookup.thru_verse.unwrap_or(lookup.verse).max(lookup.verse);
+        Self::from_verses(
+            lookup.to_string(),
+            &lookup.book,
+            lookup.chapter,
+            lookup.verse..=last,
+            bible,
+        )
+    }
+
+    /// A whole chapter, or None if it can't be found
+    pub fn from_chapter(book: &str, chapter: u32, bible: &Bible) -> Option<Self> {
+        let last = bible.get_max_verse(book, chapter).ok()?;
+        let title = format!(
+            "{} {}",
+            BibleLookup::capitalize_book(&book.to_string()),
+            chapter
+        );
+        Self::from_verses(title, book, chapter, 1..=last, bible)
+    }
+
+    fn from_verses(
+        title: String,
+        book: &str,
+        chapter: u32,
+        verses: impl Iterator<Item = u32>,
+        bible: &Bible,
+    ) -> Option<Self> {
+        let verses = verses
+            .map(|verse| {
+                let text = bible
+                    .get_verse(BibleLookup::new(book, chapter, verse), false)
+                    .ok()?;
+                Some((verse, text.trim().to_string()))
+            })
+            .collect::<Option<Vec<(u32, String)>>>()?;
+        Some(Self { title, verses })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "markdown" => Some(ExportFormat::Markdown),
+            "text" => Some(ExportFormat::Text),
+            "pdf" => Some(ExportFormat::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Text => "txt",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Writes passages out as a file, returning None if it couldn't be made
+pub fn export(
+    passages: &[ExportPassage],
+    translation: &str,
+    format: ExportFormat,
+) -> Option<Vec<u8>> {
+    match format {
+        ExportFormat::Markdown => Some(to_markdown(passages, translation).into_bytes()),
+        ExportFormat::Text => Some(to_text(passages, translation).into_bytes()),
+        ExportFormat::Pdf => pdf::render(passages, translation),
+    }
+}
+
+/// Each passage as a heading and a paragraph, with the verse numbers in bold
+fn to_markdown(passages: &[ExportPassage], translation: &str) -> String {
+    let sections = passages
+        .iter()
+        .map(|passage| {
+            let verses = passage
+                .verses
+                .iter()
+                .map(|(verse, text)| format!("**{}** {}", verse, text))
+                .collect::<Vec<String>>();
+            format!("## {}\n\n{}", passage.title, verses.join(" "))
+        })
+        .collect::<Vec<String>>();
+    format!(
+        "{}\n\n*From the {} Bible.*\n",
+        sections.join("\n\n"),
+        translation
+    )
+}
+
+/// Each passage under its title, one verse to a line
+fn to_text(passages: &[ExportPassage], translation: &str) -> String {
+    let sections = passages
+        .iter()
+        .map(|passage| {
+            let verses = passage
+                .verses
+                .iter()
+                .map(|(verse, text)| format!("[{}] {}", verse, text))
+                .collect::<Vec<String>>();
+            format!("{}\n\n{}", passage.title, verses.join("\n"))
+        })
+        .collect::<Vec<String>>();
+    format!(
+        "{}\n\nFrom the {} Bible.\n",
+        sections.join("\n\n\n"),
+        translation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passages() -> Vec<ExportPassage> {
+        vec![
+            ExportPassage {
+                title: "John 11:35".to_string(),
+                verses: vec![(35, "Jesus wept.".to_string())],
+            },
+            ExportPassage {
+                title: "Psalm 23:1-2".to_string(),
+                verses: vec![
+                    (1, "The LORD is my shepherd; I shall not want.".to_string()),
+                    (2, "He maketh me to lie down in green pastures.".to_string()),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            to_markdown(&passages(), "KJV"),
+            "## John 11:35\n\n**35** Jesus wept.\n\n\
+             ## Psalm 23:1-2\n\n**1** The LORD is my shepherd; I shall not want. \
+             **2** He maketh me to lie down in green pastures.\n\n\
+             *From the KJV Bible.*\n"
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            to_text(&passages(), "KJV"),
+            "John 11:35\n\n[35] Jesus wept.\n\n\n\
+             Psalm 23:1-2\n\n[1] The LORD is my shepherd; I shall not want.\n\
+             [2] He maketh me to lie down in green pastures.\n\n\
+             From the KJV Bible.\n"
+        );
+    }
+
+    #[test]
+    fn formats() {
+        for (code, format) in [
+            ("markdown", ExportFormat::Markdown),
+            ("text", ExportFormat::Text),
+            ("pdf", ExportFormat::Pdf),
+        ] {
+            assert_eq!(ExportFormat::from_code(code), Some(format));
+            assert!(export(&passages(), "KJV", format).is_some_and(|file| !file.is_empty()));
+        }
+        assert_eq!(ExportFormat::from_code("docx"), None);
+    }
+}
@@ -0,0 +1,434 @@
+// PDF handouts for `/export`. Pages are laid out here, measured with `ab_glyph`, and written with
+// `printpdf`. DejaVu Serif (the font `/verse_image` uses) is embedded so the file looks the same in
+// every viewer, cut down with `subsetter` to the characters the file uses.
+//
+// Characters DejaVu Serif doesn't have are shown as "?".
+
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontRef, GlyphId};
+use printpdf::{
+    Color, FontData, FontMetrics, GlyphMetrics, Greyscale, IndirectFontRef, Mm, PdfDocument,
+    PdfLayerReference, Pt,
+};
+
+use crate::{export::ExportPassage, verse_image::SERIF};
+
+// US letter, in points
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 72.0;
+const LINE_WIDTH: f32 = PAGE_WIDTH - MARGIN * 2.0;
+const TITLE_SIZE: f32 = 18.0;
+// the space above a title that follows another passage
+const TITLE_GAP: f32 = 24.0;
+const BODY_SIZE: f32 = 12.0;
+const LEADING: f32 = 18.0;
+// verse numbers are small and raised, like in a printed bible
+const NUMBER_SIZE: f32 = 7.0;
+const NUMBER_RISE: f32 = 5.0;
+const NUMBER_GAP: f32 = 1.5;
+const FOOTER_SIZE: f32 = 9.0;
+const FOOTER_BASELINE: f32 = 40.0;
+// the grey of verse numbers and the footer (0 is black, 1 is white)
+const GREY: f32 = 0.45;
+
+/// A word of a paragraph, with the number of the verse it starts
+struct Word {
+    number: Option<u32>,
+    text: String,
+}
+
+/// Some text to draw, with its baseline in points from the bottom left of the page
+struct Span {
+    text: String,
+    x: f32,
+    y: f32,
+    size: f32,
+    rise: f32,
+    grey: f32,
+}
+
+/// Lays passages out onto pages, keeping what is drawn on each
+struct Layout<'a> {
+    font: &'a FontRef<'static>,
+    /// font units per em
+    units_per_em: f32,
+    pages: Vec<Vec<Span>>,
+    /// the baseline of the last line drawn on the current page
+    y: f32,
+}
+
+impl<'a> Layout<'a> {
+    fn new(font: &'a FontRef<'static>, units_per_em: f32) -> Self {
+        Self {
+            font,
+            units_per_em,
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// The text with anything the font can't show swapped for `?`
+    fn printable(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| if self.font.glyph_id(c).0 == 0 { '?' } else { c })
+            .collect()
+    }
+
+    fn width(&self, text: &str, size: f32) -> f32 {
+        let scale = size / self.units_per_em;
+        text.chars()
+            .map(|c| self.font.h_advance_unscaled(self.font.glyph_id(c)) * scale)
+            .sum()
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Moves down by `height`, starting a new page if it wouldn't fit above the bottom margin
+    fn advance(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+        self.y -= height;
+    }
+
+    fn text(&mut self, text: &str, x: f32, y: f32, size: f32, rise: f32, grey: f32) {
+        let text = self.printable(text);
+        let page = self.pages.last_mut().expect("there is always a page");
+        page.push(Span {
+            text,
+            x,
+            y,
+            size,
+            rise,
+            grey,
+        });
+    }
+
+    fn number_width(&self, number: Option<u32>) -> f32 {
+        number.map_or(0.0, |number| {
+            self.width(&number.to_string(), NUMBER_SIZE) + NUMBER_GAP
+        })
+    }
+
+    /// Splits a word too wide for a line of its own into pieces that each fit on one
+    fn split_word(&self, word: Word) -> Vec<Word> {
+        let mut room = LINE_WIDTH - self.number_width(word.number);
+        if self.width(&word.text, BODY_SIZE) <= room {
+            return vec![word];
+        }
+
+        let mut pieces = Vec::new();
+        let mut number = word.number;
+        let mut piece = String::new();
+        for c in word.text.chars() {
+            let candidate = format!("{}{}", piece, c);
+            if !piece.is_empty() && self.width(&candidate, BODY_SIZE) > room {
+                pieces.push(Word {
+                    number: number.take(),
+                    text: std::mem::take(&mut piece),
+                });
+                room = LINE_WIDTH;
+            }
+            piece.push(c);
+        }
+        pieces.push(Word {
+            number,
+            text: piece,
+        });
+        pieces
+    }
+
+    fn passage(&mut self, passage: &ExportPassage, first: bool) {
+        if !first {
+            self.y -= TITLE_GAP;
+        }
+        // a title always has at least a couple of lines under it on the same page
+        if self.y - TITLE_SIZE - LEADING * 2.0 < MARGIN {
+            self.new_page();
+        }
+        self.advance(TITLE_SIZE);
+        self.text(&passage.title, MARGIN, self.y, TITLE_SIZE, 0.0, 0.0);
+        self.y -= TITLE_SIZE * 0.5;
+
+        let words = passage
+            .verses
+            .iter()
+            .flat_map(|(verse, text)| {
+                text.split_whitespace().enumerate().map(|(x, word)| Word {
+                    number: (x == 0).then_some(*verse),
+                    text: self.printable(word),
+                })
+            })
+            .flat_map(|word| self.split_word(word))
+            .collect::<Vec<Word>>();
+
+        let space = self.width(" ", BODY_SIZE);
+        let mut line: Vec<(Word, f32)> = Vec::new();
+        let mut x = 0.0;
+        for word in words {
+            let width = self.number_width(word.number) + self.width(&word.text, BODY_SIZE);
+            let start = if line.is_empty() { 0.0 } else { x + space };
+            if !line.is_empty() && start + width > LINE_WIDTH {
+                self.line(&line);
+                line.clear();
+                x = width;
+                line.push((word, 0.0));
+            } else {
+                x = start + width;
+                line.push((word, start));
+            }
+        }
+        if !line.is_empty() {
+            self.line(&line);
+        }
+    }
+
+    /// Draws a line of words, each at its offset from the left margin
+    fn line(&mut self, words: &[(Word, f32)]) {
+        self.advance(LEADING);
+        for (word, offset) in words {
+            let mut x = MARGIN + offset;
+            if let Some(number) = word.number {
+                let number = number.to_string();
+                self.text(&number, x, self.y, NUMBER_SIZE, NUMBER_RISE, GREY);
+                x += self.width(&number, NUMBER_SIZE) + NUMBER_GAP;
+            }
+            self.text(&word.text, x, self.y, BODY_SIZE, 0.0, 0.0);
+        }
+    }
+
+    /// The translation and page number at the bottom of every page
+    fn footers(&mut self, translation: &str) {
+        let attribution = self.printable(&format!("From the {} Bible.", translation));
+        let count = self.pages.len();
+        for index in 0..count {
+            let number = format!("Page {} of {}", index + 1, count);
+            let right = PAGE_WIDTH - MARGIN - self.width(&number, FOOTER_SIZE);
+            for (text, x) in [(attribution.clone(), MARGIN), (number, right)] {
+                self.pages[index].push(Span {
+                    text,
+                    x,
+                    y: FOOTER_BASELINE,
+                    size: FOOTER_SIZE,
+                    rise: 0.0,
+                    grey: GREY,
+                });
+            }
+        }
+    }
+}
+
+/// The embedded font as printpdf sees it: only the characters the file uses, so the widths and
+/// character map written for it stay small
+#[derive(Debug, Clone)]
+struct UsedGlyphs {
+    font: FontRef<'static>,
+    chars: HashMap<u16, char>,
+}
+
+impl FontData for UsedGlyphs {
+    fn font_metrics(&self) -> FontMetrics {
+        FontMetrics {
+            ascent: self.font.ascent_unscaled() as i16,
+            descent: self.font.descent_unscaled() as i16,
+            units_per_em: self.font.units_per_em().unwrap_or(1000.0) as u16,
+        }
+    }
+
+    fn glyph_id(&self, c: char) -> Option<u16> {
+        Some(self.font.glyph_id(c).0).filter(|glyph| self.chars.contains_key(glyph))
+    }
+
+    fn glyph_ids(&self) -> HashMap<u16, char> {
+        self.chars.clone()
+    }
+
+    fn glyph_count(&self) -> u16 {
+        self.font.glyph_count() as u16
+    }
+
+    fn glyph_metrics(&self, glyph: u16) -> Option<GlyphMetrics> {
+        let glyph = GlyphId(glyph);
+        // the bounds are given top down, so `min.y` is the highest point
+        let height = self.font.outline(glyph).map_or(1000.0, |outline| {
+            outline.bounds.min.y - outline.bounds.max.y - self.font.descent_unscaled()
+        });
+        Some(GlyphMetrics {
+            width: self.font.h_advance_unscaled(glyph) as u32,
+            height: height as u32,
+        })
+    }
+}
+
+/// Draws the spans of a page onto its layer
+fn draw_page(layer: &PdfLayerReference, font: &IndirectFontRef, spans: &[Span]) {
+    for span in spans {
+        layer.begin_text_section();
+        layer.set_font(font, span.size);
+        layer.set_fill_color(Color::Greyscale(Greyscale::new(span.grey, None)));
+        layer.set_line_offset(span.rise);
+        layer.set_text_cursor(Mm::from(Pt(span.x)), Mm::from(Pt(span.y)));
+        layer.write_text(span.text.as_str(), font);
+        layer.end_text_section();
+    }
+}
+
+/// Writes passages as a PDF, returning None if the font couldn't be read or the file written
+pub fn render(passages: &[ExportPassage], translation: &str) -> Option<Vec<u8>> {
+    let font = FontRef::try_from_slice(SERIF).ok()?;
+    let units_per_em = font.units_per_em()?;
+    let mut layout = Layout::new(&font, units_per_em);
+    for (x, passage) in passages.iter().enumerate() {
+        layout.passage(passage, x == 0);
+    }
+    layout.footers(translation);
+
+    // only the outlines of the characters that are used are embedded
+    let chars = layout
+        .pages
+        .iter()
+        .flatten()
+        .flat_map(|span| span.text.chars())
+        .map(|c| (font.glyph_id(c).0, c))
+        .collect::<HashMap<u16, char>>();
+    let mut glyphs = chars.keys().copied().chain([0]).collect::<Vec<u16>>();
+    glyphs.sort_unstable();
+    let subset = subsetter::subset(SERIF, 0, subsetter::Profile::pdf(&glyphs)).ok()?;
+
+    let title = passages
+        .iter()
+        .map(|passage| passage.title.as_str())
+        .collect::<Vec<&str>>()
+        .join("; ");
+    let (width, height) = (Mm::from(Pt(PAGE_WIDTH)), Mm::from(Pt(PAGE_HEIGHT)));
+    let (document, page, layer) = PdfDocument::new(title, width, height, "Text");
+    let pdf_font = document
+        .add_external_font_data(
+            subset,
+            UsedGlyphs {
+                font: font.clone(),
+                chars,
+            },
+        )
+        .ok()?;
+
+    for (index, spans) in layout.pages.iter().enumerate() {
+        let (page, layer) = match index {
+            0 => (page, layer),
+            _ => document.add_page(width, height, "Text"),
+        };
+        draw_page(&document.get_page(page).get_layer(layer), &pdf_font, spans);
+    }
+
+    document.save_to_bytes().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use printpdf::lopdf;
+
+    fn passage(title: &str, verses: u32, text: &str) -> ExportPassage {
+        ExportPassage {
+            title: title.to_string(),
+            verses: (1..=verses)
+                .map(|verse| (verse, text.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn pages_are_counted() {
+        let short = render(&[passage("John 11:35", 1, "Jesus wept.")], "KJV").unwrap();
+        let document = lopdf::Document::load_mem(&short).unwrap();
+        assert_eq!(document.get_pages().len(), 1);
+
+        let verse = "And God said, Let there be light: and there was light. ".repeat(4);
+        let long = render(
+            &[
+                passage("Genesis 1", 60, &verse),
+                passage("Psalm 23", 6, &verse),
+            ],
+            "KJV",
+        )
+        .unwrap();
+        let document = lopdf::Document::load_mem(&long).unwrap();
+        let pages = document.get_pages();
+        assert!(pages.len() > 2);
+        // each page ends with its footer, written in the font's glyph ids
+        let font = FontRef::try_from_slice(SERIF).unwrap();
+        let chars = (' '..='~')
+            .map(|c| (font.glyph_id(c).0, c))
+            .collect::<HashMap<u16, char>>();
+        for (number, id) in &pages {
+            let content = document.get_and_decode_page_content(*id).unwrap();
+            let text = content
+                .operations
+                .iter()
+                .filter(|operation| operation.operator == "Tj")
+                .filter_map(|operation| operation.operands.first()?.as_str().ok())
+                .map(|glyphs| {
+                    glyphs
+                        .chunks(2)
+                        .map(|glyph| chars[&u16::from_be_bytes([glyph[0], glyph[1]])])
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>();
+            assert_eq!(
+                text.last().unwrap(),
+                &format!("Page {} of {}", number, pages.len())
+            );
+        }
+    }
+
+    #[test]
+    fn only_used_glyphs_are_embedded() {
+        let pdf = render(&[passage("John 11:35", 1, "Jesus wept.")], "KJV").unwrap();
+        // the outlines of a few letters, not the whole font
+        assert!(pdf.len() < SERIF.len() / 4);
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        let font = FontRef::try_from_slice(SERIF).unwrap();
+        let layout = Layout::new(&font, font.units_per_em().unwrap());
+        let word = "Mahershalalhashbaz".repeat(6);
+        let pieces = layout.split_word(Word {
+            number: Some(1),
+            text: word.clone(),
+        });
+        assert!(pieces.len() > 1);
+        assert_eq!(pieces[0].number, Some(1));
+        assert!(pieces[1..].iter().all(|piece| piece.number.is_none()));
+        assert!(pieces.iter().all(|piece| {
+            layout.number_width(piece.number) + layout.width(&piece.text, BODY_SIZE) <= LINE_WIDTH
+        }));
+        assert_eq!(
+            pieces
+                .iter()
+                .map(|piece| piece.text.as_str())
+                .collect::<String>(),
+            word
+        );
+
+        // and drawn inside the margins
+        let mut layout = Layout::new(&font, font.units_per_em().unwrap());
+        layout.passage(&passage("Isaiah 8:1", 1, &word), true);
+        assert!(layout.pages[0].iter().all(|span| {
+            span.x >= MARGIN && span.x + layout.width(&span.text, span.size) <= PAGE_WIDTH - MARGIN
+        }));
+    }
+
+    #[test]
+    fn missing_characters_are_replaced() {
+        let font = FontRef::try_from_slice(SERIF).unwrap();
+        let layout = Layout::new(&font, font.units_per_em().unwrap());
+        assert_eq!(layout.printable("Amen 🙏"), "Amen ?");
+        assert_eq!(layout.printable("“Ἀμήν”"), "“Ἀμήν”");
+    }
+}
@@ -1,15 +1,202 @@
-use crate::hey;
+use crate::discord_helpers::MAX_BUTTON_ROWS;
+use crate::saved_passage::SavedPassage;
+use crate::{hey, nay};
 use serde::{Deserialize, Serialize};
-use serenity::all::{ChannelId, GuildId};
+use serenity::all::{ChannelId, GuildId, UserId};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
+use std::sync::{LazyLock, Mutex, MutexGuard, PoisonError};
+
+// every guild's file has its own lock, held while it is read or changed,
+// so two changes at once are written one after the other instead of one undoing the other
+static FILE_LOCKS: LazyLock<Mutex<HashMap<GuildId, &'static Mutex<()>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Locks a guild's file until the guard is dropped
+fn lock_file(id: &GuildId) -> MutexGuard<'static, ()> {
+    // locks are never removed from the map, so leaking them costs nothing more
+    let lock: &'static Mutex<()> = FILE_LOCKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(*id)
+        .or_insert_with(|| Box::leak(Box::default()));
+    lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// How passages too long for a single message are sent
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryPolicy {
+    /// one message with buttons to turn the pages
+    #[default]
+    Paginate,
+    /// one message per page
+    Split,
+    /// a message that starts a thread, with the pages inside it
+    Thread,
+    /// a message with the passage attached as a text file
+    File,
+}
+
+impl DeliveryPolicy {
+    pub const ALL: [DeliveryPolicy; 4] = [
+        DeliveryPolicy::Paginate,
+        DeliveryPolicy::Split,
+        DeliveryPolicy::Thread,
+        DeliveryPolicy::File,
+    ];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "paginate" => Some(DeliveryPolicy::Paginate),
+            "split" => Some(DeliveryPolicy::Split),
+            "thread" => Some(DeliveryPolicy::Thread),
+            "file" => Some(DeliveryPolicy::File),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            DeliveryPolicy::Paginate => "paginate",
+            DeliveryPolicy::Split => "split",
+            DeliveryPolicy::Thread => "thread",
+            DeliveryPolicy::File => "file",
+        }
+    }
+}
+
+impl Display for DeliveryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryPolicy::Paginate => write!(f, "Pages with buttons"),
+            DeliveryPolicy::Split => write!(f, "Several messages"),
+            DeliveryPolicy::Thread => write!(f, "A new thread"),
+            DeliveryPolicy::File => write!(f, "A text file"),
+        }
+    }
+}
+
+/// Limits applied to verses that are automatically detected in messages
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct DetectionSettings {
+    /// the most references that will be shown for a single message
+    pub max_references: usize,
+    /// how many verse embeds are grouped into a single reply
+    /// (at most 5 are used, see `verses_per_reply`, though older settings may have up to 10)
+    pub verses_per_message: usize,
+    /// seconds between detection replies in the same channel
+    pub channel_cooldown: u64,
+    /// seconds between detection replies to the same user
+    pub user_cooldown: u64,
+    /// reply to chapter-only references like `Psalm 23` with the whole chapter
+    pub whole_chapters: bool,
+    /// reply to book-only references like `Jude` with an overview of the book
+    pub whole_books: bool,
+    /// add a "Commentary" button to verse embeds (replies and commands)
+    pub commentary_button: bool,
+    /// how passages too long for one message are sent (replies and commands)
+    pub long_passages: DeliveryPolicy,
+}
+
+impl DetectionSettings {
+    /// How many verses actually go in one reply: each verse has its own row of buttons
+    /// (two with the commentary button), and a message can only hold 5 rows
+    pub fn verses_per_reply(&self) -> usize {
+        self.verses_per_message
+            .clamp(1, MAX_BUTTON_ROWS / self.button_rows_per_verse())
+    }
+
+    /// The "Commentary" button doesn't fit next to the other 5 buttons, so it gets a row of its own
+    pub fn button_rows_per_verse(&self) -> usize {
+        if self.commentary_button {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        Self {
+            max_references: 5,
+            verses_per_message: 5,
+            channel_cooldown: 0,
+            user_cooldown: 5,
+            whole_chapters: false,
+            whole_books: false,
+            commentary_button: false,
+            long_passages: DeliveryPolicy::default(),
+        }
+    }
+}
+
+/// A named list of passages, like a sermon series or memory verses
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Collection {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub passages: Vec<SavedPassage>,
+}
+
+/// A request posted to a guild's prayer channel
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrayerRequest {
+    /// numbered from 1 in each guild
+    pub id: u32,
+    /// kept even for anonymous requests, so the author can mark it answered
+    pub author: u64,
+    #[serde(default)]
+    pub anonymous: bool,
+    pub text: String,
+    /// where the request was posted
+    pub channel: u64,
+    pub message: u64,
+    /// the users who pressed "Praying"
+    #[serde(default)]
+    pub praying: Vec<u64>,
+    /// when it was posted, as a unix timestamp
+    pub posted: i64,
+    /// the follow-up given when it was marked answered
+    #[serde(default)]
+    pub answered: Option<String>,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct GuildFile {
     daily_verse_channel: Option<u64>,
     reading_schedule_channel: Option<u64>,
+    #[serde(default)]
+    prayer_channel: Option<u64>,
+    /// days after which prayer requests are closed, if set
+    #[serde(default)]
+    prayer_expiry_days: Option<u32>,
+    #[serde(default)]
+    prayer_requests: Vec<PrayerRequest>,
+    /// the id the next prayer request gets, so ids aren't reused after requests are removed
+    #[serde(default)]
+    next_prayer_id: u32,
+    #[serde(default)]
+    detection: DetectionSettings,
+    /// custom book aliases for this guild (alias -> book)
+    #[serde(default)]
+    book_aliases: HashMap<String, String>,
+    #[serde(default)]
+    collections: Vec<Collection>,
+    /// the collection the daily verse is taken from, instead of the verse shared by every guild
+    #[serde(default)]
+    daily_verse_collection: Option<String>,
+    /// `/quiz` points of each member (user id -> points)
+    #[serde(default)]
+    quiz_scores: HashMap<u64, u32>,
 }
 
 // Struct to hold guild settings and associated file data
@@ -21,10 +208,19 @@ pub struct GuildSettings {
 impl GuildSettings {
     pub fn new(guild_id: &GuildId) -> Self {
         Self {
-            id: guild_id.clone(),
+            id: *guild_id,
             file: GuildFile {
                 daily_verse_channel: None,
                 reading_schedule_channel: None,
+                prayer_channel: None,
+                prayer_expiry_days: None,
+                prayer_requests: Vec::new(),
+                next_prayer_id: 1,
+                detection: DetectionSettings::default(),
+                book_aliases: HashMap::new(),
+                collections: Vec::new(),
+                daily_verse_collection: None,
+                quiz_scores: HashMap::new(),
             },
         }
     }
@@ -63,6 +259,11 @@ impl GuildSettings {
     }
 
     pub fn get(id: &GuildId) -> Self {
+        let _lock = lock_file(id);
+        Self::read(id)
+    }
+
+    fn read(id: &GuildId) -> Self {
         let raw_path = format!("./guilds/{}.json", id);
         let path = std::path::Path::new(&raw_path);
 
@@ -76,11 +277,16 @@ impl GuildSettings {
             return Self::new(id);
         };
 
-        let guildfile: GuildFile = serde_json::from_str(data.as_str())
-            .expect(format!("failed to deserialize guild data with ID {}", id).as_str());
+        let guildfile: GuildFile = match serde_json::from_str(data.as_str()) {
+            Ok(guildfile) => guildfile,
+            Err(e) => {
+                nay!("Failed to deserialize guild data with ID {}: {}", id, e);
+                return Self::new(id);
+            }
+        };
 
         Self {
-            id: id.clone(),
+            id: *id,
             file: guildfile,
         }
     }
@@ -98,6 +304,7 @@ impl GuildSettings {
             .read(false)
             .write(true)
             .create(true)
+            .truncate(false)
             .append(false)
             .open(path)
         else {
@@ -117,8 +324,9 @@ impl GuildSettings {
         }
     }
 
+    /// Reads the file again, which must be locked first so nothing changes it in between
     fn reload(&mut self) {
-        *self = Self::get(&self.id);
+        *self = Self::read(&self.id);
     }
 
     fn update(&self) {
@@ -129,25 +337,33 @@ impl GuildSettings {
             Self::generate(&self.id);
         };
 
+        let Ok(data) = serde_json::to_string(&self.file) else {
+            hey!("Failed to serialize guild data: {}", &self.id);
+            return;
+        };
+
+        // write a temporary file and move it over the old one, so a failed write can't leave
+        // the guild file half written
+        let temp_path = format!("{}.tmp", raw_path);
         let Ok(mut file) = OpenOptions::new()
             .read(false)
             .write(true)
             .create(true)
             .append(false)
             .truncate(true)
-            .open(path)
+            .open(&temp_path)
         else {
             hey!("Failed to get file for guild data: {}", &self.id);
             return;
         };
 
-        let Ok(data) = serde_json::to_string(&self.file) else {
-            hey!("Failed to serialize guild data: {}", &self.id);
+        if let Err(e) = write!(file, "{}", data).and_then(|_| file.sync_all()) {
+            hey!("Failed to write to file for guild {}: {}", &self.id, e);
             return;
-        };
+        }
 
-        if let Err(e) = write!(file, "{}", data) {
-            hey!("Failed to write to file for guild {}: {}", &self.id, e);
+        if let Err(e) = fs::rename(&temp_path, path) {
+            hey!("Failed to replace file for guild {}: {}", &self.id, e);
         }
     }
 
@@ -157,6 +373,7 @@ impl GuildSettings {
     }
 
     pub fn get_daily_verse_channel_as_mut(&mut self) -> Option<ChannelId> {
+        let _lock = lock_file(&self.id);
         self.reload();
         let id = self.file.daily_verse_channel?;
         Some(ChannelId::from(id))
@@ -168,36 +385,54 @@ impl GuildSettings {
     }
 
     pub fn get_reading_schedule_channel_as_mut(&mut self) -> Option<ChannelId> {
+        let _lock = lock_file(&self.id);
         self.reload();
         let id = self.file.reading_schedule_channel?;
         Some(ChannelId::from(id))
     }
 
+    pub fn get_prayer_channel(&self) -> Option<ChannelId> {
+        let id = self.file.prayer_channel?;
+        Some(ChannelId::from(id))
+    }
+
     pub fn set_daily_verse_channel(&mut self, channel_id: ChannelId) {
+        let _lock = lock_file(&self.id);
         self.reload();
         self.file.daily_verse_channel = Some(channel_id.get());
         self.update();
     }
 
     pub fn set_reading_schedule_channel(&mut self, channel_id: ChannelId) {
+        let _lock = lock_file(&self.id);
         self.reload();
         self.file.reading_schedule_channel = Some(channel_id.get());
         self.update();
     }
 
+    pub fn set_prayer_channel(&mut self, channel_id: ChannelId) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.prayer_channel = Some(channel_id.get());
+        self.update();
+    }
+
     pub fn clear_daily_verse_channel(&mut self) {
+        let _lock = lock_file(&self.id);
         self.reload();
         self.file.daily_verse_channel = None;
         self.update();
     }
 
     pub fn clear_reading_schedule_channel(&mut self) {
+        let _lock = lock_file(&self.id);
         self.reload();
         self.file.reading_schedule_channel = None;
         self.update();
     }
 
     pub fn clear_channel_by_id(&mut self, channel_id: u64) {
+        let _lock = lock_file(&self.id);
         self.reload();
         if let Some(id) = self.file.daily_verse_channel {
             if id == channel_id {
@@ -209,6 +444,267 @@ impl GuildSettings {
                 self.file.reading_schedule_channel = None;
             }
         }
+        if let Some(id) = self.file.prayer_channel {
+            if id == channel_id {
+                self.file.prayer_channel = None;
+            }
+        }
+        self.update();
+    }
+
+    pub fn get_detection_settings(&self) -> DetectionSettings {
+        self.file.detection
+    }
+
+    pub fn set_detection_settings(&mut self, settings: DetectionSettings) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.detection = settings;
         self.update();
     }
+
+    pub fn get_book_aliases(&self) -> &HashMap<String, String> {
+        &self.file.book_aliases
+    }
+
+    pub fn add_book_alias(&mut self, alias: String, book: String) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.book_aliases.insert(alias, book);
+        self.update();
+    }
+
+    /// Returns false if the alias did not exist
+    pub fn remove_book_alias(&mut self, alias: &str) -> bool {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let removed = self.file.book_aliases.remove(alias).is_some();
+        self.update();
+        removed
+    }
+
+    pub fn get_collections(&self) -> &[Collection] {
+        &self.file.collections
+    }
+
+    /// Collection names are matched case-insensitively
+    pub fn get_collection(&self, name: &str) -> Option<&Collection> {
+        self.file
+            .collections
+            .iter()
+            .find(|collection| collection.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    fn get_collection_mut(&mut self, name: &str) -> Option<&mut Collection> {
+        self.file
+            .collections
+            .iter_mut()
+            .find(|collection| collection.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Returns false if there is already a collection with the name
+    pub fn create_collection(&mut self, name: String, description: Option<String>) -> bool {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        if self.get_collection(&name).is_some() {
+            return false;
+        }
+        self.file.collections.push(Collection {
+            name,
+            description,
+            passages: Vec::new(),
+        });
+        self.update();
+        true
+    }
+
+    /// Returns false if the collection did not exist
+    pub fn delete_collection(&mut self, name: &str) -> bool {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let count = self.file.collections.len();
+        self.file
+            .collections
+            .retain(|collection| !collection.name.eq_ignore_ascii_case(name.trim()));
+        let removed = self.file.collections.len() < count;
+        if self
+            .file
+            .daily_verse_collection
+            .as_ref()
+            .is_some_and(|daily| daily.eq_ignore_ascii_case(name.trim()))
+        {
+            self.file.daily_verse_collection = None;
+        }
+        self.update();
+        removed
+    }
+
+    /// Returns None if the collection did not exist, or Some(false) if the passage was already in it
+    pub fn add_to_collection(&mut self, name: &str, passage: SavedPassage) -> Option<bool> {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let collection = self.get_collection_mut(name)?;
+        if collection.passages.contains(&passage) {
+            return Some(false);
+        }
+        collection.passages.push(passage);
+        self.update();
+        Some(true)
+    }
+
+    /// Removes the passage at `index` of a collection
+    /// Returns None if there is no such collection or passage
+    pub fn remove_from_collection(&mut self, name: &str, index: usize) -> Option<SavedPassage> {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let collection = self.get_collection_mut(name)?;
+        if index >= collection.passages.len() {
+            return None;
+        }
+        let removed = collection.passages.remove(index);
+        self.update();
+        Some(removed)
+    }
+
+    /// The collection the daily verse is taken from, if one is set and still exists
+    pub fn get_daily_verse_collection(&self) -> Option<&Collection> {
+        self.get_collection(self.file.daily_verse_collection.as_ref()?)
+    }
+
+    pub fn set_daily_verse_collection(&mut self, name: Option<String>) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.daily_verse_collection = name;
+        self.update();
+    }
+
+    pub fn get_quiz_scores(&self) -> &HashMap<u64, u32> {
+        &self.file.quiz_scores
+    }
+
+    /// Returns the user's new total
+    pub fn add_quiz_score(&mut self, user: UserId, points: u32) -> u32 {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let score = self.file.quiz_scores.entry(user.get()).or_default();
+        *score += points;
+        let total = *score;
+        self.update();
+        total
+    }
+
+    pub fn get_prayer_expiry_days(&self) -> Option<u32> {
+        self.file.prayer_expiry_days
+    }
+
+    pub fn set_prayer_expiry_days(&mut self, days: Option<u32>) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.prayer_expiry_days = days;
+        self.update();
+    }
+
+    pub fn get_prayer_requests(&self) -> &[PrayerRequest] {
+        &self.file.prayer_requests
+    }
+
+    pub fn get_prayer_request(&self, id: u32) -> Option<&PrayerRequest> {
+        self.file
+            .prayer_requests
+            .iter()
+            .find(|request| request.id == id)
+    }
+
+    /// Takes the id for a new prayer request, which is never given to another request
+    /// Files from before ids were counted carry on from the highest id in use
+    pub fn reserve_prayer_request_id(&mut self) -> u32 {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let highest = self
+            .file
+            .prayer_requests
+            .iter()
+            .map(|request| request.id)
+            .max()
+            .unwrap_or(0);
+        let id = self.file.next_prayer_id.max(highest + 1);
+        self.file.next_prayer_id = id + 1;
+        self.update();
+        id
+    }
+
+    /// Adds a request, making room by removing the oldest answered requests if there are more than `max`
+    pub fn add_prayer_request(&mut self, request: PrayerRequest, max: usize) {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        self.file.prayer_requests.push(request);
+        while self.file.prayer_requests.len() > max {
+            let Some(oldest) = self
+                .file
+                .prayer_requests
+                .iter()
+                .position(|request| request.answered.is_some())
+            else {
+                break;
+            };
+            self.file.prayer_requests.remove(oldest);
+        }
+        self.update();
+    }
+
+    /// Adds or removes a user from those praying for a request, returning the request afterwards
+    /// Returns None if there is no such request
+    pub fn toggle_praying(&mut self, id: u32, user: UserId) -> Option<PrayerRequest> {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let request = self
+            .file
+            .prayer_requests
+            .iter_mut()
+            .find(|request| request.id == id)?;
+        match request
+            .praying
+            .iter()
+            .position(|praying| *praying == user.get())
+        {
+            Some(index) => {
+                request.praying.remove(index);
+            }
+            None => request.praying.push(user.get()),
+        }
+        let request = request.clone();
+        self.update();
+        Some(request)
+    }
+
+    /// Returns None if there is no such request
+    pub fn answer_prayer_request(&mut self, id: u32, follow_up: String) -> Option<PrayerRequest> {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let request = self
+            .file
+            .prayer_requests
+            .iter_mut()
+            .find(|request| request.id == id)?;
+        request.answered = Some(follow_up);
+        let request = request.clone();
+        self.update();
+        Some(request)
+    }
+
+    /// Removes the requests posted before `cutoff` (a unix timestamp), returning them
+    pub fn remove_prayer_requests_before(&mut self, cutoff: i64) -> Vec<PrayerRequest> {
+        let _lock = lock_file(&self.id);
+        self.reload();
+        let (expired, kept) = self
+            .file
+            .prayer_requests
+            .drain(..)
+            .partition(|request| request.posted < cutoff);
+        self.file.prayer_requests = kept;
+        if !expired.is_empty() {
+            self.update();
+        }
+        expired
+    }
 }
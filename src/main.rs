@@ -1,17 +1,45 @@
 use bible_lib::{Bible, Translation};
 use serenity::all::GatewayIntents;
 use serenity::Client;
+use std::collections::HashMap;
 use std::env;
 use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use crate::books::aliases::BookAliases;
+use crate::commentary::Commentaries;
 use crate::config::ConfigSettings;
+use crate::cross_references::CrossReferences;
+use crate::detection::cooldowns::Cooldowns;
+use crate::detection::reply_tracker::ReplyTracker;
+use crate::dictionary::Dictionary;
 use crate::discord_handler::Handler;
+use crate::quiz::QuizTracker;
+use crate::stats::Statistics;
+use crate::strongs::Strongs;
+use crate::topics::TopicalIndex;
+use crate::translations::Translations;
 
+pub mod books;
+pub mod commentary;
+pub mod cross_references;
 pub mod daily_messages;
+pub mod detection;
+pub mod dictionary;
 pub mod discord_helpers;
+pub mod export;
 pub mod guildfile;
 pub mod logging;
+pub mod memory;
+pub mod quiz;
+pub mod reference_parser;
+pub mod saved_passage;
+pub mod stats;
+pub mod strongs;
+pub mod topics;
+pub mod translations;
+pub mod userfile;
+pub mod verse_image;
 
 mod commands;
 mod config;
@@ -44,6 +72,17 @@ async fn main() {
             return;
         };
     }
+    // and the users directory for bookmarks and memory decks
+    let Ok(exists) = std::fs::exists("./users") else {
+        nay!("Failed to check if users directory exists");
+        return;
+    };
+    if !exists {
+        if let Err(e) = std::fs::create_dir_all("./users") {
+            nay!("Failed to create users directory: {}", e);
+            return;
+        };
+    }
 
     // get the env variables
     dotenv::dotenv().expect("Failed to load .env file");
@@ -57,6 +96,10 @@ async fn main() {
     let config = ConfigSettings::get();
     let translation = config.get_translation();
 
+    // book aliases, with any extras from the config
+    let mut aliases = BookAliases::new();
+    aliases.extend(config.get_book_aliases());
+
     // load the bible
     say!("Loading bible into ram...");
     let Ok(bible) = Bible::new(translation) else {
@@ -64,16 +107,52 @@ async fn main() {
         return;
     };
     say!("Bible loaded!");
+    let bible = Arc::new(bible);
+
+    // and the other translations for `/passage` and `/stats`
+    let translations = Translations::load(Arc::clone(&bible));
+    say!("Translations loaded!");
+
+    // work out the statistics for `/stats`
+    say!("Counting words...");
+    let statistics = Statistics::load(&bible, &translations);
+    say!("Statistics ready!");
+
+    // import the cross references for `/xref`
+    let cross_references = CrossReferences::load(&aliases);
+    // and the topics for `/topic`
+    let topics = TopicalIndex::load(&aliases);
+    // and the dictionary for `/define`
+    let dictionary = Dictionary::load();
+    // and the commentary for `/commentary`
+    let commentaries = Commentaries::load(&aliases);
+    // and the Strong's tagged text for `/word` and `/strongs`
+    let strongs = Strongs::load(&aliases);
 
     // discord client
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+        | GatewayIntents::DIRECT_MESSAGE_REACTIONS
         | GatewayIntents::MESSAGE_CONTENT;
 
     let Ok(mut client) = Client::builder(token, intents)
         .event_handler(Handler {
-            bible: Arc::new(bible),
+            bible: Arc::clone(&bible),
             subprocess_running: Arc::new(AtomicBool::new(false)),
+            cooldowns: Arc::new(Mutex::new(Cooldowns::new())),
+            replies: Arc::new(Mutex::new(ReplyTracker::new())),
+            quizzes: Arc::new(Mutex::new(QuizTracker::new())),
+            aliases: Arc::new(aliases),
+            guild_alias_cache: Arc::new(Mutex::new(HashMap::new())),
+            detection_settings_cache: Arc::new(Mutex::new(HashMap::new())),
+            translations: Arc::new(translations),
+            statistics: Arc::new(statistics),
+            cross_references: Arc::new(cross_references),
+            topics: Arc::new(topics),
+            dictionary: Arc::new(dictionary),
+            commentaries: Arc::new(commentaries),
+            strongs: Arc::new(strongs),
         })
         .await
     else {
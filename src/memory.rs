@@ -0,0 +1,258 @@
+// Spaced repetition for `/memory`: each user's deck is kept in their user file, and reviews are
+// sent by DM as cards come due. Remembering a verse doubles the days until it is asked again,
+// missing it brings it back within the hour.
+//
+// Everything needed to schedule reviews is in the user files, so a restart only delays reviews
+// until the first check after the bot is back.
+
+use std::time::Duration;
+
+use bible_lib::{Bible, BibleLookup};
+use serenity::all::{
+    ButtonStyle, Colour, Context, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateMessage, UserId,
+};
+
+use crate::{
+    books::aliases::BookAliases,
+    discord_helpers::verse_navigation::lookup_id,
+    hey, nay,
+    userfile::{MemoryCard, UserSettings},
+};
+
+// how often the decks are checked for due cards
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DAY: i64 = 24 * 60 * 60;
+// the longest a remembered verse goes without a review
+const MAX_INTERVAL: u32 = 180;
+// missed verses come back this soon
+const MISSED_DELAY: i64 = 60 * 60;
+// a review that hasn't been answered is sent again after this long
+const RESEND_AFTER: i64 = DAY;
+
+/// Records a review of a card and schedules the next one
+pub fn schedule(card: &mut MemoryCard, remembered: bool, now: i64) {
+    if remembered {
+        card.interval = (card.interval * 2).clamp(1, MAX_INTERVAL);
+        card.due = now + card.interval as i64 * DAY;
+    } else {
+        card.interval = 0;
+        card.due = now + MISSED_DELAY;
+    }
+    card.sent = None;
+    card.reviews += 1;
+}
+
+/// The card a user should review next: the one that has been due longest
+/// Returns None if nothing is due, or a review is still waiting for an answer
+pub fn next_due(deck: &[MemoryCard], now: i64) -> Option<&MemoryCard> {
+    let waiting = deck
+        .iter()
+        .any(|card| card.sent.is_some_and(|sent| now - sent < RESEND_AFTER));
+    if waiting {
+        return None;
+    }
+    deck.iter()
+        .filter(|card| card.due <= now)
+        .min_by_key(|card| card.due)
+}
+
+/// How many cards in a deck are due
+pub fn due_count(deck: &[MemoryCard], now: i64) -> usize {
+    deck.iter().filter(|card| card.due <= now).count()
+}
+
+/// The id of a review button, formatted as `memory:{action}:{book}:{chapter}:{verse}[:{thru_verse}]`
+fn review_button_id(action: &str, lookup: &BibleLookup) -> String {
+    format!("memory:{}:{}", action, lookup_id(lookup))
+}
+
+/// A memory verse with its text, hidden behind a spoiler until the user has tried to recall it
+pub fn craft_review_embed(
+    card: &MemoryCard,
+    bible: &Bible,
+    aliases: &BookAliases,
+    hidden: bool,
+) -> Option<CreateEmbed> {
+    let lookup = card.lookup(bible, aliases);
+    let text = bible.get_verse(lookup.clone(), false).ok()?;
+    let text = text.trim();
+
+    let description = if hidden {
+        format!(
+            "Say the verse from memory, then check yourself:\n\n||{}||",
+            text
+        )
+    } else {
+        text.to_string()
+    };
+    let reviews = match card.reviews {
+        0 => "First review".to_string(),
+        1 => "Reviewed once".to_string(),
+        reviews => format!("Reviewed {} times", reviews),
+    };
+
+    Some(
+        CreateEmbed::new()
+            .title(format!("🧠 Memory verse: {}", lookup))
+            .description(description)
+            .color(Colour::DARK_GREEN)
+            .footer(CreateEmbedFooter::new(format!(
+                "{} · {} Bible",
+                reviews,
+                bible.get_translation()
+            ))),
+    )
+}
+
+fn review_buttons(lookup: &BibleLookup) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(review_button_id("got", lookup))
+            .label("Got it")
+            .style(ButtonStyle::Success),
+        CreateButton::new(review_button_id("missed", lookup))
+            .label("Missed it")
+            .style(ButtonStyle::Danger),
+    ])]
+}
+
+/// DMs a card to a user and marks it as sent
+/// It is marked even if the DM fails, so users with closed DMs aren't retried every check
+/// Returns false if the DM couldn't be sent
+pub async fn send_review(
+    ctx: &Context,
+    user: UserId,
+    card: &MemoryCard,
+    bible: &Bible,
+    aliases: &BookAliases,
+) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    let mut settings = UserSettings::get(&user);
+    settings.update_memory_card(&card.passage, |card| card.sent = Some(now));
+
+    let Some(embed) = craft_review_embed(card, bible, aliases, true) else {
+        hey!(
+            "Memory verse {} {}:{} of user {} isn't in the bible",
+            card.passage.book,
+            card.passage.chapter,
+            card.passage.verse,
+            user
+        );
+        return false;
+    };
+    let message = CreateMessage::new()
+        .embed(embed)
+        .components(review_buttons(&card.lookup(bible, aliases)));
+    if let Err(e) = user.direct_message(&ctx.http, message).await {
+        hey!("Failed to send memory verse review to {}: {}", user, e);
+        return false;
+    }
+    true
+}
+
+/// The next card of every user with a card due (and reviews not paused)
+fn due_reviews(now: i64) -> Vec<(UserId, MemoryCard)> {
+    UserSettings::get_user_files()
+        .into_iter()
+        .filter(|settings| !settings.is_memory_paused())
+        .filter_map(|settings| {
+            Some((
+                settings.id,
+                next_due(settings.get_memory_deck(), now)?.clone(),
+            ))
+        })
+        .collect()
+}
+
+/// Sends every user with a card due (and reviews not paused) their next review
+pub async fn send_due_reviews(ctx: &Context, bible: &Bible, aliases: &BookAliases) {
+    let now = chrono::Utc::now().timestamp();
+    // reading every user file is slow, so it's kept off the async threads
+    let due = match tokio::task::spawn_blocking(move || due_reviews(now)).await {
+        Ok(due) => due,
+        Err(e) => {
+            nay!("Failed to check for due memory verses: {}", e);
+            return;
+        }
+    };
+    for (user, card) in due {
+        send_review(ctx, user, &card, bible, aliases).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saved_passage::SavedPassage;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn card(verse: u32, due: i64, sent: Option<i64>) -> MemoryCard {
+        MemoryCard {
+            passage: SavedPassage::new(&BibleLookup::new("John", 3, verse), &BookAliases::new()),
+            interval: 0,
+            due,
+            sent,
+            reviews: 0,
+        }
+    }
+
+    #[test]
+    fn remembering_doubles_the_interval() {
+        let mut card = card(16, NOW, Some(NOW));
+        let intervals: Vec<u32> = (0..5)
+            .map(|_| {
+                schedule(&mut card, true, NOW);
+                card.interval
+            })
+            .collect();
+        assert_eq!(intervals, vec![1, 2, 4, 8, 16]);
+        assert_eq!(card.due, NOW + 16 * DAY);
+        assert_eq!(card.sent, None);
+        assert_eq!(card.reviews, 5);
+    }
+
+    #[test]
+    fn intervals_are_capped() {
+        let mut card = card(16, NOW, None);
+        card.interval = MAX_INTERVAL;
+        schedule(&mut card, true, NOW);
+        assert_eq!(card.interval, MAX_INTERVAL);
+    }
+
+    #[test]
+    fn missing_starts_again() {
+        let mut card = card(16, NOW, Some(NOW));
+        card.interval = 32;
+        schedule(&mut card, false, NOW);
+        assert_eq!(card.interval, 0);
+        assert_eq!(card.due, NOW + MISSED_DELAY);
+        assert_eq!(card.sent, None);
+    }
+
+    #[test]
+    fn longest_due_first() {
+        let deck = vec![
+            card(1, NOW - 10, None),
+            card(2, NOW - 100, None),
+            card(3, NOW + 10, None),
+        ];
+        assert_eq!(next_due(&deck, NOW).unwrap().passage.verse, 2);
+        assert_eq!(due_count(&deck, NOW), 2);
+        assert!(next_due(&deck, NOW - 200).is_none());
+    }
+
+    #[test]
+    fn unanswered_reviews_wait() {
+        let deck = vec![card(1, NOW - 10, Some(NOW - 60)), card(2, NOW - 100, None)];
+        assert!(next_due(&deck, NOW).is_none());
+        // until they're sent again a day later
+        assert_eq!(
+            next_due(&deck, NOW - 60 + RESEND_AFTER)
+                .unwrap()
+                .passage
+                .verse,
+            2
+        );
+    }
+}
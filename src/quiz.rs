@@ -0,0 +1,700 @@
+// Memorization quiz questions for `/quiz`, drawn from the loaded bible.
+//
+// Questions are multiple choice. Which choice is right is kept here rather than in the button ids
+// (which anyone can read), so answers are checked against the questions still open in `QuizTracker`.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use bible_lib::{Bible, BibleLookup};
+use rand::{
+    seq::{IndexedRandom, IteratorRandom, SliceRandom},
+    Rng,
+};
+use serenity::all::UserId;
+
+use crate::{books::metadata::book_info, stats::COMMON_WORDS};
+
+// every question has this many choices
+pub const CHOICES: usize = 4;
+// how long a question can be answered for
+pub const QUESTION_TIME: Duration = Duration::from_secs(10 * 60);
+// how many open questions are remembered before the oldest are forgotten
+const DEFAULT_CAPACITY: usize = 500;
+// how many random verses are tried before giving up on the filters
+const MAX_ATTEMPTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuizMode {
+    /// words removed from a verse
+    Blank,
+    /// which book a verse is from
+    Book,
+    /// the end of a verse from its start
+    Finish,
+}
+
+impl QuizMode {
+    pub const ALL: [QuizMode; 3] = [QuizMode::Blank, QuizMode::Book, QuizMode::Finish];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "blank" => Some(QuizMode::Blank),
+            "book" => Some(QuizMode::Book),
+            "finish" => Some(QuizMode::Finish),
+            _ => None,
+        }
+    }
+}
+
+impl Display for QuizMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuizMode::Blank => write!(f, "Fill in the blank"),
+            QuizMode::Book => write!(f, "Which book is this from?"),
+            QuizMode::Finish => write!(f, "Finish the verse"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// The points a correct answer is worth
+    pub fn points(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard => 3,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+/// Where the verses of questions come from
+pub enum QuizSource {
+    /// any verse, or only verses from one book (as named by the bible)
+    Bible { book: Option<String> },
+    /// only these passages, i.e. a guild's collection
+    Passages(Vec<BibleLookup>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub mode: QuizMode,
+    pub difficulty: Difficulty,
+    /// the verse the question is about
+    pub lookup: BibleLookup,
+    /// what players are shown, i.e. the verse with words blanked out
+    pub prompt: String,
+    pub choices: Vec<String>,
+    /// the index of the right choice
+    pub answer: usize,
+}
+
+/// The letter shown for a choice, i.e. `B` for the second one
+pub fn choice_letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
+
+/// A verse's text with its whitespace tidied up
+fn verse_text(lookup: &BibleLookup, bible: &Bible) -> Option<String> {
+    let text = bible.get_verse(lookup.clone(), false).ok()?;
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Any verse in a book, or in the whole bible
+fn random_verse_in(bible: &Bible, book: Option<&str>) -> Option<BibleLookup> {
+    let Some(book) = book else {
+        return Some(bible.random_verse());
+    };
+    let mut rng = rand::rng();
+    let chapters = bible.verses.get(book)?;
+    let (chapter, verses) = chapters.iter().choose(&mut rng)?;
+    let verse = verses.keys().choose(&mut rng)?;
+    Some(BibleLookup::new(book, *chapter, *verse))
+}
+
+/// A random verse from the source with at least `min_words` words, and its text
+fn random_source_verse(
+    source: &QuizSource,
+    bible: &Bible,
+    min_words: usize,
+) -> Option<(BibleLookup, String)> {
+    let mut rng = rand::rng();
+    for _ in 0..MAX_ATTEMPTS {
+        let lookup = match source {
+            QuizSource::Bible { book } => random_verse_in(bible, book.as_deref())?,
+            QuizSource::Passages(passages) => passages.choose(&mut rng)?.clone(),
+        };
+        let Some(text) = verse_text(&lookup, bible) else {
+            continue;
+        };
+        if text.split(' ').count() >= min_words {
+            return Some((lookup, text));
+        }
+    }
+    None
+}
+
+/// A word with the punctuation around it removed
+fn core_word(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphabetic())
+}
+
+/// Words worth asking about: long enough and not something like "unto"
+fn is_key_word(word: &str) -> bool {
+    let word = core_word(word);
+    word.chars().count() >= 4
+        && word.chars().all(|c| c.is_alphabetic())
+        && !COMMON_WORDS.contains(&word.to_lowercase().as_str())
+}
+
+fn starts_upper(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+/// Puts the choices in a random order, returning them and where the right one ended up
+fn shuffle_choices(correct: String, mut wrong: Vec<String>) -> (Vec<String>, usize) {
+    wrong.truncate(CHOICES - 1);
+    wrong.push(correct.clone());
+    wrong.shuffle(&mut rand::rng());
+    let answer = wrong
+        .iter()
+        .position(|choice| *choice == correct)
+        .unwrap_or(0);
+    (wrong, answer)
+}
+
+/// Makes a question, or returns None if no verse fits the source and mode
+pub fn generate(
+    mode: QuizMode,
+    difficulty: Difficulty,
+    source: &QuizSource,
+    bible: &Bible,
+) -> Option<Question> {
+    // a few tries, as some verses don't have enough to ask about
+    (0..MAX_ATTEMPTS / 5).find_map(|_| match mode {
+        QuizMode::Blank => blank_question(difficulty, source, bible),
+        QuizMode::Book => book_question(difficulty, source, bible),
+        QuizMode::Finish => finish_question(difficulty, source, bible),
+    })
+}
+
+/// Key words to use as wrong answers, from the same chapter for hard questions
+fn decoy_words(lookup: &BibleLookup, difficulty: Difficulty, bible: &Bible) -> Vec<String> {
+    let texts: Vec<String> = if difficulty == Difficulty::Hard {
+        bible
+            .verses
+            .get(&lookup.book)
+            .and_then(|chapters| chapters.get(&lookup.chapter))
+            .map(|verses| verses.values().cloned().collect())
+            .unwrap_or_default()
+    } else {
+        (0..5)
+            .filter_map(|_| verse_text(&bible.random_verse(), bible))
+            .collect()
+    };
+
+    let mut words: Vec<String> = Vec::new();
+    for word in texts.iter().flat_map(|text| text.split_whitespace()) {
+        if is_key_word(word) {
+            let word = core_word(word).to_string();
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+    }
+    words
+}
+
+fn blank_question(difficulty: Difficulty, source: &QuizSource, bible: &Bible) -> Option<Question> {
+    let blanks = match difficulty {
+        Difficulty::Easy => 1,
+        Difficulty::Medium => 2,
+        Difficulty::Hard => 3,
+    };
+    let (lookup, text) = random_source_verse(source, bible, 6)?;
+    let mut rng = rand::rng();
+
+    let mut tokens: Vec<String> = text.split(' ').map(String::from).collect();
+    let candidates: Vec<usize> = (0..tokens.len())
+        .filter(|index| is_key_word(&tokens[*index]))
+        .collect();
+    if candidates.len() < blanks {
+        return None;
+    }
+    let mut chosen: Vec<usize> = candidates
+        .choose_multiple(&mut rng, blanks)
+        .copied()
+        .collect();
+    chosen.sort();
+
+    let answers: Vec<String> = chosen
+        .iter()
+        .map(|index| core_word(&tokens[*index]).to_string())
+        .collect();
+    for (number, index) in chosen.iter().enumerate() {
+        let word = core_word(&tokens[*index]).to_string();
+        let blank = if blanks == 1 {
+            "`_____`".to_string()
+        } else {
+            format!("`__{}__`", number + 1)
+        };
+        tokens[*index] = tokens[*index].replacen(&word, &blank, 1);
+    }
+
+    let decoys: Vec<String> = decoy_words(&lookup, difficulty, bible)
+        .into_iter()
+        .filter(|word| {
+            !answers
+                .iter()
+                .any(|answer| answer.eq_ignore_ascii_case(word))
+        })
+        .collect();
+    if decoys.len() < CHOICES {
+        return None;
+    }
+
+    // easy questions change every word, hard ones only one so the rest give nothing away
+    let changed = match difficulty {
+        Difficulty::Easy => blanks,
+        Difficulty::Medium => blanks.div_ceil(2),
+        Difficulty::Hard => 1,
+    };
+    let correct = answers.join(" / ");
+    let mut wrong: Vec<String> = Vec::new();
+    for _ in 0..MAX_ATTEMPTS {
+        if wrong.len() >= CHOICES - 1 {
+            break;
+        }
+        let mut words = answers.clone();
+        for index in (0..blanks)
+            .collect::<Vec<usize>>()
+            .choose_multiple(&mut rng, changed)
+        {
+            // a capitalised name in place of a plain word would give it away
+            let capitalised = starts_upper(&answers[*index]);
+            let matching: Vec<&String> = decoys
+                .iter()
+                .filter(|decoy| starts_upper(decoy) == capitalised)
+                .collect();
+            let pool = if matching.is_empty() {
+                decoys.iter().collect()
+            } else {
+                matching
+            };
+            words[*index] = pool.choose(&mut rng)?.to_string();
+        }
+        let choice = words.join(" / ");
+        if choice != correct && !wrong.contains(&choice) {
+            wrong.push(choice);
+        }
+    }
+    if wrong.len() < CHOICES - 1 {
+        return None;
+    }
+
+    let (choices, answer) = shuffle_choices(correct, wrong);
+    Some(Question {
+        mode: QuizMode::Blank,
+        difficulty,
+        lookup,
+        prompt: tokens.join(" "),
+        choices,
+        answer,
+    })
+}
+
+fn book_question(difficulty: Difficulty, source: &QuizSource, bible: &Bible) -> Option<Question> {
+    let (lookup, text) = random_source_verse(source, bible, 8)?;
+    let info = book_info(&lookup.book);
+    let mut rng = rand::rng();
+
+    // easy questions pick books from other parts of the bible, hard ones from the same part
+    let others: Vec<&String> = bible
+        .verses
+        .keys()
+        .filter(|book| **book != lookup.book)
+        .collect();
+    let similar = |book: &&&String, same_section: bool| {
+        let (Some(info), Some(other)) = (info, book_info(book)) else {
+            return !same_section;
+        };
+        (info.section == other.section) == same_section
+    };
+    let mut pool: Vec<&String> = match difficulty {
+        Difficulty::Easy => others
+            .iter()
+            .filter(|book| similar(book, false))
+            .copied()
+            .collect(),
+        Difficulty::Medium => others.clone(),
+        Difficulty::Hard => others
+            .iter()
+            .filter(|book| similar(book, true))
+            .copied()
+            .collect(),
+    };
+    // some sections (like Prophecy) are too small to fill the choices
+    if pool.len() < CHOICES - 1 {
+        pool = others
+            .iter()
+            .filter(|book| match (info, book_info(book)) {
+                (Some(info), Some(other)) => info.testament == other.testament,
+                _ => true,
+            })
+            .copied()
+            .collect();
+    }
+
+    let wrong: Vec<String> = pool
+        .choose_multiple(&mut rng, CHOICES - 1)
+        .map(|book| BibleLookup::capitalize_book(book))
+        .collect();
+    if wrong.len() < CHOICES - 1 {
+        return None;
+    }
+
+    let (choices, answer) = shuffle_choices(BibleLookup::capitalize_book(&lookup.book), wrong);
+    Some(Question {
+        mode: QuizMode::Book,
+        difficulty,
+        lookup,
+        prompt: text,
+        choices,
+        answer,
+    })
+}
+
+fn finish_question(difficulty: Difficulty, source: &QuizSource, bible: &Bible) -> Option<Question> {
+    let (lookup, text) = random_source_verse(source, bible, 10)?;
+    let words: Vec<&str> = text.split(' ').collect();
+
+    // harder questions show less of the verse
+    let shown = match difficulty {
+        Difficulty::Easy => words.len() * 2 / 3,
+        Difficulty::Medium => words.len() / 2,
+        Difficulty::Hard => words.len() / 3,
+    }
+    .max(3);
+    let ending_words = words.len() - shown;
+    let correct = words[shown..].join(" ");
+
+    // other verses' endings: from anywhere, the same book or the same chapter
+    let chapters = bible.verses.get(&lookup.book)?;
+    let mut rng = rand::rng();
+    let mut wrong: Vec<String> = Vec::new();
+    for _ in 0..MAX_ATTEMPTS {
+        if wrong.len() >= CHOICES - 1 {
+            break;
+        }
+        let other = match difficulty {
+            Difficulty::Easy => bible.random_verse(),
+            Difficulty::Medium => random_verse_in(bible, Some(&lookup.book))?,
+            Difficulty::Hard => {
+                let verses = chapters.get(&lookup.chapter)?;
+                let verse = verses.keys().choose(&mut rng)?;
+                BibleLookup::new(&lookup.book, lookup.chapter, *verse)
+            }
+        };
+        let Some(other_text) = verse_text(&other, bible) else {
+            continue;
+        };
+        let other_words: Vec<&str> = other_text.split(' ').collect();
+        if other_words.len() < 6 {
+            continue;
+        }
+        let take = ending_words.min(other_words.len() - 3);
+        let ending = other_words[other_words.len() - take..].join(" ");
+        if ending != correct && !wrong.contains(&ending) {
+            wrong.push(ending);
+        }
+    }
+    if wrong.len() < CHOICES - 1 {
+        return None;
+    }
+
+    let (choices, answer) = shuffle_choices(correct, wrong);
+    Some(Question {
+        mode: QuizMode::Finish,
+        difficulty,
+        lookup,
+        prompt: format!("{} …", words[..shown].join(" ")),
+        choices,
+        answer,
+    })
+}
+
+/// A question that can still be answered
+struct OpenQuestion {
+    question: Question,
+    asked: Instant,
+    answered: HashSet<UserId>,
+}
+
+/// What happened when someone answered a question
+pub enum Answer {
+    /// the question is too old, or was forgotten when the bot restarted
+    Closed,
+    AlreadyAnswered,
+    Correct(Question),
+    Wrong(Question),
+}
+
+/// A bounded map of open questions, by the id of the interaction that asked them
+pub struct QuizTracker {
+    capacity: usize,
+    order: VecDeque<u64>,
+    questions: HashMap<u64, OpenQuestion>,
+}
+
+impl QuizTracker {
+    pub fn new() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            order: VecDeque::new(),
+            questions: HashMap::new(),
+        }
+    }
+
+    /// Remember a question, forgetting the oldest if full
+    pub fn track(&mut self, id: u64, question: Question) {
+        self.questions.insert(
+            id,
+            OpenQuestion {
+                question,
+                asked: Instant::now(),
+                answered: HashSet::new(),
+            },
+        );
+        self.order.push_back(id);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.questions.remove(&oldest);
+            }
+        }
+    }
+
+    /// Stop tracking a question, i.e. because it couldn't be sent
+    pub fn forget(&mut self, id: u64) {
+        self.questions.remove(&id);
+        self.order.retain(|tracked| *tracked != id);
+    }
+
+    /// Check a user's answer, each user gets one try per question
+    pub fn answer(&mut self, id: u64, user: UserId, choice: usize) -> Answer {
+        let Some(open) = self.questions.get_mut(&id) else {
+            return Answer::Closed;
+        };
+        if open.asked.elapsed() > QUESTION_TIME {
+            return Answer::Closed;
+        }
+        if !open.answered.insert(user) {
+            return Answer::AlreadyAnswered;
+        }
+
+        if choice == open.question.answer {
+            Answer::Correct(open.question.clone())
+        } else {
+            Answer::Wrong(open.question.clone())
+        }
+    }
+}
+
+impl Default for QuizTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Some random choice of mode, for when none is given
+pub fn random_mode() -> QuizMode {
+    QuizMode::ALL[rand::rng().random_range(0..QuizMode::ALL.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bible_lib::Translation;
+    use std::sync::OnceLock;
+
+    fn bible() -> &'static Bible {
+        static BIBLE: OnceLock<Bible> = OnceLock::new();
+        BIBLE.get_or_init(|| Bible::new(Translation::KingJames).unwrap())
+    }
+
+    fn check_choices(question: &Question) {
+        assert_eq!(question.choices.len(), CHOICES);
+        assert!(question.answer < CHOICES);
+        for (index, choice) in question.choices.iter().enumerate() {
+            assert!(!choice.is_empty());
+            assert!(!question.choices[index + 1..].contains(choice));
+        }
+    }
+
+    fn question(answer: usize) -> Question {
+        Question {
+            mode: QuizMode::Book,
+            difficulty: Difficulty::Easy,
+            lookup: BibleLookup::new("john", 3, 16),
+            prompt: String::new(),
+            choices: vec!["A".into(), "B".into(), "C".into(), "D".into()],
+            answer,
+        }
+    }
+
+    #[test]
+    fn shuffled_choices_keep_the_answer() {
+        for _ in 0..20 {
+            let wrong = vec!["b".to_string(), "c".into(), "d".into(), "e".into()];
+            let (choices, answer) = shuffle_choices("a".to_string(), wrong);
+            assert_eq!(choices.len(), CHOICES);
+            assert_eq!(choices[answer], "a");
+        }
+    }
+
+    #[test]
+    fn blank_questions() {
+        let source = QuizSource::Bible { book: None };
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let question = generate(QuizMode::Blank, difficulty, &source, bible()).unwrap();
+            check_choices(&question);
+            let blanks = question.prompt.matches("`_").count();
+            assert_eq!(
+                blanks,
+                question.choices[question.answer].split(" / ").count()
+            );
+
+            // every word of the answer was taken out of the verse
+            let text = verse_text(&question.lookup, bible()).unwrap();
+            for word in question.choices[question.answer].split(" / ") {
+                assert!(text.contains(word));
+            }
+        }
+    }
+
+    #[test]
+    fn finish_questions() {
+        let source = QuizSource::Bible {
+            book: Some("psalms".to_string()),
+        };
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let question = generate(QuizMode::Finish, difficulty, &source, bible()).unwrap();
+            check_choices(&question);
+            assert_eq!(question.lookup.book, "psalms");
+
+            let text = verse_text(&question.lookup, bible()).unwrap();
+            let start = question.prompt.trim_end_matches(" …");
+            assert_eq!(
+                format!("{} {}", start, question.choices[question.answer]),
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn book_questions() {
+        let question = generate(
+            QuizMode::Book,
+            Difficulty::Hard,
+            &QuizSource::Bible { book: None },
+            bible(),
+        )
+        .unwrap();
+        check_choices(&question);
+        assert_eq!(
+            question.choices[question.answer],
+            BibleLookup::capitalize_book(&question.lookup.book)
+        );
+    }
+
+    #[test]
+    fn questions_from_passages() {
+        let source = QuizSource::Passages(vec![BibleLookup::new("john", 3, 16)]);
+        let question = generate(QuizMode::Blank, Difficulty::Easy, &source, bible()).unwrap();
+        assert_eq!(question.lookup, BibleLookup::new("john", 3, 16));
+        assert!(generate(
+            QuizMode::Blank,
+            Difficulty::Easy,
+            &QuizSource::Passages(vec![]),
+            bible()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn one_answer_each() {
+        let mut tracker = QuizTracker::new();
+        tracker.track(1, question(2));
+        assert!(matches!(
+            tracker.answer(1, UserId::new(1), 2),
+            Answer::Correct(_)
+        ));
+        assert!(matches!(
+            tracker.answer(1, UserId::new(1), 2),
+            Answer::AlreadyAnswered
+        ));
+        assert!(matches!(
+            tracker.answer(1, UserId::new(2), 0),
+            Answer::Wrong(_)
+        ));
+        assert!(matches!(
+            tracker.answer(2, UserId::new(1), 0),
+            Answer::Closed
+        ));
+    }
+
+    #[test]
+    fn old_questions_are_forgotten() {
+        let mut tracker = QuizTracker {
+            capacity: 2,
+            ..QuizTracker::new()
+        };
+        for id in 1..=3 {
+            tracker.track(id, question(0));
+        }
+        assert!(matches!(
+            tracker.answer(1, UserId::new(1), 0),
+            Answer::Closed
+        ));
+        assert!(matches!(
+            tracker.answer(3, UserId::new(1), 0),
+            Answer::Correct(_)
+        ));
+
+        tracker.forget(3);
+        assert!(matches!(
+            tracker.answer(3, UserId::new(2), 0),
+            Answer::Closed
+        ));
+        assert_eq!(tracker.order, vec![2]);
+    }
+}
@@ -0,0 +1,755 @@
+// Parses the references people write in sermon notes and chat into verse spans.
+// Handles everything bible-lib's detection does plus:
+//  - verse lists:         John 3:16,18,20
+//  - partial verses:      Rom 8:28a (the whole verse is shown)
+//  - following verses:    Psalm 119:1ff (to the end of the chapter), John 3:16f (the next verse)
+//  - continued lists:     Matt 5:3-12; 6:9-13 or John 3:16, 4:5 (the book carries over)
+//  - chapter ranges:      John 3:16-4:2
+
+use std::sync::LazyLock;
+
+use bible_lib::{Bible, BibleLookup};
+use regex::Regex;
+
+use crate::books::{
+    aliases::{canonical_books, normalize_book_name, BookAliases},
+    metadata::book_info,
+};
+
+// the most spans one message can give, so a reference like `John 1:1-4000000000:1` can't ask for
+// billions of chapters (the longest book, Psalms, has 150)
+const MAX_SPANS: usize = 150;
+
+// matches any full book name, longest names first so `1 john` wins over `john`
+static BOOK_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    let mut books = canonical_books();
+    books.sort_by_key(|book| std::cmp::Reverse(book.len()));
+    let names = books
+        .iter()
+        .map(|book| regex::escape(book).replace(' ', r"\s+"))
+        .collect::<Vec<String>>()
+        .join("|");
+    Regex::new(&format!(r"(?i)\b(?:{})\b\.?", names)).unwrap()
+});
+
+// a reference written in square brackets, i.e. `[Psalm 23]`
+static BRACKETED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\[\]]+)\]").unwrap());
+// a book name with an optional chapter, i.e. `Psalm 23` or `Jude`
+static BOOK_AND_CHAPTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)\.?\s*(\d+)?$").unwrap());
+// what has to come before a book's name for it to count as a reference outside of brackets
+static BOOK_OF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(?:the\s+)?book\s+of\s+").unwrap());
+
+/// A reference to a whole chapter or book, without any verses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WholeReference {
+    Chapter { book: String, chapter: u32 },
+    Book(String),
+}
+
+/// Where a span of verses ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanEnd {
+    Verse(u32),
+    EndOfChapter,
+}
+
+/// A run of verses within a single chapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerseSpan {
+    /// the full book name, lowercase
+    pub book: String,
+    pub chapter: u32,
+    pub start: u32,
+    /// None for a single verse
+    pub end: Option<SpanEnd>,
+}
+
+impl VerseSpan {
+    fn single(book: &str, chapter: u32, verse: u32) -> Self {
+        Self {
+            book: book.to_string(),
+            chapter,
+            start: verse,
+            end: None,
+        }
+    }
+
+    fn range(book: &str, chapter: u32, start: u32, end: SpanEnd) -> Self {
+        Self {
+            book: book.to_string(),
+            chapter,
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// Convert to a lookup, using the bible to find where chapters end
+    /// `book` should be the book name used by the bible
+    pub fn to_lookup(&self, book: &str, bible: &Bible) -> BibleLookup {
+        let end = match self.end {
+            None => None,
+            Some(SpanEnd::Verse(verse)) => Some(verse),
+            Some(SpanEnd::EndOfChapter) => bible.get_max_verse(book, self.chapter).ok(),
+        };
+
+        match end {
+            Some(end) if end > self.start => {
+                BibleLookup::new_range(book, self.chapter, self.start, end)
+            }
+            _ => BibleLookup::new(book, self.chapter, self.start),
+        }
+    }
+}
+
+/// Find every reference in a message, understanding book aliases
+pub fn parse_references(text: &str, aliases: &BookAliases) -> Vec<VerseSpan> {
+    let text = aliases.normalize_text(text);
+    let mut spans = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(found) = BOOK_NAME.find_at(&text, search_from) {
+        let book = found
+            .as_str()
+            .trim_end_matches('.')
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase();
+
+        let mut cursor = Cursor::new(&text, found.end());
+        let parsed = parse_after_book(&book, &mut cursor);
+        if parsed.is_empty() {
+            search_from = found.end();
+        } else {
+            spans.extend(parsed);
+            search_from = cursor.pos;
+        }
+
+        if spans.len() >= MAX_SPANS {
+            spans.truncate(MAX_SPANS);
+            break;
+        }
+    }
+
+    spans
+}
+
+/// Find a reference to a whole chapter or book, i.e. `Psalm 23` or `the book of Jude`
+/// So casual mentions like "we read Acts 2 today" are ignored, the message must either be
+/// nothing but the reference (using the book's full name) or have the reference in brackets
+/// (where abbreviations are allowed too), i.e. "we read [Acts 2] today"
+/// As many book names are also people's names, a book on its own needs brackets or to be
+/// written as `the book of James`, so a message that just says "John" is left alone
+pub fn parse_whole_reference(text: &str, aliases: &BookAliases) -> Option<WholeReference> {
+    for bracketed in BRACKETED.captures_iter(text) {
+        if let Some(reference) = whole_reference(&bracketed[1], aliases, true) {
+            return Some(reference);
+        }
+    }
+
+    let text = text.trim().trim_end_matches(['.', '!', '?']);
+    let book_of = BOOK_OF.find(text);
+    let text = book_of.map_or(text, |prefix| &text[prefix.end()..]);
+    match whole_reference(text, aliases, false)? {
+        WholeReference::Book(_) if book_of.is_none() => None,
+        reference => Some(reference),
+    }
+}
+
+fn whole_reference(
+    text: &str,
+    aliases: &BookAliases,
+    allow_aliases: bool,
+) -> Option<WholeReference> {
+    let captures = BOOK_AND_CHAPTER.captures(text.trim())?;
+    let name = normalize_book_name(&captures[1]);
+
+    let book = aliases.canonical_name(&name)?;
+    // outside of brackets only the full name counts (`psalm` is as full as `psalms`)
+    if !allow_aliases && book != name && book.strip_suffix('s') != Some(name.as_str()) {
+        return None;
+    }
+
+    match captures.get(2) {
+        Some(chapter) => {
+            let chapter = chapter
+                .as_str()
+                .parse()
+                .ok()
+                .filter(|chapter| *chapter > 0)?;
+            Some(WholeReference::Chapter { book, chapter })
+        }
+        None => Some(WholeReference::Book(book)),
+    }
+}
+
+/// A simple cursor over the text following a book name
+struct Cursor<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str, pos: usize) -> Self {
+        Self {
+            text: text.as_bytes(),
+            pos,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// a hyphen or an en dash
+    fn eat_dash(&mut self) -> bool {
+        if self.eat(b'-') {
+            return true;
+        }
+        if self.text[self.pos.min(self.text.len())..].starts_with("–".as_bytes()) {
+            self.pos += "–".len();
+            return true;
+        }
+        false
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        let number = std::str::from_utf8(&self.text[start..self.pos])
+            .ok()?
+            .parse()
+            .ok();
+        if number.is_none() {
+            self.pos = start;
+        }
+        number
+    }
+
+    /// true if the byte at `pos` is not part of a word
+    fn word_ends_at(&self, pos: usize) -> bool {
+        !self
+            .text
+            .get(pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric())
+    }
+
+    /// A whole word (case-insensitive), optionally followed by a period
+    fn eat_word(&mut self, word: &str) -> bool {
+        let end = self.pos + word.len();
+        let Some(slice) = self.text.get(self.pos..end) else {
+            return false;
+        };
+        if !slice.eq_ignore_ascii_case(word.as_bytes()) || !self.word_ends_at(end) {
+            return false;
+        }
+        self.pos = end;
+        self.eat(b'.');
+        true
+    }
+
+    /// The `a`, `b` or `c` of a partial verse like `28a`
+    fn eat_partial_suffix(&mut self) {
+        if self
+            .peek()
+            .is_some_and(|c| matches!(c.to_ascii_lowercase(), b'a' | b'b' | b'c'))
+            && self.word_ends_at(self.pos + 1)
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// `chapter:` followed by a verse number (only the chapter and colon are consumed)
+    fn chapter(&mut self) -> Option<u32> {
+        let start = self.pos;
+        let chapter = self.number();
+        if chapter.is_some() && self.eat(b':') && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return chapter;
+        }
+        self.pos = start;
+        None
+    }
+}
+
+/// Parse `chapter:verses` (and any continued chapters) after a book name
+fn parse_after_book(book: &str, cursor: &mut Cursor) -> Vec<VerseSpan> {
+    let mut spans = Vec::new();
+
+    cursor.skip_whitespace();
+    let Some(mut chapter) = cursor.chapter() else {
+        return spans;
+    };
+    parse_verse_list(book, &mut chapter, cursor, &mut spans);
+
+    // more chapters of the same book, i.e. `; 6:9-13` or `, 4:5`
+    loop {
+        let save = cursor.pos;
+        cursor.skip_whitespace();
+        if !(cursor.eat(b';') || cursor.eat(b',')) {
+            cursor.pos = save;
+            break;
+        }
+        cursor.skip_whitespace();
+        let Some(next_chapter) = cursor.chapter() else {
+            cursor.pos = save;
+            break;
+        };
+        chapter = next_chapter;
+        parse_verse_list(book, &mut chapter, cursor, &mut spans);
+    }
+
+    spans
+}
+
+/// Parse a comma separated list of verses and ranges, i.e. `16,18,20-22`
+fn parse_verse_list(
+    book: &str,
+    chapter: &mut u32,
+    cursor: &mut Cursor,
+    spans: &mut Vec<VerseSpan>,
+) {
+    if !parse_verse_item(book, chapter, cursor, spans) {
+        return;
+    }
+
+    loop {
+        let save = cursor.pos;
+        cursor.skip_whitespace();
+        if !cursor.eat(b',') {
+            cursor.pos = save;
+            return;
+        }
+        cursor.skip_whitespace();
+
+        // a new chapter is handled by the caller
+        let before_chapter = cursor.pos;
+        if cursor.chapter().is_some() {
+            cursor.pos = save;
+            return;
+        }
+        cursor.pos = before_chapter;
+
+        if !parse_verse_item(book, chapter, cursor, spans) {
+            cursor.pos = save;
+            return;
+        }
+    }
+}
+
+/// Parse a single verse or range, i.e. `16`, `28a`, `1ff`, `3-12` or `16-4:2`
+/// Returns false if there was no verse to parse
+fn parse_verse_item(
+    book: &str,
+    chapter: &mut u32,
+    cursor: &mut Cursor,
+    spans: &mut Vec<VerseSpan>,
+) -> bool {
+    let Some(start) = cursor.number() else {
+        return false;
+    };
+
+    // `ff` runs to the end of the chapter, `f` includes the next verse
+    let save = cursor.pos;
+    cursor.skip_whitespace();
+    if cursor.eat_word("ff") {
+        spans.push(VerseSpan::range(
+            book,
+            *chapter,
+            start,
+            SpanEnd::EndOfChapter,
+        ));
+        return true;
+    }
+    if cursor.eat_word("f") {
+        match start.checked_add(1) {
+            Some(next) => spans.push(VerseSpan::range(
+                book,
+                *chapter,
+                start,
+                SpanEnd::Verse(next),
+            )),
+            None => spans.push(VerseSpan::single(book, *chapter, start)),
+        }
+        return true;
+    }
+    cursor.pos = save;
+    cursor.eat_partial_suffix();
+
+    let save = cursor.pos;
+    cursor.skip_whitespace();
+    if !cursor.eat_dash() {
+        cursor.pos = save;
+        spans.push(VerseSpan::single(book, *chapter, start));
+        return true;
+    }
+    cursor.skip_whitespace();
+
+    // a range into a later chapter, i.e. `3:16-4:2`
+    if let Some(end_chapter) = cursor.chapter() {
+        let Some(end_verse) = cursor.number() else {
+            cursor.pos = save;
+            spans.push(VerseSpan::single(book, *chapter, start));
+            return true;
+        };
+        cursor.eat_partial_suffix();
+
+        // ranges past the end of the book stop at its last chapter
+        let last_chapter = book_info(book).map_or(MAX_SPANS as u32, |info| info.chapters);
+        let end = if end_chapter > last_chapter {
+            SpanEnd::EndOfChapter
+        } else {
+            SpanEnd::Verse(end_verse)
+        };
+        let end_chapter = end_chapter.min(last_chapter);
+
+        let count = (end_chapter.saturating_sub(*chapter) + 1) as usize;
+        if end_chapter <= *chapter || spans.len() + count > MAX_SPANS {
+            // backwards ranges (and ones too long to show) only show the first verse
+            spans.push(VerseSpan::single(book, *chapter, start));
+            return true;
+        }
+
+        spans.push(VerseSpan::range(
+            book,
+            *chapter,
+            start,
+            SpanEnd::EndOfChapter,
+        ));
+        for middle in (*chapter + 1)..end_chapter {
+            spans.push(VerseSpan::range(book, middle, 1, SpanEnd::EndOfChapter));
+        }
+        spans.push(VerseSpan::range(book, end_chapter, 1, end));
+        *chapter = end_chapter;
+        return true;
+    }
+
+    let Some(end) = cursor.number() else {
+        cursor.pos = save;
+        spans.push(VerseSpan::single(book, *chapter, start));
+        return true;
+    };
+    cursor.eat_partial_suffix();
+
+    if end > start {
+        spans.push(VerseSpan::range(book, *chapter, start, SpanEnd::Verse(end)));
+    } else {
+        // backwards ranges only show the first verse
+        spans.push(VerseSpan::single(book, *chapter, start));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Vec<VerseSpan> {
+        parse_references(text, &BookAliases::new())
+    }
+
+    fn single(book: &str, chapter: u32, verse: u32) -> VerseSpan {
+        VerseSpan::single(book, chapter, verse)
+    }
+
+    fn range(book: &str, chapter: u32, start: u32, end: u32) -> VerseSpan {
+        VerseSpan::range(book, chapter, start, SpanEnd::Verse(end))
+    }
+
+    fn to_end(book: &str, chapter: u32, start: u32) -> VerseSpan {
+        VerseSpan::range(book, chapter, start, SpanEnd::EndOfChapter)
+    }
+
+    #[test]
+    fn single_verse() {
+        assert_eq!(parse("John 3:16"), vec![single("john", 3, 16)]);
+    }
+
+    #[test]
+    fn verse_range() {
+        assert_eq!(parse("Luke 23:39-43"), vec![range("luke", 23, 39, 43)]);
+        assert_eq!(parse("Luke 23:39 - 43"), vec![range("luke", 23, 39, 43)]);
+        assert_eq!(parse("Luke 23:39–43"), vec![range("luke", 23, 39, 43)]);
+    }
+
+    #[test]
+    fn reference_inside_a_sentence() {
+        assert_eq!(
+            parse("Today we read (John 3:16) together."),
+            vec![single("john", 3, 16)]
+        );
+    }
+
+    #[test]
+    fn verse_list() {
+        assert_eq!(
+            parse("John 3:16,18,20"),
+            vec![
+                single("john", 3, 16),
+                single("john", 3, 18),
+                single("john", 3, 20)
+            ]
+        );
+    }
+
+    #[test]
+    fn verse_list_with_spaces_and_ranges() {
+        assert_eq!(
+            parse("John 3:16, 18-20, 22"),
+            vec![
+                single("john", 3, 16),
+                range("john", 3, 18, 20),
+                single("john", 3, 22)
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_verses() {
+        assert_eq!(parse("Rom 8:28a"), vec![single("romans", 8, 28)]);
+        assert_eq!(parse("Rom 8:28b-30a"), vec![range("romans", 8, 28, 30)]);
+    }
+
+    #[test]
+    fn partial_suffix_is_not_a_word() {
+        // `a` starts the next word, it isn't a partial verse
+        assert_eq!(parse("Rom 8:28 and more"), vec![single("romans", 8, 28)]);
+        assert_eq!(parse("Rom 8:28and"), vec![single("romans", 8, 28)]);
+        assert_eq!(
+            parse("John 3:16 for God so loved"),
+            vec![single("john", 3, 16)]
+        );
+    }
+
+    #[test]
+    fn following_verses() {
+        assert_eq!(parse("Psalm 119:1ff"), vec![to_end("psalms", 119, 1)]);
+        assert_eq!(parse("Psalm 119:1 ff."), vec![to_end("psalms", 119, 1)]);
+        assert_eq!(parse("John 3:16f"), vec![range("john", 3, 16, 17)]);
+    }
+
+    #[test]
+    fn continued_chapters_with_semicolons() {
+        assert_eq!(
+            parse("Matt 5:3-12; 6:9-13"),
+            vec![range("matthew", 5, 3, 12), range("matthew", 6, 9, 13)]
+        );
+    }
+
+    #[test]
+    fn continued_chapters_with_commas() {
+        assert_eq!(
+            parse("John 3:16, 4:5"),
+            vec![single("john", 3, 16), single("john", 4, 5)]
+        );
+    }
+
+    #[test]
+    fn multiple_books() {
+        assert_eq!(
+            parse("John 3:16; Rom 8:28"),
+            vec![single("john", 3, 16), single("romans", 8, 28)]
+        );
+        assert_eq!(
+            parse("Gen 1:1 and then Rev 22:21"),
+            vec![single("genesis", 1, 1), single("revelation", 22, 21)]
+        );
+    }
+
+    #[test]
+    fn chapter_ranges() {
+        assert_eq!(
+            parse("John 3:16-4:2"),
+            vec![to_end("john", 3, 16), range("john", 4, 1, 2)]
+        );
+        assert_eq!(
+            parse("John 3:16-5:2"),
+            vec![
+                to_end("john", 3, 16),
+                to_end("john", 4, 1),
+                range("john", 5, 1, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn list_continues_in_the_new_chapter() {
+        assert_eq!(
+            parse("John 3:36-4:2,5"),
+            vec![
+                to_end("john", 3, 36),
+                range("john", 4, 1, 2),
+                single("john", 4, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn backwards_ranges() {
+        assert_eq!(parse("John 3:18-16"), vec![single("john", 3, 18)]);
+        assert_eq!(parse("John 4:2-3:16"), vec![single("john", 4, 2)]);
+    }
+
+    #[test]
+    fn numbered_books_and_aliases() {
+        assert_eq!(
+            parse("1 Cor 13:4-7"),
+            vec![range("1 corinthians", 13, 4, 7)]
+        );
+        assert_eq!(parse("I John 1:9"), vec![single("1 john", 1, 9)]);
+        assert_eq!(parse("1st John 2:1"), vec![single("1 john", 2, 1)]);
+        assert_eq!(parse("1Jn 4:8"), vec![single("1 john", 4, 8)]);
+        assert_eq!(
+            parse("Song of Songs 2:4"),
+            vec![single("song of solomon", 2, 4)]
+        );
+    }
+
+    #[test]
+    fn common_words_are_not_aliases() {
+        assert_eq!(parse("re: John 3:16"), vec![single("john", 3, 16)]);
+        assert_eq!(parse("Re 3:20"), Vec::<VerseSpan>::new());
+        assert_eq!(parse("see you at 3 pm 4:30"), Vec::<VerseSpan>::new());
+        assert_eq!(parse("es 4:14"), Vec::<VerseSpan>::new());
+    }
+
+    #[test]
+    fn number_books_are_not_confused_with_plain_books() {
+        assert_eq!(
+            parse("John 1:1 and 1 John 1:1"),
+            vec![single("john", 1, 1), single("1 john", 1, 1)]
+        );
+    }
+
+    #[test]
+    fn no_references() {
+        assert_eq!(parse("meet at 10:30"), Vec::<VerseSpan>::new());
+        assert_eq!(parse("John is here"), Vec::<VerseSpan>::new());
+        assert_eq!(parse("John 3"), Vec::<VerseSpan>::new());
+        assert_eq!(parse("3:16"), Vec::<VerseSpan>::new());
+    }
+
+    #[test]
+    fn trailing_punctuation() {
+        assert_eq!(parse("Read John 3:16-18."), vec![range("john", 3, 16, 18)]);
+        assert_eq!(parse("John 3:16, then pray"), vec![single("john", 3, 16)]);
+        assert_eq!(parse("John 3:16-"), vec![single("john", 3, 16)]);
+    }
+
+    fn whole(text: &str) -> Option<WholeReference> {
+        parse_whole_reference(text, &BookAliases::new())
+    }
+
+    fn chapter(book: &str, chapter: u32) -> Option<WholeReference> {
+        Some(WholeReference::Chapter {
+            book: book.to_string(),
+            chapter,
+        })
+    }
+
+    #[test]
+    fn whole_chapter_on_its_own() {
+        assert_eq!(whole("Psalm 23"), chapter("psalms", 23));
+        assert_eq!(whole("  Psalms 23. "), chapter("psalms", 23));
+        assert_eq!(whole("1 John 2"), chapter("1 john", 2));
+    }
+
+    #[test]
+    fn whole_book_on_its_own() {
+        assert_eq!(
+            whole("the book of Jude"),
+            Some(WholeReference::Book("jude".to_string()))
+        );
+        assert_eq!(
+            whole("Book of Song of Solomon!"),
+            Some(WholeReference::Book("song of solomon".to_string()))
+        );
+        assert_eq!(whole("the book of Psalms 23"), chapter("psalms", 23));
+    }
+
+    #[test]
+    fn book_names_alone_are_not_whole_references() {
+        assert_eq!(whole("John"), None);
+        assert_eq!(whole("Mark!"), None);
+        assert_eq!(whole("James."), None);
+        assert_eq!(whole("the book of Jn"), None);
+    }
+
+    #[test]
+    fn whole_references_in_brackets() {
+        assert_eq!(whole("we read [Acts 2] today"), chapter("acts", 2));
+        assert_eq!(whole("tonight: [Ps 23]"), chapter("psalms", 23));
+        assert_eq!(
+            whole("[1 Jn]"),
+            Some(WholeReference::Book("1 john".to_string()))
+        );
+        assert_eq!(whole("[not a book] then [Jn 1]"), chapter("john", 1));
+    }
+
+    #[test]
+    fn casual_mentions_are_not_whole_references() {
+        assert_eq!(whole("we read Acts 2 today"), None);
+        assert_eq!(whole("Mark my words"), None);
+        // abbreviations need brackets
+        assert_eq!(whole("Ps 23"), None);
+        assert_eq!(whole("Acts 0"), None);
+        assert_eq!(whole("[link]"), None);
+    }
+
+    #[test]
+    fn huge_numbers_are_ignored() {
+        assert_eq!(parse("John 99999999999:1"), Vec::<VerseSpan>::new());
+    }
+
+    #[test]
+    fn chapter_ranges_stop_at_the_end_of_the_book() {
+        let spans = parse("John 1:1-4000000000:1");
+        assert_eq!(spans.len(), 21);
+        assert_eq!(spans[0], to_end("john", 1, 1));
+        assert_eq!(spans[20], to_end("john", 21, 1));
+    }
+
+    #[test]
+    fn spans_are_capped() {
+        assert_eq!(parse("Psalm 1:1-150:1").len(), 150);
+        // a second long range doesn't fit, so only its first verse is shown
+        let spans = parse("Psalm 1:1-149:1; 1:1-150:1");
+        assert_eq!(spans.len(), 150);
+        assert_eq!(spans[149], single("psalms", 1, 1));
+        let many = "John 1:1-21:1; ".repeat(20);
+        assert_eq!(parse(&many).len(), MAX_SPANS);
+    }
+
+    #[test]
+    fn following_verse_of_the_last_possible_verse() {
+        assert_eq!(
+            parse("John 3:4294967295f"),
+            vec![single("john", 3, u32::MAX)]
+        );
+        assert_eq!(
+            parse("John 3:4294967295ff"),
+            vec![to_end("john", 3, u32::MAX)]
+        );
+    }
+}
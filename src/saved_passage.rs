@@ -0,0 +1,43 @@
+// A verse or passage as it is stored in user and guild files (bookmarks and collections).
+// Books are kept by their full name from `BookAliases` rather than as the loaded translation names
+// them, so saved passages still work if the translation changes.
+
+use bible_lib::{Bible, BibleLookup};
+use serde::{Deserialize, Serialize};
+
+use crate::books::aliases::{normalize_book_name, BookAliases};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SavedPassage {
+    pub book: String,
+    pub chapter: u32,
+    pub verse: u32,
+    #[serde(default)]
+    pub thru_verse: Option<u32>,
+}
+
+impl SavedPassage {
+    pub fn new(lookup: &BibleLookup, aliases: &BookAliases) -> Self {
+        Self {
+            book: aliases
+                .canonical_name(&lookup.book)
+                .unwrap_or_else(|| normalize_book_name(&lookup.book)),
+            chapter: lookup.chapter,
+            verse: lookup.verse,
+            thru_verse: lookup
+                .thru_verse
+                .filter(|thru_verse| *thru_verse > lookup.verse),
+        }
+    }
+
+    /// The passage, with the book named as it is in `bible`
+    pub fn lookup(&self, bible: &Bible, aliases: &BookAliases) -> BibleLookup {
+        let book = aliases
+            .resolve(&self.book, bible)
+            .unwrap_or_else(|| self.book.clone());
+        match self.thru_verse {
+            Some(thru_verse) => BibleLookup::new_range(book, self.chapter, self.verse, thru_verse),
+            None => BibleLookup::new(book, self.chapter, self.verse),
+        }
+    }
+}
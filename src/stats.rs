@@ -0,0 +1,277 @@
+// Statistics about each translation, worked out once at startup so `/stats` can answer instantly.
+
+use std::{collections::HashMap, sync::Arc};
+
+use bible_lib::{Bible, BibleLookup};
+
+use crate::{
+    say,
+    translations::{Translations, TRANSLATION_CHOICES},
+};
+
+/// Words so common they drown out everything else in frequency lists
+pub const COMMON_WORDS: &[&str] = &[
+    "a", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be", "because", "been",
+    "before", "but", "by", "came", "come", "did", "do", "for", "from", "had", "has", "hast",
+    "hath", "have", "he", "her", "him", "his", "i", "if", "in", "into", "is", "it", "let", "me",
+    "my", "not", "now", "o", "of", "on", "one", "or", "our", "out", "said", "saith", "shall",
+    "she", "so", "that", "the", "thee", "their", "them", "then", "there", "these", "they", "thine",
+    "this", "those", "thou", "thus", "thy", "to", "unto", "up", "upon", "us", "was", "we", "were",
+    "what", "when", "which", "who", "whom", "will", "with", "ye", "you", "your",
+];
+
+/// Split a verse into lowercase words
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+/// A verse or chapter and how long it is
+#[derive(Debug, Clone)]
+pub struct Measured {
+    pub reference: String,
+    /// characters for verses, verses for chapters
+    pub length: usize,
+}
+
+/// Totals for a whole translation
+#[derive(Debug, Clone, Default)]
+pub struct Totals {
+    pub books: usize,
+    pub chapters: usize,
+    pub verses: usize,
+    pub words: usize,
+    pub unique_words: usize,
+    pub characters: usize,
+}
+
+/// Everything `/stats` can report for one translation
+pub struct BibleStats {
+    pub translation: String,
+    pub totals: Totals,
+    /// the books in canonical order
+    books: Vec<String>,
+    /// how often each word appears, per book
+    word_counts: HashMap<String, HashMap<String, usize>>,
+    /// how many verses in each book contain a word (word -> book -> verses)
+    verses_with_word: HashMap<String, HashMap<String, usize>>,
+    /// every verse as its words separated by single spaces (with one either side), for searching phrases
+    verses: Vec<(String, String)>,
+    /// verses sorted from longest to shortest
+    verses_by_length: Vec<Measured>,
+    /// chapters sorted from longest to shortest
+    chapters_by_length: Vec<Measured>,
+}
+
+impl BibleStats {
+    pub fn new(bible: &Bible) -> Self {
+        let mut totals = Totals::default();
+        let mut word_counts: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let mut verses_with_word: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        let mut verses = Vec::new();
+        let mut verses_by_length = Vec::new();
+        let mut chapters_by_length = Vec::new();
+
+        let books = bible.get_sorted_books();
+        for book in &books {
+            let Some(chapters) = bible.verses.get(book) else {
+                continue;
+            };
+            totals.books += 1;
+            let book_counts = word_counts.entry(book.clone()).or_default();
+
+            let mut chapter_numbers: Vec<&u32> = chapters.keys().collect();
+            chapter_numbers.sort();
+            for chapter in chapter_numbers {
+                let chapter_verses = &chapters[chapter];
+                totals.chapters += 1;
+                chapters_by_length.push(Measured {
+                    reference: format!("{} {}", BibleLookup::capitalize_book(book), chapter),
+                    length: chapter_verses.len(),
+                });
+
+                let mut verse_numbers: Vec<&u32> = chapter_verses.keys().collect();
+                verse_numbers.sort();
+                for verse in verse_numbers {
+                    let text = &chapter_verses[verse];
+                    totals.verses += 1;
+                    totals.characters += text.chars().count();
+                    // some translations leave a verse empty where it is missing from older manuscripts
+                    let length = text.trim().chars().count();
+                    if length > 0 {
+                        verses_by_length.push(Measured {
+                            reference: BibleLookup::new(book, *chapter, *verse).to_string(),
+                            length,
+                        });
+                    }
+
+                    let mut seen = Vec::new();
+                    for word in words(text) {
+                        totals.words += 1;
+                        *book_counts.entry(word.clone()).or_default() += 1;
+                        if !seen.contains(&word) {
+                            seen.push(word);
+                        }
+                    }
+                    for word in seen {
+                        *verses_with_word
+                            .entry(word)
+                            .or_default()
+                            .entry(book.clone())
+                            .or_default() += 1;
+                    }
+
+                    verses.push((
+                        book.clone(),
+                        format!(" {} ", words(text).collect::<Vec<String>>().join(" ")),
+                    ));
+                }
+            }
+        }
+        totals.unique_words = verses_with_word.len();
+
+        // longest first; the sort is stable so ties stay in canonical order
+        verses_by_length.sort_by_key(|m| std::cmp::Reverse(m.length));
+        chapters_by_length.sort_by_key(|m| std::cmp::Reverse(m.length));
+
+        Self {
+            translation: bible.get_translation().to_string(),
+            totals,
+            books,
+            word_counts,
+            verses_with_word,
+            verses,
+            verses_by_length,
+            chapters_by_length,
+        }
+    }
+
+    /// The most frequent words, in one book or the whole Bible
+    /// Common words like `the` and `unto` are left out unless `include_common` is set
+    pub fn top_words(
+        &self,
+        book: Option<&str>,
+        include_common: bool,
+        limit: usize,
+    ) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&String, usize> = HashMap::new();
+        for (_, book_counts) in self
+            .word_counts
+            .iter()
+            .filter(|(name, _)| book.is_none_or(|book| book == name.as_str()))
+        {
+            for (word, count) in book_counts {
+                *counts.entry(word).or_default() += count;
+            }
+        }
+
+        let mut top: Vec<(String, usize)> = counts
+            .into_iter()
+            .filter(|(word, _)| include_common || !COMMON_WORDS.contains(&word.as_str()))
+            .map(|(word, count)| (word.clone(), count))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(limit);
+        top
+    }
+
+    /// How many verses in each book contain a word or phrase, in canonical order
+    pub fn verses_containing(&self, term: &str) -> Vec<(String, usize)> {
+        let term_words: Vec<String> = words(term).collect();
+        let mut per_book: Vec<(String, usize)> = Vec::new();
+
+        match term_words.as_slice() {
+            [] => {}
+            // single words come straight from the index
+            [word] => {
+                if let Some(books) = self.verses_with_word.get(word) {
+                    per_book = books
+                        .iter()
+                        .map(|(book, count)| (book.clone(), *count))
+                        .collect();
+                }
+            }
+            // phrases have to be searched for
+            _ => {
+                let phrase = format!(" {} ", term_words.join(" "));
+                for (book, text) in &self.verses {
+                    if text.contains(&phrase) {
+                        match per_book.last_mut() {
+                            Some((last, count)) if last == book => *count += 1,
+                            _ => per_book.push((book.clone(), 1)),
+                        }
+                    }
+                }
+            }
+        }
+
+        per_book.sort_by_key(|(book, _)| self.books.iter().position(|b| b == book));
+        per_book
+    }
+
+    pub fn longest_verses(&self, limit: usize) -> Vec<&Measured> {
+        self.verses_by_length.iter().take(limit).collect()
+    }
+
+    pub fn shortest_verses(&self, limit: usize) -> Vec<&Measured> {
+        self.verses_by_length.iter().rev().take(limit).collect()
+    }
+
+    pub fn longest_chapters(&self, limit: usize) -> Vec<&Measured> {
+        self.chapters_by_length.iter().take(limit).collect()
+    }
+
+    pub fn shortest_chapters(&self, limit: usize) -> Vec<&Measured> {
+        self.chapters_by_length.iter().rev().take(limit).collect()
+    }
+}
+
+/// Statistics for every translation users can pick from, all worked out at startup
+pub struct Statistics {
+    default: Arc<BibleStats>,
+    // translation code -> statistics, for the translations other than the configured one
+    loaded: HashMap<String, Arc<BibleStats>>,
+}
+
+impl Statistics {
+    /// Works out the statistics for the configured bible and every other translation that was loaded
+    pub fn load(default: &Bible, translations: &Translations) -> Self {
+        let mut loaded = HashMap::new();
+        for (_, code) in TRANSLATION_CHOICES {
+            let Some(bible) = translations.get(Some(code)) else {
+                continue;
+            };
+            if bible.get_translation() == default.get_translation() {
+                continue;
+            }
+
+            say!(
+                "Working out statistics for the {}...",
+                bible.get_translation()
+            );
+            loaded.insert(code.to_string(), Arc::new(BibleStats::new(&bible)));
+        }
+
+        Self {
+            default: Arc::new(BibleStats::new(default)),
+            loaded,
+        }
+    }
+
+    /// Get the statistics for a translation code (see `TRANSLATION_CHOICES`)
+    /// `None` gives the configured translation
+    pub fn get(&self, code: Option<&str>) -> Option<Arc<BibleStats>> {
+        let Some(code) = code else {
+            return Some(Arc::clone(&self.default));
+        };
+
+        self.loaded.get(&code.to_lowercase()).cloned().or_else(|| {
+            // the configured translation is kept separately
+            TRANSLATION_CHOICES
+                .iter()
+                .any(|(_, choice)| choice.eq_ignore_ascii_case(code))
+                .then(|| Arc::clone(&self.default))
+        })
+    }
+}
@@ -0,0 +1,342 @@
+// A Strong's-number-tagged translation and a Strong's lexicon, for `/word` and `/strongs`.
+//
+// The tagged text has one verse per line: the reference (`Gen.1.1` or `Genesis 1:1`), a tab, then the
+// verse with Strong's numbers in braces after the words they belong to. A number applies to every
+// word since the one before it, and a word can have several:
+// `Gen.1.1	In the beginning{H7225} God{H430} created{H1254}{H853} the heaven{H8064}...`
+//
+// The lexicon has one entry per line: the number, the lemma, its transliteration and a short gloss,
+// separated by tabs (the transliteration can be left out):
+// `H7225	רֵאשִׁית	rêʼshîyth	the first, in place, time, order or rank`
+// Lines that can't be read in either file (like a header) are skipped.
+
+use std::collections::HashMap;
+
+use crate::{books::aliases::BookAliases, cross_references::VerseKey, hey, say};
+
+pub const TEXT_PATH: &str = "./data/strongs_text.txt";
+pub const LEXICON_PATH: &str = "./data/strongs_lexicon.txt";
+
+/// Tidies a Strong's number so `h07225` and `H7225` are the same
+/// Returns None if it isn't a Hebrew (`H`) or Greek (`G`) number
+pub fn normalize_number(number: &str) -> Option<String> {
+    let number = number.trim();
+    let mut chars = number.chars();
+    let language = chars.next()?.to_ascii_uppercase();
+    if language != 'H' && language != 'G' {
+        return None;
+    }
+    let digits: u32 = chars.as_str().parse().ok()?;
+    Some(format!("{}{}", language, digits))
+}
+
+/// Some words of a verse and the Strong's numbers they translate
+#[derive(Debug, Clone)]
+pub struct TaggedWord {
+    /// the words as they appear in the verse, including the spaces and punctuation around them
+    pub text: String,
+    /// empty for words that aren't tagged, like added words or punctuation
+    pub numbers: Vec<String>,
+}
+
+/// A Hebrew or Greek word from the lexicon
+#[derive(Debug, Clone)]
+pub struct LexiconEntry {
+    pub number: String,
+    pub lemma: String,
+    pub transliteration: Option<String>,
+    pub gloss: String,
+}
+
+impl LexiconEntry {
+    /// The lemma, transliteration and gloss, i.e. `רֵאשִׁית (rêʼshîyth): the first`
+    pub fn summary(&self) -> String {
+        match &self.transliteration {
+            Some(transliteration) => {
+                format!("{} ({}): {}", self.lemma, transliteration, self.gloss)
+            }
+            None => format!("{}: {}", self.lemma, self.gloss),
+        }
+    }
+}
+
+/// Splits a tagged verse into its words and their numbers
+pub fn parse_tagged_verse(text: &str) -> Vec<TaggedWord> {
+    let mut words: Vec<TaggedWord> = Vec::new();
+    let mut current = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        current.push_str(&rest[..open]);
+        let number = normalize_number(&rest[open + 1..close]);
+        rest = &rest[close + 1..];
+
+        let Some(number) = number else {
+            continue;
+        };
+        if current.trim().is_empty() {
+            // another number for the same words
+            match words.last_mut() {
+                Some(word) if !word.numbers.is_empty() => word.numbers.push(number),
+                _ => {}
+            }
+        } else {
+            words.push(TaggedWord {
+                text: std::mem::take(&mut current),
+                numbers: vec![number],
+            });
+        }
+    }
+
+    current.push_str(rest);
+    if !current.trim().is_empty() {
+        words.push(TaggedWord {
+            text: current,
+            numbers: Vec::new(),
+        });
+    }
+
+    words
+}
+
+/// Puts a tagged verse back together as plain text, cut short after about `max_chars` characters
+/// Words translating `highlight` are shown in bold
+pub fn render_verse(words: &[TaggedWord], highlight: Option<&str>, max_chars: usize) -> String {
+    let mut text = String::new();
+    for word in words {
+        if text.chars().count() >= max_chars {
+            text.push('…');
+            break;
+        }
+
+        let highlighted = highlight.is_some_and(|number| word.numbers.iter().any(|n| n == number));
+        let trimmed = word.text.trim();
+        if highlighted && !trimmed.is_empty() {
+            // keep the spaces outside the bold markers so discord shows them
+            let start = word.text.len() - word.text.trim_start().len();
+            let end = start + trimmed.len();
+            text.push_str(&word.text[..start]);
+            text.push_str(&format!("**{}**", trimmed));
+            text.push_str(&word.text[end..]);
+        } else {
+            text.push_str(&word.text);
+        }
+    }
+    text.trim().to_string()
+}
+
+/// The tagged translation and lexicon
+pub struct Strongs {
+    verses: HashMap<VerseKey, Vec<TaggedWord>>,
+    lexicon: HashMap<String, LexiconEntry>,
+    /// the verses each number is used in, in the order of the tagged text
+    usage: HashMap<String, Vec<VerseKey>>,
+}
+
+impl Strongs {
+    /// Import the tagged text at `TEXT_PATH` and the lexicon at `LEXICON_PATH`, if there are any
+    pub fn load(aliases: &BookAliases) -> Self {
+        let text = std::fs::read_to_string(TEXT_PATH).unwrap_or_else(|_| {
+            hey!(
+                "No Strong's tagged text found at {}, `/word` and `/strongs` will have nothing to show",
+                TEXT_PATH
+            );
+            String::new()
+        });
+        let lexicon = std::fs::read_to_string(LEXICON_PATH).unwrap_or_else(|_| {
+            hey!(
+                "No Strong's lexicon found at {}, words will be shown without their meanings",
+                LEXICON_PATH
+            );
+            String::new()
+        });
+
+        let (strongs, skipped) = Self::import(&text, &lexicon, aliases);
+        if !strongs.is_empty() {
+            say!(
+                "Loaded {} Strong's tagged verses and {} lexicon entries ({} lines skipped)",
+                strongs.verses.len(),
+                strongs.lexicon.len(),
+                skipped
+            );
+        }
+        strongs
+    }
+
+    /// Read the tagged text and lexicon, returning them and how many lines couldn't be read
+    pub fn import(text: &str, lexicon: &str, aliases: &BookAliases) -> (Self, usize) {
+        let mut skipped = 0;
+
+        let mut verses = HashMap::new();
+        let mut usage: HashMap<String, Vec<VerseKey>> = HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let Some((key, verse)) = line
+                .split_once('\t')
+                .and_then(|(key, verse)| Some((VerseKey::parse(key, aliases)?, verse)))
+            else {
+                skipped += 1;
+                continue;
+            };
+
+            let words = parse_tagged_verse(verse.trim());
+            for number in words.iter().flat_map(|word| &word.numbers) {
+                let used = usage.entry(number.clone()).or_default();
+                if used.last() != Some(&key) {
+                    used.push(key.clone());
+                }
+            }
+            verses.insert(key, words);
+        }
+
+        let mut entries = HashMap::new();
+        for line in lexicon.lines().filter(|line| !line.trim().is_empty()) {
+            let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let entry = match columns.as_slice() {
+                [number, lemma, gloss] => normalize_number(number).map(|number| LexiconEntry {
+                    number,
+                    lemma: lemma.to_string(),
+                    transliteration: None,
+                    gloss: gloss.to_string(),
+                }),
+                [number, lemma, transliteration, gloss, ..] => {
+                    normalize_number(number).map(|number| LexiconEntry {
+                        number,
+                        lemma: lemma.to_string(),
+                        transliteration: Some(transliteration.to_string())
+                            .filter(|transliteration| !transliteration.is_empty()),
+                        gloss: gloss.to_string(),
+                    })
+                }
+                _ => None,
+            };
+            match entry {
+                Some(entry) => {
+                    entries.insert(entry.number.clone(), entry);
+                }
+                None => skipped += 1,
+            }
+        }
+
+        (
+            Self {
+                verses,
+                lexicon: entries,
+                usage,
+            },
+            skipped,
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verses.is_empty()
+    }
+
+    /// The words of a verse with their numbers
+    pub fn verse(&self, key: &VerseKey) -> Option<&Vec<TaggedWord>> {
+        self.verses.get(key)
+    }
+
+    /// What a number means, if it's in the lexicon
+    pub fn entry(&self, number: &str) -> Option<&LexiconEntry> {
+        self.lexicon.get(&normalize_number(number)?)
+    }
+
+    /// Every verse a number is used in
+    pub fn usage(&self, number: &str) -> &[VerseKey] {
+        normalize_number(number)
+            .and_then(|number| self.usage.get(&number))
+            .map_or(&[], |verses| verses.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_normalized() {
+        assert_eq!(normalize_number("h07225").as_deref(), Some("H7225"));
+        assert_eq!(normalize_number(" G26 ").as_deref(), Some("G26"));
+        assert_eq!(normalize_number("X26"), None);
+        assert_eq!(normalize_number("H"), None);
+    }
+
+    #[test]
+    fn tagged_verse() {
+        let words = parse_tagged_verse(
+            "In the beginning{H7225} God{H430} created{H1254}{H853} the heaven.",
+        );
+        let numbers: Vec<(&str, Vec<&str>)> = words
+            .iter()
+            .map(|word| {
+                (
+                    word.text.as_str(),
+                    word.numbers.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                ("In the beginning", vec!["H7225"]),
+                (" God", vec!["H430"]),
+                (" created", vec!["H1254", "H853"]),
+                (" the heaven.", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tagged_verse_with_bad_tags() {
+        let words = parse_tagged_verse("{H1}Jesus{X5} wept{G1145}. {unclosed");
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "Jesus wept");
+        assert_eq!(words[0].numbers, vec!["G1145"]);
+        assert_eq!(words[1].text, ". {unclosed");
+        assert!(words[1].numbers.is_empty());
+    }
+
+    #[test]
+    fn rendering_highlights_words() {
+        let words = parse_tagged_verse("Jesus{G2424} wept{G1145}.");
+        assert_eq!(render_verse(&words, Some("G1145"), 100), "Jesus **wept**.");
+        assert_eq!(render_verse(&words, None, 3), "Jesus…");
+    }
+
+    #[test]
+    fn lexicon() {
+        let (strongs, skipped) = Strongs::import(
+            "",
+            "number\tlemma\n\
+             H7225\tרֵאשִׁית\trêʼshîyth\tthe first\n\
+             g26\tἀγάπη\tlove\n\
+             H430\tאֱלֹהִים\t\tGod\n\
+             bad\tentry\there\n",
+            &BookAliases::new(),
+        );
+        assert_eq!(skipped, 2);
+        assert_eq!(
+            strongs.entry("h7225").unwrap().summary(),
+            "רֵאשִׁית (rêʼshîyth): the first"
+        );
+        assert_eq!(strongs.entry("G26").unwrap().summary(), "ἀγάπη: love");
+        assert_eq!(strongs.entry("H430").unwrap().transliteration, None);
+        assert!(strongs.entry("H1").is_none());
+    }
+
+    #[test]
+    fn tagged_text_usage() {
+        let (strongs, skipped) = Strongs::import(
+            "Gen.1.1\tGod{H430} created{H1254}\nGenesis 1:2\tthe Spirit of God{H430}\nnot a verse\n",
+            "",
+            &BookAliases::new(),
+        );
+        assert_eq!(skipped, 1);
+        assert_eq!(strongs.usage("H430").len(), 2);
+        assert_eq!(strongs.usage("h1254").len(), 1);
+        assert!(strongs.usage("H1").is_empty());
+    }
+}
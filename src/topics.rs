@@ -0,0 +1,220 @@
+// A topical index imported from a Nave's style topical Bible, for `/topic`.
+//
+// The file has one topic per line: the topic's name, a tab, then its references written the way
+// people write them, i.e. `Forgiveness	Matt 6:14-15; Eph 4:32; Col 3:13`.
+// A topic can be split over several lines, which are joined together. Lines starting with `#` are comments.
+
+use bible_lib::{Bible, BibleLookup};
+use levenshtein::levenshtein;
+
+use crate::{
+    books::aliases::BookAliases,
+    hey,
+    reference_parser::{parse_references, VerseSpan},
+    say,
+};
+
+pub const PATH: &str = "./data/topics.txt";
+
+// how far a misspelled topic can be from a real one to be suggested
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+// names go into `topic_page:{name}:{page}` button ids and autocomplete choices,
+// which discord limits to 100 characters
+pub const MAX_NAME_CHARS: usize = 80;
+
+/// A topic and the verses about it
+#[derive(Debug, Clone)]
+pub struct Topic {
+    pub name: String,
+    pub spans: Vec<VerseSpan>,
+}
+
+impl Topic {
+    /// The topic's verses in the loaded bible, skipping any books it doesn't have
+    pub fn lookups(&self, bible: &Bible, aliases: &BookAliases) -> Vec<BibleLookup> {
+        self.spans
+            .iter()
+            .filter_map(|span| Some(span.to_lookup(&aliases.resolve(&span.book, bible)?, bible)))
+            .collect()
+    }
+}
+
+/// Every imported topic, sorted by name
+pub struct TopicalIndex {
+    topics: Vec<Topic>,
+}
+
+impl TopicalIndex {
+    /// Import the topics at `PATH`, if there are any
+    pub fn load(aliases: &BookAliases) -> Self {
+        let Ok(data) = std::fs::read_to_string(PATH) else {
+            hey!(
+                "No topical index found at {}, `/topic` will have nothing to show",
+                PATH
+            );
+            return Self { topics: Vec::new() };
+        };
+
+        let (index, skipped) = Self::import(&data, aliases);
+        say!(
+            "Loaded {} topics ({} lines skipped)",
+            index.topics.len(),
+            skipped
+        );
+        index
+    }
+
+    /// Read the topics, returning them and how many lines couldn't be read
+    /// Names longer than `MAX_NAME_CHARS` are cut short
+    pub fn import(data: &str, aliases: &BookAliases) -> (Self, usize) {
+        let mut topics: Vec<Topic> = Vec::new();
+        let mut skipped = 0;
+
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, references)) = line.split_once('\t') else {
+                skipped += 1;
+                continue;
+            };
+            let name: String = name.trim().chars().take(MAX_NAME_CHARS).collect();
+            let name = name.trim_end();
+            let spans = parse_references(references, aliases);
+            if name.is_empty() || spans.is_empty() {
+                skipped += 1;
+                continue;
+            }
+
+            let topic = match topics
+                .iter_mut()
+                .find(|topic| topic.name.eq_ignore_ascii_case(name))
+            {
+                Some(topic) => topic,
+                None => {
+                    topics.push(Topic {
+                        name: name.to_string(),
+                        spans: Vec::new(),
+                    });
+                    topics.last_mut().unwrap()
+                }
+            };
+            for span in spans {
+                if !topic.spans.contains(&span) {
+                    topic.spans.push(span);
+                }
+            }
+        }
+
+        topics.sort_by_key(|topic| topic.name.to_lowercase());
+        (Self { topics }, skipped)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+
+    /// Find a topic by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Topic> {
+        let name = name.trim();
+        self.topics
+            .iter()
+            .find(|topic| topic.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Topic names for autocomplete: ones starting with the text first, then ones containing it
+    pub fn complete(&self, partial: &str, limit: usize) -> Vec<&str> {
+        let partial = partial.trim().to_lowercase();
+        let starting = self
+            .topics
+            .iter()
+            .filter(|topic| topic.name.to_lowercase().starts_with(&partial));
+        let containing = self.topics.iter().filter(|topic| {
+            let name = topic.name.to_lowercase();
+            !name.starts_with(&partial) && name.contains(&partial)
+        });
+
+        starting
+            .chain(containing)
+            .take(limit)
+            .map(|topic| topic.name.as_str())
+            .collect()
+    }
+
+    /// Topics that may have been meant by a name that doesn't exist, closest first
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<&str> {
+        let name = name.trim().to_lowercase();
+        let mut suggestions: Vec<(usize, &str)> = self
+            .topics
+            .iter()
+            .map(|topic| {
+                (
+                    levenshtein(&name, &topic.name.to_lowercase()),
+                    topic.name.as_str(),
+                )
+            })
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .collect();
+        suggestions.sort_by_key(|(distance, _)| *distance);
+
+        let mut names: Vec<&str> = suggestions.into_iter().map(|(_, name)| name).collect();
+        for topic in self.complete(&name, limit) {
+            if !names.contains(&topic) {
+                names.push(topic);
+            }
+        }
+        names.truncate(limit);
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(data: &str) -> (TopicalIndex, usize) {
+        TopicalIndex::import(data, &BookAliases::new())
+    }
+
+    #[test]
+    fn topics_are_read_and_sorted() {
+        let (index, skipped) = import("Patience\tJas 1:4\nForgiveness\tMatt 6:14-15; Eph 4:32\n");
+        assert_eq!(skipped, 0);
+        assert_eq!(index.complete("", 10), vec!["Forgiveness", "Patience"]);
+        assert_eq!(index.get("forgiveness").unwrap().spans.len(), 2);
+    }
+
+    #[test]
+    fn split_topics_are_joined() {
+        let (index, _) = import("Love\t1 John 4:8\nlove\t1 Cor 13:4; 1 John 4:8\n");
+        assert_eq!(index.complete("", 10), vec!["Love"]);
+        assert_eq!(index.get("Love").unwrap().spans.len(), 2);
+    }
+
+    #[test]
+    fn comments_and_bad_lines() {
+        let (index, skipped) = import(
+            "# a comment\n\nNo tab here\nNo references\tnothing\n\tJohn 3:16\nGrace\tEph 2:8\n",
+        );
+        assert_eq!(skipped, 3);
+        assert_eq!(index.complete("", 10), vec!["Grace"]);
+    }
+
+    #[test]
+    fn long_names_are_cut_short() {
+        let name = "Word ".repeat(40);
+        let (index, _) = import(&format!("{}\tJohn 1:1\n", name));
+        let topics = index.complete("", 10);
+        assert_eq!(topics.len(), 1);
+        assert!(topics[0].chars().count() <= MAX_NAME_CHARS);
+        assert!(!topics[0].ends_with(' '));
+    }
+
+    #[test]
+    fn suggestions_for_misspellings() {
+        let (index, _) = import("Patience\tJas 1:4\nPeace\tJohn 14:27\n");
+        assert_eq!(index.suggest("patiense", 5), vec!["Patience"]);
+    }
+}
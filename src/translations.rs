@@ -0,0 +1,86 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bible_lib::{Bible, Translation};
+
+use crate::{nay, say};
+
+/// The built-in translations users can pick from (display name, code)
+pub const TRANSLATION_CHOICES: &[(&str, &str)] = &[
+    ("American Standard Version", "asv"),
+    ("American King James Version", "akjv"),
+    ("English Revised Version", "erv"),
+    ("King James Version", "kjv"),
+];
+
+fn translation_from_code(code: &str) -> Option<Translation> {
+    match code.to_lowercase().as_str() {
+        "asv" => Some(Translation::AmericanStandard),
+        "akjv" => Some(Translation::AmericanKingJames),
+        "erv" => Some(Translation::EnglishedRevised),
+        "kjv" => Some(Translation::KingJames),
+        _ => None,
+    }
+}
+
+/// The code of a bible's translation (see `TRANSLATION_CHOICES`), for button ids
+/// Returns None for translations users can't pick
+pub fn translation_code(bible: &Bible) -> Option<&'static str> {
+    TRANSLATION_CHOICES
+        .iter()
+        .map(|(_, code)| *code)
+        .find(|code| translation_from_code(code).as_ref() == Some(bible.get_translation()))
+}
+
+/// Bibles in every translation users can pick from, all loaded at startup
+/// so commands never wait for a bible to be parsed
+pub struct Translations {
+    default: Arc<Bible>,
+    // translation code -> bible, for the translations other than the configured one
+    loaded: HashMap<String, Arc<Bible>>,
+}
+
+impl Translations {
+    /// Loads every translation in `TRANSLATION_CHOICES`, reusing the configured one
+    /// Translations that fail to load are left out
+    pub fn load(default: Arc<Bible>) -> Self {
+        let mut loaded = HashMap::new();
+        for (_, code) in TRANSLATION_CHOICES {
+            let Some(translation) = translation_from_code(code) else {
+                continue;
+            };
+            if &translation == default.get_translation() {
+                continue;
+            }
+
+            say!("Loading the {} into ram...", translation);
+            match Bible::new(translation) {
+                Ok(bible) => {
+                    loaded.insert(code.to_string(), Arc::new(bible));
+                }
+                Err(_) => nay!("Failed to load translation: {}", code),
+            }
+        }
+
+        Self { default, loaded }
+    }
+
+    /// Get the bible for a translation code (see `TRANSLATION_CHOICES`)
+    /// `None` gives the configured translation
+    pub fn get(&self, code: Option<&str>) -> Option<Arc<Bible>> {
+        let Some(code) = code else {
+            return Some(Arc::clone(&self.default));
+        };
+        let translation = translation_from_code(code)?;
+        if &translation == self.default.get_translation() {
+            return Some(Arc::clone(&self.default));
+        }
+
+        self.loaded.get(&code.to_lowercase()).cloned()
+    }
+
+    /// Get the bible for a translation code from a button id
+    /// Missing codes and translations that failed to load give the configured translation
+    pub fn get_or_default(&self, code: Option<&str>) -> Arc<Bible> {
+        self.get(code).unwrap_or_else(|| Arc::clone(&self.default))
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{books::aliases::BookAliases, hey, nay, saved_passage::SavedPassage};
+use bible_lib::{Bible, BibleLookup};
+use serde::{Deserialize, Serialize};
+use serenity::all::UserId;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// A passage a user has saved
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    #[serde(flatten)]
+    pub passage: SavedPassage,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// lowercase, without the `#`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// when it was saved, as a unix timestamp
+    #[serde(default)]
+    pub saved: i64,
+}
+
+impl Bookmark {
+    pub fn new(lookup: &BibleLookup, aliases: &BookAliases) -> Self {
+        Self {
+            passage: SavedPassage::new(lookup, aliases),
+            note: None,
+            tags: Vec::new(),
+            saved: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// The bookmarked passage, with the book named as it is in `bible`
+    pub fn lookup(&self, bible: &Bible, aliases: &BookAliases) -> BibleLookup {
+        self.passage.lookup(bible, aliases)
+    }
+
+    pub fn same_passage(&self, other: &Bookmark) -> bool {
+        self.passage == other.passage
+    }
+}
+
+/// A passage in a user's memory deck, with when it should next be reviewed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemoryCard {
+    #[serde(flatten)]
+    pub passage: SavedPassage,
+    /// days between reviews, which grows each time it is remembered
+    #[serde(default)]
+    pub interval: u32,
+    /// when it is next due, as a unix timestamp
+    #[serde(default)]
+    pub due: i64,
+    /// when it was sent for review, if it hasn't been answered since
+    #[serde(default)]
+    pub sent: Option<i64>,
+    #[serde(default)]
+    pub reviews: u32,
+}
+
+impl MemoryCard {
+    /// A new card, due straight away
+    pub fn new(lookup: &BibleLookup, aliases: &BookAliases) -> Self {
+        Self {
+            passage: SavedPassage::new(lookup, aliases),
+            interval: 0,
+            due: chrono::Utc::now().timestamp(),
+            sent: None,
+            reviews: 0,
+        }
+    }
+
+    /// The passage, with the book named as it is in `bible`
+    pub fn lookup(&self, bible: &Bible, aliases: &BookAliases) -> BibleLookup {
+        self.passage.lookup(bible, aliases)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct UserFile {
+    bookmarks: Vec<Bookmark>,
+    memory_deck: Vec<MemoryCard>,
+    /// stops memory verse reviews being sent without losing the deck
+    memory_paused: bool,
+}
+
+// Struct to hold a user's saved data, in `./users/{id}.json`
+// Unlike guilds, nothing is written for a user until they save something
+pub struct UserSettings {
+    pub id: UserId,
+    pub file: UserFile,
+}
+
+impl UserSettings {
+    pub fn new(user_id: &UserId) -> Self {
+        Self {
+            id: *user_id,
+            file: UserFile::default(),
+        }
+    }
+
+    pub fn get(id: &UserId) -> Self {
+        let raw_path = format!("./users/{}.json", id);
+        let Ok(data) = fs::read_to_string(&raw_path) else {
+            return Self::new(id);
+        };
+
+        match serde_json::from_str(&data) {
+            Ok(file) => Self { id: *id, file },
+            Err(e) => {
+                nay!("Failed to deserialize user data with ID {}: {}", id, e);
+                Self::new(id)
+            }
+        }
+    }
+
+    /// Every user with saved data
+    pub fn get_user_files() -> Vec<UserSettings> {
+        let Ok(paths) = fs::read_dir("./users/") else {
+            nay!("Failed to read the users directory");
+            return Vec::new();
+        };
+
+        // files are named by the user's ID
+        paths
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .filter_map(|path| path.file_stem()?.to_str()?.parse::<u64>().ok())
+            .map(|id| UserSettings::get(&UserId::new(id)))
+            .collect()
+    }
+
+    fn reload(&mut self) {
+        *self = Self::get(&self.id);
+    }
+
+    fn update(&self) {
+        let raw_path = format!("./users/{}.json", self.id.get());
+        let path = Path::new(raw_path.as_str());
+
+        let Ok(mut file) = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+        else {
+            hey!("Failed to get file for user data: {}", &self.id);
+            return;
+        };
+
+        let Ok(data) = serde_json::to_string(&self.file) else {
+            hey!("Failed to serialize user data: {}", &self.id);
+            return;
+        };
+
+        if let Err(e) = write!(file, "{}", data) {
+            hey!("Failed to write to file for user {}: {}", &self.id, e);
+        }
+    }
+
+    pub fn get_bookmarks(&self) -> &[Bookmark] {
+        &self.file.bookmarks
+    }
+
+    /// Returns false if the passage was already bookmarked, in which case its note and tags are updated instead
+    /// Returns None if the passage is new but there are already `max` bookmarks
+    pub fn add_bookmark(&mut self, bookmark: Bookmark, max: usize) -> Option<bool> {
+        self.reload();
+        let count = self.file.bookmarks.len();
+        let added = match self
+            .file
+            .bookmarks
+            .iter_mut()
+            .find(|existing| existing.same_passage(&bookmark))
+        {
+            Some(existing) => {
+                if bookmark.note.is_some() {
+                    existing.note = bookmark.note;
+                }
+                for tag in bookmark.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+                false
+            }
+            None if count >= max => return None,
+            None => {
+                self.file.bookmarks.push(bookmark);
+                true
+            }
+        };
+        self.update();
+        Some(added)
+    }
+
+    /// Removes the bookmark at `index` (in the order they were saved)
+    /// Returns None if there is no such bookmark
+    pub fn remove_bookmark(&mut self, index: usize) -> Option<Bookmark> {
+        self.reload();
+        if index >= self.file.bookmarks.len() {
+            return None;
+        }
+        let removed = self.file.bookmarks.remove(index);
+        self.update();
+        Some(removed)
+    }
+
+    pub fn get_memory_deck(&self) -> &[MemoryCard] {
+        &self.file.memory_deck
+    }
+
+    /// Returns false if the passage is already in the deck
+    pub fn add_memory_card(&mut self, card: MemoryCard) -> bool {
+        self.reload();
+        if self
+            .file
+            .memory_deck
+            .iter()
+            .any(|existing| existing.passage == card.passage)
+        {
+            return false;
+        }
+        self.file.memory_deck.push(card);
+        self.update();
+        true
+    }
+
+    /// Removes the card at `index` (in the order they were added)
+    /// Returns None if there is no such card
+    pub fn remove_memory_card(&mut self, index: usize) -> Option<MemoryCard> {
+        self.reload();
+        if index >= self.file.memory_deck.len() {
+            return None;
+        }
+        let removed = self.file.memory_deck.remove(index);
+        self.update();
+        Some(removed)
+    }
+
+    /// Changes a card with `change`, returning it afterwards
+    /// Returns None if the passage isn't in the deck (i.e. it was removed)
+    pub fn update_memory_card<F: FnOnce(&mut MemoryCard)>(
+        &mut self,
+        passage: &SavedPassage,
+        change: F,
+    ) -> Option<MemoryCard> {
+        self.reload();
+        let card = self
+            .file
+            .memory_deck
+            .iter_mut()
+            .find(|card| card.passage == *passage)?;
+        change(card);
+        let card = card.clone();
+        self.update();
+        Some(card)
+    }
+
+    pub fn is_memory_paused(&self) -> bool {
+        self.file.memory_paused
+    }
+
+    pub fn set_memory_paused(&mut self, paused: bool) {
+        self.reload();
+        self.file.memory_paused = paused;
+        self.update();
+    }
+}
@@ -0,0 +1,441 @@
+// Verse cards for `/verse_image`: the text of a passage, its reference and translation drawn onto
+// a square PNG that can be shared anywhere.
+//
+// Cards are drawn on the CPU with `ab_glyph` and encoded with `png`, using the fonts in
+// `assets/fonts` built into the binary, so they work offline and don't need any system fonts.
+
+use std::fmt::{Display, Formatter};
+
+use ab_glyph::{Font, FontRef};
+use rand::Rng;
+
+use crate::verse_image::raster::{mix, text_width, Canvas, Colour};
+
+mod raster;
+
+// cards are square, which suits most places they are shared
+const CARD_SIZE: u32 = 1080;
+const MARGIN: f32 = 110.0;
+// the verse text starts this big, and shrinks until it fits
+const MAX_TEXT_SIZE: f32 = 72.0;
+const MIN_TEXT_SIZE: f32 = 30.0;
+const TEXT_SIZE_STEP: f32 = 2.0;
+const LINE_SPACING: f32 = 1.35;
+const REFERENCE_SIZE: f32 = 40.0;
+const TRANSLATION_SIZE: f32 = 26.0;
+// the gap between the verse text and the divider above the reference
+const REFERENCE_GAP: f32 = 56.0;
+const DIVIDER_WIDTH: f32 = 120.0;
+const DIVIDER_HEIGHT: f32 = 3.0;
+const TRANSLATION_GAP: f32 = 10.0;
+
+pub const SERIF: &[u8] = include_bytes!("../../assets/fonts/DejaVuSerif.ttf");
+const SERIF_ITALIC: &[u8] = include_bytes!("../../assets/fonts/DejaVuSerif-Italic.ttf");
+const SANS: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardStyle {
+    /// warm paper with darkened edges
+    Parchment,
+    /// a starry night sky
+    Night,
+    /// an orange and pink sky
+    Sunrise,
+    /// plain white with a thin frame
+    Minimal,
+}
+
+impl CardStyle {
+    pub const ALL: [CardStyle; 4] = [
+        CardStyle::Parchment,
+        CardStyle::Night,
+        CardStyle::Sunrise,
+        CardStyle::Minimal,
+    ];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "parchment" => Some(CardStyle::Parchment),
+            "night" => Some(CardStyle::Night),
+            "sunrise" => Some(CardStyle::Sunrise),
+            "minimal" => Some(CardStyle::Minimal),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CardStyle::Parchment => "parchment",
+            CardStyle::Night => "night",
+            CardStyle::Sunrise => "sunrise",
+            CardStyle::Minimal => "minimal",
+        }
+    }
+
+    /// The colours of the verse text, the divider and the translation
+    fn palette(&self) -> (Colour, Colour, Colour) {
+        match self {
+            CardStyle::Parchment => ([70, 48, 28], [150, 110, 60], [120, 90, 60]),
+            CardStyle::Night => ([245, 245, 250], [240, 200, 110], [180, 190, 220]),
+            CardStyle::Sunrise => ([255, 255, 255], [255, 240, 200], [255, 232, 222]),
+            CardStyle::Minimal => ([34, 34, 34], [180, 150, 90], [120, 120, 120]),
+        }
+    }
+
+    fn draw_background(&self, canvas: &mut Canvas) {
+        let mut rng = rand::rng();
+        let size = CARD_SIZE as f32;
+        match self {
+            CardStyle::Parchment => {
+                let centre = size / 2.0;
+                let furthest = centre * std::f32::consts::SQRT_2;
+                for y in 0..CARD_SIZE {
+                    let row = gradient([246, 236, 210], [232, 214, 176], y);
+                    for x in 0..CARD_SIZE {
+                        let distance = ((x as f32 - centre).powi(2) + (y as f32 - centre).powi(2))
+                            .sqrt()
+                            / furthest;
+                        let mut colour = mix(row, [120, 90, 50], 0.35 * distance.powi(2));
+                        // a little grain, so it looks like paper rather than a flat colour
+                        let grain: i16 = rng.random_range(-5..=5);
+                        for channel in colour.iter_mut() {
+                            *channel = (*channel as i16 + grain).clamp(0, 255) as u8;
+                        }
+                        canvas.set(x, y, colour);
+                    }
+                }
+            }
+            CardStyle::Night => {
+                fill_gradient(canvas, [14, 22, 48], [34, 40, 84]);
+                for _ in 0..180 {
+                    let x = rng.random_range(0.0..size);
+                    let y = rng.random_range(0.0..size);
+                    let radius = rng.random_range(0.5..1.8);
+                    let brightness = rng.random_range(0.3..0.9);
+                    canvas.fill_circle(x, y, radius, [255, 255, 240], brightness);
+                }
+            }
+            CardStyle::Sunrise => {
+                fill_gradient(canvas, [236, 92, 124], [255, 170, 90]);
+                // the glow of the sun coming up over the bottom edge
+                let (sun_x, sun_y) = (size / 2.0, size * 1.05);
+                for y in 0..CARD_SIZE {
+                    for x in 0..CARD_SIZE {
+                        let distance =
+                            ((x as f32 - sun_x).powi(2) + (y as f32 - sun_y).powi(2)).sqrt() / size;
+                        let glow = (1.0 - distance * 1.6).max(0.0).powi(2) * 0.6;
+                        let colour = mix(canvas.get(x, y), [255, 226, 150], glow);
+                        canvas.set(x, y, colour);
+                    }
+                }
+            }
+            CardStyle::Minimal => {
+                fill_gradient(canvas, [252, 252, 250], [252, 252, 250]);
+                let inset = MARGIN / 2.0;
+                let length = size - inset * 2.0;
+                let frame = [200, 200, 200];
+                canvas.fill_rect(inset, inset, length, 2.0, frame);
+                canvas.fill_rect(inset, size - inset - 2.0, length, 2.0, frame);
+                canvas.fill_rect(inset, inset, 2.0, length, frame);
+                canvas.fill_rect(size - inset - 2.0, inset, 2.0, length, frame);
+            }
+        }
+    }
+}
+
+impl Display for CardStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CardStyle::Parchment => write!(f, "Parchment"),
+            CardStyle::Night => write!(f, "Night sky"),
+            CardStyle::Sunrise => write!(f, "Sunrise"),
+            CardStyle::Minimal => write!(f, "Minimal"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardFont {
+    Serif,
+    Sans,
+    Italic,
+}
+
+impl CardFont {
+    pub const ALL: [CardFont; 3] = [CardFont::Serif, CardFont::Sans, CardFont::Italic];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "serif" => Some(CardFont::Serif),
+            "sans" => Some(CardFont::Sans),
+            "italic" => Some(CardFont::Italic),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CardFont::Serif => "serif",
+            CardFont::Sans => "sans",
+            CardFont::Italic => "italic",
+        }
+    }
+
+    fn data(&self) -> &'static [u8] {
+        match self {
+            CardFont::Serif => SERIF,
+            CardFont::Sans => SANS,
+            CardFont::Italic => SERIF_ITALIC,
+        }
+    }
+}
+
+impl Display for CardFont {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CardFont::Serif => write!(f, "Serif"),
+            CardFont::Sans => write!(f, "Sans serif"),
+            CardFont::Italic => write!(f, "Italic serif"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardError {
+    /// the text doesn't fit on the card, even at the smallest size
+    TooLong,
+    /// the font couldn't be read or the image couldn't be encoded
+    Render,
+}
+
+/// Draws a verse card, returning the PNG
+pub fn render_card(
+    text: &str,
+    reference: &str,
+    translation: &str,
+    style: CardStyle,
+    font: CardFont,
+) -> Result<Vec<u8>, CardError> {
+    let font = FontRef::try_from_slice(font.data()).map_err(|_| CardError::Render)?;
+    let units_per_em = font.units_per_em().ok_or(CardError::Render)?;
+    let size = CARD_SIZE as f32;
+    let text_area = size - MARGIN * 2.0;
+    let ascent = font.ascent_unscaled() / units_per_em;
+    let descent = -font.descent_unscaled() / units_per_em;
+
+    // the reference and translation always take the same room under the text
+    let reference_height = REFERENCE_GAP
+        + DIVIDER_HEIGHT
+        + REFERENCE_GAP * 0.75
+        + REFERENCE_SIZE * (ascent + descent)
+        + TRANSLATION_GAP
+        + TRANSLATION_SIZE * (ascent + descent);
+
+    let quoted = format!(
+        "\u{201c}{}\u{201d}",
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    );
+    let mut text_size = MAX_TEXT_SIZE;
+    let (text_size, lines) = loop {
+        // a word too long for a line of its own needs smaller text too
+        let lines = wrap(&font, &quoted, text_size, text_area);
+        let height = text_block_height(lines.len(), text_size, ascent, descent);
+        let widest = lines
+            .iter()
+            .map(|line| text_width(&font, line, text_size))
+            .fold(0.0, f32::max);
+        if height + reference_height <= text_area && widest <= text_area {
+            break (text_size, lines);
+        }
+        text_size -= TEXT_SIZE_STEP;
+        if text_size < MIN_TEXT_SIZE {
+            return Err(CardError::TooLong);
+        }
+    };
+
+    let mut canvas = Canvas::new(CARD_SIZE, CARD_SIZE);
+    style.draw_background(&mut canvas);
+    let (text_colour, accent, muted) = style.palette();
+
+    // the text and reference together are centred on the card
+    let text_height = text_block_height(lines.len(), text_size, ascent, descent);
+    let mut top = (size - text_height - reference_height) / 2.0;
+    for (x, line) in lines.iter().enumerate() {
+        let baseline = top + text_size * ascent + x as f32 * text_size * LINE_SPACING;
+        let left = (size - text_width(&font, line, text_size)) / 2.0;
+        canvas.draw_text(&font, line, text_size, left, baseline, text_colour);
+    }
+    top += text_height + REFERENCE_GAP;
+
+    canvas.fill_rect(
+        (size - DIVIDER_WIDTH) / 2.0,
+        top,
+        DIVIDER_WIDTH,
+        DIVIDER_HEIGHT,
+        accent,
+    );
+    top += DIVIDER_HEIGHT + REFERENCE_GAP * 0.75;
+
+    let baseline = top + REFERENCE_SIZE * ascent;
+    let left = (size - text_width(&font, reference, REFERENCE_SIZE)) / 2.0;
+    canvas.draw_text(
+        &font,
+        reference,
+        REFERENCE_SIZE,
+        left,
+        baseline,
+        text_colour,
+    );
+    top += REFERENCE_SIZE * (ascent + descent) + TRANSLATION_GAP;
+
+    let baseline = top + TRANSLATION_SIZE * ascent;
+    let left = (size - text_width(&font, translation, TRANSLATION_SIZE)) / 2.0;
+    canvas.draw_text(&font, translation, TRANSLATION_SIZE, left, baseline, muted);
+
+    encode_png(&canvas).ok_or(CardError::Render)
+}
+
+/// Encodes a canvas as an RGB PNG
+fn encode_png(canvas: &Canvas) -> Option<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(&canvas.pixels).ok()?;
+    writer.finish().ok()?;
+    Some(png)
+}
+
+/// The height of a paragraph, from the top of the first line to the bottom of the last
+fn text_block_height(lines: usize, size: f32, ascent: f32, descent: f32) -> f32 {
+    lines.saturating_sub(1) as f32 * size * LINE_SPACING + size * (ascent + descent)
+}
+
+/// Splits text into lines no wider than `width`
+/// A word too long for a line of its own is left to run over, for the caller to check
+fn wrap(font: &FontRef, text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if text_width(font, &candidate, size) <= width || line.is_empty() {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The colour of row `y` of a gradient from the top of the card to the bottom
+fn gradient(top: Colour, bottom: Colour, y: u32) -> Colour {
+    mix(top, bottom, y as f32 / (CARD_SIZE - 1) as f32)
+}
+
+fn fill_gradient(canvas: &mut Canvas, top: Colour, bottom: Colour) {
+    for y in 0..CARD_SIZE {
+        let colour = gradient(top, bottom, y);
+        for x in 0..CARD_SIZE {
+            canvas.set(x, y, colour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serif() -> FontRef<'static> {
+        FontRef::try_from_slice(SERIF).unwrap()
+    }
+
+    #[test]
+    fn card_is_a_png() {
+        let card = render_card(
+            "Jesus wept.",
+            "John 11:35",
+            "KJV",
+            CardStyle::Minimal,
+            CardFont::Serif,
+        )
+        .unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(card));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (CARD_SIZE, CARD_SIZE));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        // the dark text shows up on the white background
+        assert!(pixels[..info.buffer_size()]
+            .chunks(3)
+            .any(|pixel| pixel[0] < 100));
+    }
+
+    #[test]
+    fn glyph_is_drawn_where_it_should_be() {
+        let font = serif();
+        let mut canvas = Canvas::new(100, 100);
+        canvas.draw_text(&font, "I", 60.0, 20.0, 80.0, [255, 255, 255]);
+
+        let lit = |x: u32, y: u32| canvas.get(x, y) != [0, 0, 0];
+        let width = text_width(&font, "I", 60.0).ceil() as u32;
+        // the stem of the I is inside its advance, above the baseline
+        assert!((20..20 + width).any(|x| lit(x, 60)));
+        // and nothing is drawn below the baseline or past the advance
+        assert!((0..100).all(|x| !lit(x, 85)));
+        assert!((0..100).all(|y| !lit(20 + width + 2, y)));
+        assert!((0..100).all(|y| !lit(18, y)));
+    }
+
+    #[test]
+    fn wrapped_lines_fit() {
+        let font = serif();
+        let text = "In the beginning God created the heaven and the earth. And the earth was \
+                    without form, and void; and darkness was upon the face of the deep.";
+        let lines = wrap(&font, text, 40.0, 400.0);
+        assert!(lines.len() > 1);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(&font, line, 40.0) <= 400.0));
+        assert_eq!(lines.join(" "), text);
+    }
+
+    #[test]
+    fn long_words_shrink_or_fail() {
+        let font = serif();
+        let word = "Mahershalalhashbaz".repeat(3);
+        // the word gets a line of its own, even though it runs over
+        let lines = wrap(&font, &format!("and {} went", word), 40.0, 400.0);
+        assert_eq!(lines, vec!["and", word.as_str(), "went"]);
+
+        // so a card with it has to shrink the text, or give up
+        let word = "Mahershalalhashbaz".repeat(6);
+        assert_eq!(
+            render_card(
+                &word,
+                "Isaiah 8:1",
+                "KJV",
+                CardStyle::Night,
+                CardFont::Serif
+            ),
+            Err(CardError::TooLong)
+        );
+        assert!(render_card(
+            "Mahershalalhashbaz",
+            "Isaiah 8:1",
+            "KJV",
+            CardStyle::Night,
+            CardFont::Serif
+        )
+        .is_ok());
+    }
+}
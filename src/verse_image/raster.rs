@@ -0,0 +1,127 @@
+// Drawing for the verse cards: an RGB canvas with shapes, and text drawn onto it with `ab_glyph`.
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+
+pub type Colour = [u8; 3];
+
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    /// RGB bytes, row by row
+    pub pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 3],
+        }
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, colour: Colour) {
+        if x < self.width && y < self.height {
+            let index = (y as usize * self.width as usize + x as usize) * 3;
+            self.pixels[index..index + 3].copy_from_slice(&colour);
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Colour {
+        let index = (y as usize * self.width as usize + x as usize) * 3;
+        [
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+        ]
+    }
+
+    /// Mixes a colour into a pixel, `alpha` being how much of the new colour shows (0 to 1)
+    pub fn blend(&mut self, x: i32, y: i32, colour: Colour, alpha: f32) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height || alpha <= 0.0 {
+            return;
+        }
+        let current = self.get(x as u32, y as u32);
+        self.set(x as u32, y as u32, mix(current, colour, alpha.min(1.0)));
+    }
+
+    /// Fills a rectangle, with the edges blended where they fall part way through a pixel
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, colour: Colour) {
+        let (x1, y1) = (x + width, y + height);
+        for py in y.floor() as i32..y1.ceil() as i32 {
+            let cover_y = (y1.min(py as f32 + 1.0) - y.max(py as f32)).clamp(0.0, 1.0);
+            for px in x.floor() as i32..x1.ceil() as i32 {
+                let cover_x = (x1.min(px as f32 + 1.0) - x.max(px as f32)).clamp(0.0, 1.0);
+                self.blend(px, py, colour, cover_x * cover_y);
+            }
+        }
+    }
+
+    /// Fills a circle, blending the pixels its edge passes through
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, colour: Colour, alpha: f32) {
+        let reach = radius + 1.0;
+        for py in (cy - reach).floor() as i32..=(cy + reach).ceil() as i32 {
+            for px in (cx - reach).floor() as i32..=(cx + reach).ceil() as i32 {
+                let distance =
+                    ((px as f32 + 0.5 - cx).powi(2) + (py as f32 + 0.5 - cy).powi(2)).sqrt();
+                let cover = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                self.blend(px, py, colour, cover * alpha);
+            }
+        }
+    }
+
+    /// Draws a line of text with its baseline starting at (`x`, `baseline`)
+    pub fn draw_text(
+        &mut self,
+        font: &FontRef,
+        text: &str,
+        size: f32,
+        x: f32,
+        baseline: f32,
+        colour: Colour,
+    ) {
+        let font = font.as_scaled(font_scale(font, size));
+        let mut pen = x;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            let glyph = id.with_scale_and_position(font.scale(), point(pen, baseline));
+            if let Some(outline) = font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                outline.draw(|px, py, coverage| {
+                    self.blend(
+                        bounds.min.x as i32 + px as i32,
+                        bounds.min.y as i32 + py as i32,
+                        colour,
+                        coverage,
+                    )
+                });
+            }
+            pen += font.h_advance(id);
+        }
+    }
+}
+
+/// The scale for text `size` pixels to the em, the way font sizes are usually given
+/// (`ab_glyph` scales by the height from the lowest descender to the highest ascender instead)
+pub fn font_scale(font: &FontRef, size: f32) -> PxScale {
+    match font.units_per_em() {
+        Some(units_per_em) => PxScale::from(size * font.height_unscaled() / units_per_em),
+        None => PxScale::from(size),
+    }
+}
+
+/// The width of a line of text in pixels
+pub fn text_width(font: &FontRef, text: &str, size: f32) -> f32 {
+    let font = font.as_scaled(font_scale(font, size));
+    text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum()
+}
+
+/// Mixes two colours, `amount` being how much of the second shows (0 to 1)
+pub fn mix(from: Colour, to: Colour, amount: f32) -> Colour {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    [
+        channel(from[0], to[0]),
+        channel(from[1], to[1]),
+        channel(from[2], to[2]),
+    ]
+}